@@ -0,0 +1,139 @@
+use super::*;
+use egui::*;
+
+#[cfg(feature = "persistence")]
+use serde::{Deserialize, Serialize};
+
+/// Grid lines closer than this on screen are thinned out.
+const MIN_GRID_SPACING: f32 = 8.0;
+
+/// The grid drawn behind the nodes, which dragged nodes can snap to. It's a
+/// preference of the user rather than part of the graph, so it isn't saved
+/// along with the editor state.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct Grid {
+    /// Distance between the grid lines, at zoom 1.
+    pub size: f32,
+    pub visible: bool,
+    /// Whether dragged nodes snap to the grid.
+    pub snap: bool,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Self {
+            size: 20.0,
+            visible: false,
+            snap: false,
+        }
+    }
+}
+
+/// The side, or center, of the nodes that [`GraphEditorState::align_nodes`] lines up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    /// Centers the nodes on the same vertical line.
+    HorizontalCenter,
+    Right,
+    Top,
+    /// Centers the nodes on the same horizontal line.
+    VerticalCenter,
+    Bottom,
+}
+
+/// The direction [`GraphEditorState::distribute_nodes`] spaces nodes out in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Distribution {
+    Horizontal,
+    Vertical,
+}
+
+impl<NodeData, DataType, ValueType, NodeTemplate, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+{
+    /// Lines up the nodes along the outermost of their sides, or the center of
+    /// their bounding box.
+    pub fn align_nodes(&mut self, nodes: &[NodeId], alignment: Alignment) {
+        let rects: Vec<(NodeId, Rect)> = nodes
+            .iter()
+            .filter_map(|node_id| Some((*node_id, self.node_rect(*node_id)?)))
+            .collect();
+        let Some(bounds) = rects.iter().map(|(_, rect)| *rect).reduce(|a, b| a.union(b)) else {
+            return;
+        };
+        for (node_id, rect) in rects {
+            let min = match alignment {
+                Alignment::Left => pos2(bounds.left(), rect.top()),
+                Alignment::HorizontalCenter => pos2(bounds.center().x - rect.width() / 2.0, rect.top()),
+                Alignment::Right => pos2(bounds.right() - rect.width(), rect.top()),
+                Alignment::Top => pos2(rect.left(), bounds.top()),
+                Alignment::VerticalCenter => pos2(rect.left(), bounds.center().y - rect.height() / 2.0),
+                Alignment::Bottom => pos2(rect.left(), bounds.bottom() - rect.height()),
+            };
+            self.node_positions[node_id] += min - rect.min;
+        }
+    }
+
+    /// Spaces the nodes out so the gaps between them are the same, keeping the
+    /// first and last ones in place. Needs at least three nodes.
+    pub fn distribute_nodes(&mut self, nodes: &[NodeId], distribution: Distribution) {
+        let horizontal = distribution == Distribution::Horizontal;
+        let axis = |vec: Vec2| if horizontal { vec.x } else { vec.y };
+        let mut rects: Vec<(NodeId, Rect)> = nodes
+            .iter()
+            .filter_map(|node_id| Some((*node_id, self.node_rect(*node_id)?)))
+            .collect();
+        if rects.len() < 3 {
+            return;
+        }
+        rects.sort_by(|(_, a), (_, b)| axis(a.center().to_vec2()).total_cmp(&axis(b.center().to_vec2())));
+        let start = rects.iter().map(|(_, rect)| axis(rect.min.to_vec2())).fold(f32::INFINITY, f32::min);
+        let end = rects.iter().map(|(_, rect)| axis(rect.max.to_vec2())).fold(f32::NEG_INFINITY, f32::max);
+        let taken: f32 = rects.iter().map(|(_, rect)| axis(rect.size())).sum();
+        let gap = (end - start - taken) / (rects.len() - 1) as f32;
+        let mut next = start;
+        for (node_id, rect) in rects {
+            let offset = next - axis(rect.min.to_vec2());
+            self.node_positions[node_id] += if horizontal { vec2(offset, 0.0) } else { vec2(0.0, offset) };
+            next += axis(rect.size()) + gap;
+        }
+    }
+
+    /// The grid point closest to `pos`, in the space of the node positions.
+    pub fn snap_to_grid(&self, pos: Pos2) -> Pos2 {
+        let spacing = self.grid.size * self.pan_zoom.zoom;
+        if spacing <= 0.0 {
+            return pos;
+        }
+        let offset = pos - self.grid_origin;
+        self.grid_origin + (offset / spacing).round() * spacing
+    }
+
+    /// Draws the grid lines over the editor, `pan` being the offset of the node
+    /// positions on screen.
+    pub(crate) fn draw_grid(&self, ui: &Ui, pan: Vec2) {
+        if !self.grid.visible || self.grid.size <= 0.0 {
+            return;
+        }
+        let mut spacing = self.grid.size * self.pan_zoom.zoom;
+        while spacing < MIN_GRID_SPACING {
+            spacing *= 2.0;
+        }
+        let rect = ui.clip_rect();
+        let origin = self.grid_origin + pan;
+        let stroke = Stroke::new(1.0, ui.visuals().widgets.noninteractive.bg_stroke.color.gamma_multiply(0.4));
+        let painter = ui.painter();
+        let mut x = origin.x + ((rect.left() - origin.x) / spacing).ceil() * spacing;
+        while x <= rect.right() {
+            painter.vline(x, rect.y_range(), stroke);
+            x += spacing;
+        }
+        let mut y = origin.y + ((rect.top() - origin.y) / spacing).ceil() * spacing;
+        while y <= rect.bottom() {
+            painter.hline(rect.x_range(), y, stroke);
+            y += spacing;
+        }
+    }
+}
@@ -25,8 +25,17 @@ pub type ConnLocations = std::collections::HashMap<InputId, Vec<Pos2>>;
 pub type NodeRects = std::collections::HashMap<NodeId, Rect>;
 
 const DISTANCE_TO_CONNECT: f32 = 10.0;
-const NODE_INITIAL_SIZE: Vec2 = Vec2::new(200.0, 200.0);
+pub(crate) const NODE_INITIAL_SIZE: Vec2 = Vec2::new(200.0, 200.0);
 const NODE_MAX_SIZE: Vec2 = Vec2::new(800.0, 800.0);
+/// Below this zoom level nodes are drawn without their inline widgets.
+const LOW_DETAIL_ZOOM: f32 = 0.5;
+/// Distance between the dots of an animated wire, at zoom 1.
+const WIRE_FLOW_SPACING: f32 = 40.0;
+/// Speed of the dots of an animated wire in points per second, at zoom 1.
+const WIRE_FLOW_SPEED: f32 = 60.0;
+/// Nodes this close to the edge of the editor are still drawn, so ports
+/// sticking out of them stay visible.
+const CULL_MARGIN: f32 = 20.0;
 
 /// Nodes communicate certain events to the parent graph when drawn. There is
 /// one special `User` variant which can be used by users as the return value
@@ -42,12 +51,31 @@ pub enum NodeResponse<UserResponse: UserResponseTrait, NodeData: NodeDataTrait>
         /// If the input isn't a wide port this is always 0 and may be ignored.
         input_hook: usize,
     },
+    /// Emitted when a connection is dropped on a port of an incompatible type.
+    /// The ports are not connected, user code may react to it, for example by
+    /// inserting a node that converts between the two types.
+    ConnectEventRejected {
+        output: OutputId,
+        input: InputId,
+    },
     CreatedNode(NodeId),
     SelectNode(NodeId),
     ChangeSelectionColor(NodeId, Option<egui::Color32>),
     /// As a user of this library, prefer listening for `DeleteNodeFull` which
     /// will also contain the user data for the deleted node.
     DeleteNodeUi(NodeId),
+    /// Opens the node finder to pick a kind of node to replace this one with.
+    ReplaceNodeUi(NodeId),
+    /// Emitted when a node was replaced by a node of another kind, created at
+    /// its position. The connections of ports that match the new node come
+    /// along as `ConnectEventEnded`, and the old node is removed after this
+    /// with the usual `DeleteNodeFull`.
+    NodeReplaced {
+        old: NodeId,
+        new: NodeId,
+        /// Names of the ports whose connections couldn't be moved to the new node.
+        dropped: Vec<String>,
+    },
     /// Emitted when a node is deleted. The node will no longer exist in the
     /// graph after this response is returned from the draw function, but its
     /// contents are passed along with the event.
@@ -59,6 +87,23 @@ pub enum NodeResponse<UserResponse: UserResponseTrait, NodeData: NodeDataTrait>
         output: OutputId,
         input: InputId,
     },
+    /// Emitted when a connection is double clicked. The `position` is the
+    /// clicked point, in the same space as the node positions.
+    WireDoubleClicked {
+        output: OutputId,
+        input: InputId,
+        position: Pos2,
+    },
+    /// Connects a node in between the two ends of a connection, through its
+    /// first input and output that fit them. Emitted when a node without
+    /// connections is dropped on a wire, or picked with "Insert node…". The
+    /// changes to the connections follow as `DisconnectEvent` and
+    /// `ConnectEventEnded`.
+    SplitWire {
+        node: NodeId,
+        output: OutputId,
+        input: InputId,
+    },
     /// Emitted when a node is interacted with, and should be raised
     RaiseNode(NodeId),
     MoveNode {
@@ -72,6 +117,23 @@ pub enum NodeResponse<UserResponse: UserResponseTrait, NodeData: NodeDataTrait>
     User(UserResponse),
 }
 
+/// How a connection is made to stand out, see [`GraphEditorState::wire_highlights`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WireHighlight {
+    /// Drawn as a wide stroke behind the wire.
+    pub glow: Option<Color32>,
+    /// Dots moving along the wire, from the output to the input.
+    pub animated: bool,
+}
+
+/// The context menu opened by right-clicking a connection.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct WireMenu {
+    position: Pos2,
+    output: OutputId,
+    input: InputId,
+}
+
 /// The return value of [`draw_graph_editor`]. This value can be used to make
 /// user code react to specific events that happened when drawing the graph.
 #[derive(Clone, Debug)]
@@ -108,6 +170,102 @@ pub struct GraphNodeWidget<'a, NodeData, DataType, ValueType> {
     pub selected: bool,
     pub selected_color: Option<Color32>,
     pub pan: egui::Vec2,
+    /// Skip the inline widgets and user UI, only drawing the title and ports.
+    pub low_detail: bool,
+    /// Where edited values are listed, see [`GraphEditorState::edits`].
+    pub edits: &'a mut Vec<GraphEdit<NodeData, DataType, ValueType>>,
+}
+
+/// Where a node and its ports were drawn last, relative to the node position
+/// and at the zoom level of that frame. Nodes outside of the viewport aren't
+/// drawn, so their wires are routed using these instead.
+#[derive(Clone, Debug)]
+pub(crate) struct NodeMetrics {
+    zoom: f32,
+    size: Vec2,
+    rect: Rect,
+    ports: Vec<(AnyParameterId, Vec<Vec2>)>,
+    conns: Vec<(InputId, Vec<Vec2>)>,
+    /// Number of ports and connections of the node, the metrics are stale
+    /// once either changes.
+    shape: (usize, usize),
+}
+
+impl NodeMetrics {
+    fn node_shape<NodeData, DataType, ValueType>(
+        graph: &Graph<NodeData, DataType, ValueType>,
+        node_id: NodeId,
+    ) -> (usize, usize) {
+        let node = &graph[node_id];
+        let connections = node
+            .input_ids()
+            .map(|input| graph.connections.get(input).map_or(0, Vec::len))
+            .sum();
+        (node.inputs.len() + node.outputs.len(), connections)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn record<NodeData, DataType, ValueType>(
+        graph: &Graph<NodeData, DataType, ValueType>,
+        node_id: NodeId,
+        origin: Pos2,
+        zoom: f32,
+        size: Vec2,
+        port_locations: &PortLocations,
+        conn_locations: &ConnLocations,
+        node_rects: &NodeRects,
+    ) -> Option<Self> {
+        let relative = |hooks: &Vec<Pos2>| hooks.iter().map(|hook| *hook - origin).collect();
+        let node = &graph[node_id];
+        let params = node
+            .input_ids()
+            .map(AnyParameterId::Input)
+            .chain(node.output_ids().map(AnyParameterId::Output));
+        Some(Self {
+            zoom,
+            size,
+            rect: node_rects.get(&node_id)?.translate(-origin.to_vec2()),
+            ports: params
+                .filter_map(|param| Some((param, relative(port_locations.get(&param)?))))
+                .collect(),
+            conns: node
+                .input_ids()
+                .filter_map(|input| Some((input, relative(conn_locations.get(&input)?))))
+                .collect(),
+            shape: Self::node_shape(graph, node_id),
+        })
+    }
+
+    /// The rect of the node on screen if it was placed at `origin`.
+    pub(crate) fn rect_at(&self, origin: Pos2, zoom: f32) -> Rect {
+        let scale = zoom / self.zoom;
+        Rect::from_min_max(
+            origin + self.rect.min.to_vec2() * scale,
+            origin + self.rect.max.to_vec2() * scale,
+        )
+    }
+
+    /// Fills in the metrics of the node as if it was drawn at `origin`.
+    fn restore(
+        &self,
+        node_id: NodeId,
+        origin: Pos2,
+        zoom: f32,
+        port_locations: &mut PortLocations,
+        conn_locations: &mut ConnLocations,
+        node_rects: &mut NodeRects,
+    ) {
+        let scale = zoom / self.zoom;
+        let absolute =
+            |hooks: &Vec<Vec2>| hooks.iter().map(|hook| origin + *hook * scale).collect();
+        for (param, hooks) in &self.ports {
+            port_locations.insert(*param, absolute(hooks));
+        }
+        for (input, hooks) in &self.conns {
+            conn_locations.insert(*input, absolute(hooks));
+        }
+        node_rects.insert(node_id, self.rect_at(origin, zoom));
+    }
 }
 
 impl<NodeData, DataType, ValueType, NodeTemplate, UserResponse, UserState, CategoryType>
@@ -118,19 +276,21 @@ where
         UserState = UserState,
         DataType = DataType,
         ValueType = ValueType,
-    >,
+    > + Clone,
     UserResponse: UserResponseTrait,
-    ValueType:
-        WidgetValueTrait<Response = UserResponse, UserState = UserState, NodeData = NodeData>,
+    ValueType: WidgetValueTrait<Response = UserResponse, UserState = UserState, NodeData = NodeData>
+        + Clone
+        + PartialEq,
     NodeTemplate: NodeTemplateTrait<
         NodeData = NodeData,
         DataType = DataType,
         ValueType = ValueType,
         UserState = UserState,
         CategoryType = CategoryType,
-    >,
-    DataType: DataTypeTrait<UserState>,
+    > + PartialEq,
+    DataType: DataTypeTrait<UserState> + Clone,
     CategoryType: CategoryTrait,
+    UserState: Clone,
 {
     #[must_use]
     pub fn draw_graph_editor(
@@ -138,8 +298,10 @@ where
         ui: &mut Ui,
         all_kinds: impl NodeTemplateIter<Item = NodeTemplate>,
         user_state: &mut UserState,
+        finder_memory: &mut NodeFinderMemory<NodeTemplate>,
         prepend_responses: Vec<NodeResponse<UserResponse, NodeData>>,
     ) -> GraphResponse<UserResponse, NodeData> {
+        self.edits.clear();
         ui.set_clip_rect(ui.max_rect());
         let clip_rect = ui.clip_rect();
         // Zoom may have never taken place, so ensure we use parent style
@@ -160,7 +322,7 @@ where
         // Render graph zoomed
         let zoomed_style = self.pan_zoom.zoomed_style.clone();
         let graph_response = show_zoomed(ui.style().clone(), zoomed_style, ui, |ui| {
-            self.draw_graph_editor_inside_zoom(ui, all_kinds, user_state, prepend_responses)
+            self.draw_graph_editor_inside_zoom(ui, all_kinds, user_state, finder_memory, prepend_responses)
         });
 
         graph_response
@@ -196,6 +358,12 @@ where
             *node_pos = scaled_local_pos + half_size - self.pan_zoom.pan;
             // This way we can retain pan untouched when zooming :)
         }
+        for frame in self.frames.values_mut() {
+            let local_pos = frame.position.to_vec2() - half_size + self.pan_zoom.pan;
+            frame.position = (local_pos * zoom_delta).to_pos2() + half_size - self.pan_zoom.pan;
+        }
+        let local_pos = self.grid_origin.to_vec2() - half_size + self.pan_zoom.pan;
+        self.grid_origin = (local_pos * zoom_delta).to_pos2() + half_size - self.pan_zoom.pan;
     }
 
     fn draw_graph_editor_inside_zoom(
@@ -203,6 +371,7 @@ where
         ui: &mut Ui,
         all_kinds: impl NodeTemplateIter<Item = NodeTemplate>,
         user_state: &mut UserState,
+        finder_memory: &mut NodeFinderMemory<NodeTemplate>,
         prepend_responses: Vec<NodeResponse<UserResponse, NodeData>>,
     ) -> GraphResponse<UserResponse, NodeData> {
         // This causes the graph editor to use as much free space as it can.
@@ -254,7 +423,54 @@ where
         }
 
         /* Draw nodes */
+        let pan = self.pan_zoom.pan + editor_rect.min.to_vec2();
+        let zoom = self.pan_zoom.zoom;
+        let visible_rect = ui.clip_rect().expand(CULL_MARGIN * zoom);
+        let low_detail = zoom < LOW_DETAIL_ZOOM;
+
+        self.draw_grid(ui, pan);
+
+        // Frames go below the nodes.
+        let (hidden_nodes, hidden_frames) = self.collapsed_contents();
+        let pointer_over_frame_title = self.draw_frames(ui, pan, &hidden_frames);
+
         for node_id in self.node_order.iter().copied() {
+            // Nodes in a collapsed frame aren't drawn, their wires end on the frame instead.
+            if let Some(frame_id) = hidden_nodes.get(node_id) {
+                if let Some(frame_rect) = self.frame_screen_rect(*frame_id, pan) {
+                    attach_ports_to_frame(
+                        &self.graph,
+                        node_id,
+                        frame_rect,
+                        &mut port_locations,
+                        &mut conn_locations,
+                    );
+                }
+                continue;
+            }
+
+            let origin = self.node_positions[node_id] + pan;
+            let size = *self.node_sizes.get(node_id).unwrap_or(&NODE_INITIAL_SIZE);
+
+            // Nodes outside of the viewport are skipped, as long as the last
+            // time they were drawn still describes them.
+            if let Some(metrics) = self.node_metrics.get(node_id) {
+                if metrics.size == size
+                    && metrics.shape == NodeMetrics::node_shape(&self.graph, node_id)
+                    && !metrics.rect_at(origin, zoom).intersects(visible_rect)
+                {
+                    metrics.restore(
+                        node_id,
+                        origin,
+                        zoom,
+                        &mut port_locations,
+                        &mut conn_locations,
+                        &mut node_rects,
+                    );
+                    continue;
+                }
+            }
+
             let responses = GraphNodeWidget {
                 position: self.node_positions.get_mut(node_id).unwrap(),
                 graph: &mut self.graph,
@@ -268,9 +484,24 @@ where
                     .iter()
                     .any(|selected| *selected == node_id),
                 selected_color: self.selection_colors.get(node_id).copied(),
-                pan: self.pan_zoom.pan + editor_rect.min.to_vec2(),
+                pan,
+                low_detail,
+                edits: &mut self.edits,
+            }
+            .show(&self.pan_zoom, ui, user_state, size);
+
+            if let Some(metrics) = NodeMetrics::record(
+                &self.graph,
+                node_id,
+                origin,
+                zoom,
+                size,
+                &port_locations,
+                &conn_locations,
+                &node_rects,
+            ) {
+                self.node_metrics.insert(node_id, metrics);
             }
-            .show(&self.pan_zoom, ui, user_state, *self.node_sizes.get(node_id).unwrap_or(&NODE_INITIAL_SIZE));
 
             // Actions executed later
             delayed_responses.extend(responses);
@@ -283,24 +514,98 @@ where
             if let Some(pos) = node_finder.position {
                 node_finder_area = node_finder_area.current_pos(pos);
             }
+            // Opened by dropping a connection, only list what can be connected to it.
+            let connect_from = node_finder
+                .connect_from
+                .map(|(_, param)| param)
+                .filter(|param| self.graph.any_param_type(*param).is_ok());
+            // Opened from a wire, only list what can be inserted onto it.
+            let insert_on = node_finder
+                .insert_on
+                .filter(|(output, input)| self.graph.connections(*input).contains(output));
+            if let Some((output, input)) = insert_on {
+                if node_finder.connectable_kinds.is_none() {
+                    node_finder.connectable_kinds = Some(insertable_kinds(
+                        all_kinds.all_kinds(),
+                        &self.graph.get_output(output).typ,
+                        &self.graph.get_input(input).typ,
+                        user_state,
+                    ));
+                }
+            }
+            if let Some(param) = connect_from {
+                if node_finder.connectable_kinds.is_none() {
+                    let from_type = self.graph.any_param_type(param).unwrap();
+                    node_finder.connectable_kinds = Some(connectable_kinds(
+                        all_kinds.all_kinds(),
+                        from_type,
+                        matches!(param, AnyParameterId::Output(_)),
+                        user_state,
+                    ));
+                }
+            }
             node_finder_area.show(ui.ctx(), |ui| {
-                if let Some(node_kind) = node_finder.show(ui, all_kinds, user_state) {
+                if let Some(node_kind) = node_finder.show(ui, all_kinds, user_state, finder_memory) {
                     let new_node = self.graph.add_node(
                         node_kind.node_graph_label(user_state),
                         node_kind.user_data(user_state),
                         |graph, node_id| node_kind.build_node(graph, user_state, node_id),
                     );
-                    self.node_positions.insert(
-                        new_node,
-                        node_finder.position.unwrap_or(cursor_pos)
+                    let replaced = node_finder.replace.filter(|old| self.graph.nodes.contains_key(*old));
+                    let mut position = match replaced.and_then(|old| self.node_positions.get(old)) {
+                        Some(old_position) => *old_position,
+                        None => node_finder.position.unwrap_or(cursor_pos)
                             - self.pan_zoom.pan
                             - editor_rect.min.to_vec2(),
-                    );
+                    };
+                    // Keep the new node on the side of the dropped connection it gets connected to.
+                    if let Some(AnyParameterId::Input(_)) = connect_from {
+                        position.x -= NODE_INITIAL_SIZE.x;
+                    }
+                    // and centered on the wire it is inserted onto.
+                    if insert_on.is_some() {
+                        position -= NODE_INITIAL_SIZE / 2.0;
+                    }
+                    self.node_positions.insert(new_node, position);
                     self.node_sizes.insert(new_node, NODE_INITIAL_SIZE);
                     self.node_order.push(new_node);
+                    self.edits.push(GraphEdit::NodeAdded(new_node));
 
                     should_close_node_finder = true;
                     delayed_responses.push(NodeResponse::CreatedNode(new_node));
+                    if let Some(old) = replaced {
+                        let (connections, dropped) = carry_over_ports(&mut self.graph, old, new_node);
+                        delayed_responses.extend(connections.into_iter().map(|(output, input, input_hook)| {
+                            NodeResponse::ConnectEventEnded { output, input, input_hook }
+                        }));
+                        delayed_responses.push(NodeResponse::NodeReplaced { old, new: new_node, dropped });
+                        delayed_responses.push(NodeResponse::DeleteNodeUi(old));
+                        if self.selected_nodes.contains(&old) {
+                            self.selected_nodes.push(new_node);
+                        }
+                    }
+                    if let Some((output, input)) = insert_on {
+                        delayed_responses.push(NodeResponse::SplitWire { node: new_node, output, input });
+                    }
+                    if let Some(param) = connect_from {
+                        let new_param = find_connectable_port::<_, _, _, UserState>(
+                            &self.graph,
+                            new_node,
+                            self.graph.any_param_type(param).unwrap(),
+                            matches!(param, AnyParameterId::Output(_)),
+                        );
+                        match (param, new_param) {
+                            (AnyParameterId::Output(output), Some(AnyParameterId::Input(input)))
+                            | (AnyParameterId::Input(input), Some(AnyParameterId::Output(output))) => {
+                                delayed_responses.push(NodeResponse::ConnectEventEnded {
+                                    output,
+                                    input,
+                                    input_hook: self.graph.connections(input).len(),
+                                });
+                            }
+                            _ => {}
+                        }
+                    }
                 }
                 let finder_rect = ui.min_rect();
                 // If the cursor is not in the main editor, check if the cursor is in the finder
@@ -393,30 +698,144 @@ where
             draw_connection(
                 &self.pan_zoom,
                 ui.painter(),
-                src_pos,
-                dst_pos,
+                connection_points(&self.pan_zoom, src_pos, dst_pos),
                 connection_color,
             );
         }
 
+        // This locks the context, so don't hold on to it for too long.
+        let mouse = &ui.ctx().input(|i| i.pointer.clone());
+
+        // A double click on the background may land on a connection.
+        let cursor_over_node = node_rects.values().any(|rect| rect.contains(cursor_pos));
+        let mut wire_double_click = ui
+            .input(|i| i.pointer.button_double_clicked(PointerButton::Primary))
+            .then_some(cursor_pos)
+            .filter(|_| cursor_in_editor && !cursor_over_node);
+        // So may a right click, which opens the wire menu instead of the finder.
+        let mut wire_secondary_click = mouse
+            .secondary_released()
+            .then_some(cursor_pos)
+            .filter(|_| cursor_in_editor && !cursor_in_finder && !pointer_over_frame_title && !cursor_over_node);
+        let mut wire_menu_opened = false;
+
+        // A node without connections that is dragged over a wire is inserted
+        // onto it when dropped.
+        let dropping_node = self
+            .dragged_node
+            .map(|(node_id, _)| node_id)
+            .filter(|_| mouse.primary_down() || mouse.primary_released())
+            .filter(|node_id| !self.selected_nodes.contains(node_id) || self.selected_nodes.len() == 1)
+            .filter(|node_id| self.graph.nodes.contains_key(*node_id) && !has_connections(&self.graph, *node_id))
+            .and_then(|node_id| node_rects.get(&node_id).map(|rect| (node_id, *rect)));
+        let mut drop_target = None;
+
         // draw existing connections
         for (input, outputs) in self.graph.iter_connection_groups() {
             for (hook_n, &output) in outputs.iter().enumerate() {
+                // outputs can't be wide yet so this is fine.
+                let src_pos = port_locations
+                    .get(&AnyParameterId::Output(output))
+                    .and_then(|hooks| hooks.first());
+                let dst_pos = conn_locations
+                    .get(&input)
+                    .and_then(|hooks| hooks.get(hook_n));
+                let (Some(&src_pos), Some(&dst_pos)) = (src_pos, dst_pos) else {
+                    continue;
+                };
+                // Wires within a collapsed frame are hidden along with their nodes.
+                let src_frame = hidden_nodes.get(self.graph.get_output(output).node);
+                if src_frame.is_some() && src_frame == hidden_nodes.get(self.graph.get_input(input).node) {
+                    continue;
+                }
+                let points = connection_points(&self.pan_zoom, src_pos, dst_pos);
+                if !Rect::from_points(&points).intersects(visible_rect) {
+                    continue;
+                }
                 let port_type = self
                     .graph
                     .any_param_type(AnyParameterId::Output(output))
                     .unwrap();
                 let connection_color = port_type.data_type_color(user_state);
-                // outputs can't be wide yet so this is fine.
-                let src_pos = port_locations[&AnyParameterId::Output(output)][0];
-                let dst_pos = conn_locations[&input][hook_n];
-                draw_connection(
-                    &self.pan_zoom,
-                    ui.painter(),
-                    src_pos,
-                    dst_pos,
-                    connection_color,
-                );
+                let highlight = self.wire_highlights.get(&(output, input)).copied().unwrap_or_default();
+                if let Some(glow) = highlight.glow {
+                    let stroke = Stroke::new(9.0 * self.pan_zoom.zoom, glow);
+                    ui.painter()
+                        .add(CubicBezierShape::from_points_stroke(points, false, Color32::TRANSPARENT, stroke));
+                }
+                draw_connection(&self.pan_zoom, ui.painter(), points, connection_color);
+                if highlight.animated {
+                    draw_wire_flow(&self.pan_zoom, ui, points, connection_color);
+                }
+
+                if let Some(click_pos) = wire_double_click {
+                    if connection_hit(&self.pan_zoom, points, click_pos) {
+                        wire_double_click = None;
+                        delayed_responses.push(NodeResponse::WireDoubleClicked {
+                            output,
+                            input,
+                            position: click_pos - self.pan_zoom.pan - editor_rect.min.to_vec2(),
+                        });
+                    }
+                }
+                if let Some(click_pos) = wire_secondary_click {
+                    if connection_hit(&self.pan_zoom, points, click_pos) {
+                        wire_secondary_click = None;
+                        wire_menu_opened = true;
+                        self.wire_menu = Some(WireMenu { position: click_pos, output, input });
+                    }
+                }
+                if let Some((node_id, node_rect)) = dropping_node {
+                    if drop_target.is_none()
+                        && connection_crosses(points, node_rect)
+                        && wire_ports::<_, _, _, UserState>(
+                            &self.graph,
+                            node_id,
+                            port_type,
+                            &self.graph.get_input(input).typ,
+                        )
+                        .is_some()
+                    {
+                        drop_target = Some((node_id, output, input, points));
+                    }
+                }
+            }
+        }
+
+        if let Some((node, output, input, points)) = drop_target {
+            if mouse.primary_released() {
+                delayed_responses.push(NodeResponse::SplitWire { node, output, input });
+            } else {
+                // show where the node will go
+                let highlight = Stroke::new(7.0 * self.pan_zoom.zoom, ui.visuals().selection.bg_fill);
+                ui.painter()
+                    .add(CubicBezierShape::from_points_stroke(points, false, Color32::TRANSPARENT, highlight));
+            }
+        }
+        if !mouse.primary_down() {
+            self.dragged_node = None;
+        }
+
+        if wire_menu_opened {
+            self.node_finder = None;
+        }
+        if let Some(menu) = self.wire_menu {
+            let area = Area::new(Id::new("wire_menu"))
+                .order(Order::Foreground)
+                .fixed_pos(menu.position)
+                .show(ui.ctx(), |ui| {
+                    egui::Frame::menu(ui.style()).show(ui, |ui| {
+                        if ui.button("Insert node…").clicked() {
+                            self.node_finder =
+                                Some(NodeFinder::new_for_wire(menu.position, menu.output, menu.input));
+                            self.wire_menu = None;
+                        }
+                    });
+                });
+            let clicked_elsewhere = mouse.any_pressed() && !area.response.rect.contains(cursor_pos);
+            let wire_gone = !self.graph.connections(menu.input).contains(&menu.output);
+            if clicked_elsewhere || wire_gone || ui.input(|i| i.key_pressed(Key::Escape)) {
+                self.wire_menu = None;
             }
         }
 
@@ -430,15 +849,59 @@ where
             match response {
                 NodeResponse::ConnectEventStarted(node_id, port) => {
                     self.connection_in_progress = Some((*node_id, *port));
+                    self.connection_is_new = true;
                 }
                 NodeResponse::ConnectEventEnded {
                     output,
                     input,
                     input_hook,
-                } => self.graph.add_connection(*output, *input, *input_hook),
+                } => {
+                    self.log_connections(*input);
+                    self.graph.add_connection(*output, *input, *input_hook);
+                }
+                NodeResponse::ConnectEventRejected { .. } => {
+                    // Handled by the user code, if at all.
+                }
                 NodeResponse::CreatedNode(_) => {
                     //Convenience NodeResponse for users
                 }
+                NodeResponse::WireDoubleClicked { .. } => {
+                    // Handled by the user code, if at all.
+                }
+                NodeResponse::ReplaceNodeUi(node_id) => {
+                    self.node_finder = Some(NodeFinder::new_for_replace(cursor_pos, *node_id));
+                }
+                NodeResponse::NodeReplaced { .. } => {
+                    // Handled by the user code, if at all.
+                }
+                NodeResponse::SplitWire { node, output, input } => {
+                    let ports = match (self.graph.try_get_output(*output), self.graph.try_get_input(*input)) {
+                        (Some(from), Some(to)) => {
+                            wire_ports::<_, _, _, UserState>(&self.graph, *node, &from.typ, &to.typ)
+                        }
+                        _ => None,
+                    };
+                    let hook = self.graph.connections(*input).iter().position(|o| o == output);
+                    if let (Some((node_input, node_output)), Some(hook)) = (ports, hook) {
+                        self.log_connections(*input);
+                        self.log_connections(node_input);
+                        self.graph.remove_connection(*input, *output);
+                        extra_responses.push(NodeResponse::DisconnectEvent { output: *output, input: *input });
+                        let node_input_hook = self.graph.connections(node_input).len();
+                        self.graph.add_connection(*output, node_input, node_input_hook);
+                        extra_responses.push(NodeResponse::ConnectEventEnded {
+                            output: *output,
+                            input: node_input,
+                            input_hook: node_input_hook,
+                        });
+                        self.graph.add_connection(node_output, *input, hook);
+                        extra_responses.push(NodeResponse::ConnectEventEnded {
+                            output: node_output,
+                            input: *input,
+                            input_hook: hook,
+                        });
+                    }
+                }
                 NodeResponse::ChangeSelectionColor(node_id, color ) => {
                     if let Some(color) = color {
                         self.selection_colors.insert(*node_id, *color);
@@ -448,9 +911,11 @@ where
                 }
                 NodeResponse::SelectNode(node_id) => {
                     self.selected_nodes = Vec::from([*node_id]);
+                    self.selected_frames.clear();
                     self.selection_colors.remove(*node_id);
                 }
                 NodeResponse::DeleteNodeUi(node_id) => {
+                    self.log_removal(*node_id);
                     let (node, disc_events) = self.graph.remove_node(*node_id);
 
                     // Pass the disconnection responses first so user code can perform cleanup
@@ -468,6 +933,7 @@ where
                     });
                     self.node_positions.remove(*node_id);
                     self.node_sizes.remove(*node_id);
+                    self.node_metrics.remove(*node_id);
                     // Make sure to not leave references to old nodes hanging
                     self.selected_nodes.retain(|id| *id != *node_id);
                     self.node_order.retain(|id| *id != *node_id);
@@ -475,9 +941,11 @@ where
                 }
                 NodeResponse::DisconnectEvent { input, output } => {
                     let other_node = self.graph.get_output(*output).node;
+                    self.log_connections(*input);
                     self.graph.remove_connection(*input, *output);
                     self.connection_in_progress =
                         Some((other_node, AnyParameterId::Output(*output)));
+                    self.connection_is_new = false;
                 }
                 NodeResponse::RaiseNode(node_id) => {
                     let old_pos = self
@@ -489,12 +957,39 @@ where
                     self.node_order.push(*node_id);
                 }
                 NodeResponse::MoveNode { node, drag_delta } => {
-                    self.node_positions[*node] += *drag_delta;
+                    let unsnapped = match self.dragged_node {
+                        Some((dragged, position)) if dragged == *node => position,
+                        _ => self.node_positions[*node],
+                    } + *drag_delta;
+                    self.dragged_node = Some((*node, unsnapped));
+                    // the rest of the selection moves by as much as the snapped node
+                    let drag_delta = if self.grid.snap {
+                        self.snap_to_grid(unsnapped) - self.node_positions[*node]
+                    } else {
+                        *drag_delta
+                    };
+                    let dragged_selection = self.selected_nodes.contains(node);
+                    if dragged_selection {
+                        self.log_moves(self.selected_nodes.clone());
+                        if !self.selected_frames.is_empty() {
+                            self.log_frames();
+                        }
+                    } else {
+                        self.log_moves([*node]);
+                    }
+                    self.node_positions[*node] += drag_delta;
                     // Handle multi-node selection movement
-                    if self.selected_nodes.contains(node) && self.selected_nodes.len() > 1 {
+                    if dragged_selection && self.selected_nodes.len() > 1 {
                         for n in self.selected_nodes.iter().copied() {
                             if n != *node {
-                                self.node_positions[n] += *drag_delta;
+                                self.node_positions[n] += drag_delta;
+                            }
+                        }
+                    }
+                    if dragged_selection {
+                        for frame_id in self.selected_frames.iter() {
+                            if let Some(frame) = self.frames.get_mut(*frame_id) {
+                                frame.position += drag_delta;
                             }
                         }
                     }
@@ -538,6 +1033,16 @@ where
                     }
                 })
                 .collect();
+            self.selected_frames = self
+                .frames
+                .keys()
+                .filter(|frame_id| {
+                    !hidden_frames.contains_key(*frame_id)
+                        && self
+                            .frame_screen_rect(*frame_id, pan)
+                            .is_some_and(|rect| selection_rect.contains_rect(rect))
+                })
+                .collect();
         }
 
         // Push any responses that were generated during response handling.
@@ -547,14 +1052,32 @@ where
 
         /* Mouse input handling */
 
-        // This locks the context, so don't hold on to it for too long.
-        let mouse = &ui.ctx().input(|i| i.pointer.clone());
-
-        if mouse.any_released() && self.connection_in_progress.is_some() {
-            self.connection_in_progress = None;
+        if mouse.any_released() {
+            if let Some((node_id, param)) = self.connection_in_progress.take() {
+                // A new connection dropped on the background opens the finder to create a node for it,
+                // one pulled off an input is just disconnected.
+                let connected = delayed_responses
+                    .iter()
+                    .any(|response| matches!(response, NodeResponse::ConnectEventEnded { .. }));
+                if mouse.primary_released()
+                    && self.connection_is_new
+                    && !connected
+                    && !cursor_over_node
+                    && cursor_in_editor
+                {
+                    self.node_finder = Some(NodeFinder::new_for_connection(cursor_pos, node_id, param));
+                }
+            }
         }
 
-        if mouse.secondary_released() && cursor_in_editor && !cursor_in_finder {
+        // nodes have their own context menu
+        if mouse.secondary_released()
+            && cursor_in_editor
+            && !cursor_in_finder
+            && !pointer_over_frame_title
+            && !cursor_over_node
+            && !wire_menu_opened
+        {
             self.node_finder = Some(NodeFinder::new_at(cursor_pos));
         }
         if ui.ctx().input(|i| i.key_pressed(Key::Escape)) {
@@ -573,6 +1096,7 @@ where
         // *or* if the the mouse clicks off the ui
         if click_on_background || (mouse.any_click() && !cursor_in_editor) {
             self.selected_nodes = Vec::new();
+            self.selected_frames = Vec::new();
             self.node_finder = None;
         }
 
@@ -594,24 +1118,120 @@ where
     }
 }
 
-fn draw_connection(
-    pan_zoom: &PanZoom,
-    painter: &Painter,
-    src_pos: Pos2,
-    dst_pos: Pos2,
-    color: Color32,
+/// Places the ports of a node hidden in a collapsed frame on the sides of the
+/// frame's title bar, so its connections stay visible.
+fn attach_ports_to_frame<NodeData, DataType, ValueType>(
+    graph: &Graph<NodeData, DataType, ValueType>,
+    node_id: NodeId,
+    frame_rect: Rect,
+    port_locations: &mut PortLocations,
+    conn_locations: &mut ConnLocations,
 ) {
-    let connection_stroke = egui::Stroke {
-        width: 3.0 * pan_zoom.zoom,
-        color,
-    };
+    for input in graph[node_id].input_ids() {
+        port_locations.insert(input.into(), vec![frame_rect.left_center()]);
+        conn_locations.insert(input, vec![frame_rect.left_center(); graph.connections(input).len()]);
+    }
+    for output in graph[node_id].output_ids() {
+        port_locations.insert(output.into(), vec![frame_rect.right_center()]);
+    }
+}
 
+/// Control points of the curve drawn for a connection. The curve never leaves
+/// their bounding rect, which is used to skip connections outside the viewport.
+fn connection_points(pan_zoom: &PanZoom, src_pos: Pos2, dst_pos: Pos2) -> [Pos2; 4] {
     let control_scale = ((dst_pos.x - src_pos.x) * pan_zoom.zoom / 2.0).max(30.0 * pan_zoom.zoom);
     let src_control = src_pos + Vec2::X * control_scale;
     let dst_control = dst_pos - Vec2::X * control_scale;
+    [src_pos, src_control, dst_control, dst_pos]
+}
 
+/// Whether `pos` is close enough to the curve drawn for a connection to click it.
+fn connection_hit(pan_zoom: &PanZoom, points: [Pos2; 4], pos: Pos2) -> bool {
+    let max_distance = DISTANCE_TO_CONNECT * pan_zoom.zoom;
+    if !Rect::from_points(&points).expand(max_distance).contains(pos) {
+        return false;
+    }
+    let bezier = CubicBezierShape::from_points_stroke(
+        points,
+        false,
+        Color32::TRANSPARENT,
+        Stroke::NONE,
+    );
+    bezier
+        .flatten(Some(0.5))
+        .windows(2)
+        .any(|segment| pos.distance(closest_on_segment(segment[0], segment[1], pos)) < max_distance)
+}
+
+/// Dots moving along the wire, towards the input.
+fn draw_wire_flow(pan_zoom: &PanZoom, ui: &Ui, points: [Pos2; 4], color: Color32) {
     let bezier = CubicBezierShape::from_points_stroke(
-        [src_pos, src_control, dst_control, dst_pos],
+        points,
+        false,
+        Color32::TRANSPARENT,
+        Stroke::NONE,
+    );
+    let length: f32 = bezier
+        .flatten(Some(1.0))
+        .windows(2)
+        .map(|segment| segment[0].distance(segment[1]))
+        .sum();
+    let spacing = WIRE_FLOW_SPACING * pan_zoom.zoom;
+    if length < spacing {
+        return;
+    }
+    let dots = (length / spacing).floor();
+    let offset = (ui.input(|i| i.time) as f32 * WIRE_FLOW_SPEED * pan_zoom.zoom / spacing).fract();
+    let dot_color = color.lerp_to_gamma(Color32::WHITE, 0.5);
+    for dot in 0..dots as usize {
+        let t = (dot as f32 + offset) / dots;
+        ui.painter()
+            .circle_filled(bezier.sample(t), 2.5 * pan_zoom.zoom, dot_color);
+    }
+    ui.ctx().request_repaint();
+}
+
+/// Whether the wire passes through the rect.
+fn connection_crosses(points: [Pos2; 4], rect: Rect) -> bool {
+    if !Rect::from_points(&points).intersects(rect) {
+        return false;
+    }
+    let bezier = CubicBezierShape::from_points_stroke(
+        points,
+        false,
+        Color32::TRANSPARENT,
+        Stroke::NONE,
+    );
+    bezier.flatten(Some(0.5)).into_iter().any(|point| rect.contains(point))
+}
+
+fn has_connections<NodeData, DataType, ValueType>(
+    graph: &Graph<NodeData, DataType, ValueType>,
+    node_id: NodeId,
+) -> bool {
+    graph
+        .iter_connections()
+        .any(|(input, output)| graph.get_input(input).node == node_id || graph.get_output(output).node == node_id)
+}
+
+fn closest_on_segment(start: Pos2, end: Pos2, pos: Pos2) -> Pos2 {
+    let direction = end - start;
+    let length_sq = direction.length_sq();
+    if length_sq == 0.0 {
+        return start;
+    }
+    let t = ((pos - start).dot(direction) / length_sq).clamp(0.0, 1.0);
+    start + direction * t
+}
+
+fn draw_connection(pan_zoom: &PanZoom, painter: &Painter, points: [Pos2; 4], color: Color32) {
+    let connection_stroke = egui::Stroke {
+        width: 3.0 * pan_zoom.zoom,
+        color,
+    };
+
+    let bezier = CubicBezierShape::from_points_stroke(
+        points,
         false,
         Color32::TRANSPARENT,
         connection_stroke,
@@ -633,9 +1253,10 @@ where
         ValueType = ValueType,
     >,
     UserResponse: UserResponseTrait,
-    ValueType:
-        WidgetValueTrait<Response = UserResponse, UserState = UserState, NodeData = NodeData>,
-    DataType: DataTypeTrait<UserState>,
+    ValueType: WidgetValueTrait<Response = UserResponse, UserState = UserState, NodeData = NodeData>
+        + Clone
+        + PartialEq,
+    DataType: DataTypeTrait<UserState> + Clone,
 {
     pub fn show(
         self,
@@ -717,32 +1338,46 @@ where
         let mut input_port_heights = vec![];
         let mut output_port_heights = vec![];
 
+        // Knots are drawn like low detail nodes without a title, with their ports on one row.
+        let knot = self.graph[self.node_id].user_data.is_knot();
+        let low_detail = self.low_detail || knot;
+
         child_ui.vertical(|ui| {
-            ui.horizontal(|ui| {
-                ui.add(Label::new(
-                    RichText::new(&self.graph[self.node_id].label)
-                        .text_style(TextStyle::Button)
-                        .color(text_color),
-                ));
-                responses.extend(self.graph[self.node_id].user_data.top_bar_ui(
-                    ui,
-                    self.node_id,
-                    self.graph,
-                    user_state,
-                ));
-                ui.add_space(4.0 * pan_zoom.zoom); // margin
-                ui.add_space(8.0 * pan_zoom.zoom); // The size of the little cross icon
-            });
-            ui.add_space(margin.y);
+            if !knot {
+                ui.horizontal(|ui| {
+                    ui.add(Label::new(
+                        RichText::new(&self.graph[self.node_id].label)
+                            .text_style(TextStyle::Button)
+                            .color(text_color),
+                    ));
+                    if !low_detail {
+                        responses.extend(self.graph[self.node_id].user_data.top_bar_ui(
+                            ui,
+                            self.node_id,
+                            self.graph,
+                            user_state,
+                        ));
+                    }
+                    ui.add_space(4.0 * pan_zoom.zoom); // margin
+                    ui.add_space(8.0 * pan_zoom.zoom); // The size of the little cross icon
+                });
+                ui.add_space(margin.y);
+            }
             title_height = ui.min_size().y;
 
+            // In low detail every port takes up a plain row instead of its widgets.
+            let row_width = if knot { 0.0 } else { ui.available_width() };
+            let low_detail_row = vec2(row_width, ui.spacing().interact_size.y);
+
             // First pass: Draw the inner fields. Compute port heights
             let inputs = self.graph[self.node_id].inputs.clone();
             for (param_name, param_id) in inputs {
                 if self.graph[param_id].shown_inline {
                     let height_before = ui.min_rect().bottom();
 
-                    if self.graph[param_id].max_connections == NonZeroU32::new(1) {
+                    if low_detail {
+                        ui.allocate_space(low_detail_row);
+                    } else if self.graph[param_id].max_connections == NonZeroU32::new(1) {
                         // NOTE: We want to pass the `user_data` to
                         // `value_widget`, but we can't since that would require
                         // borrowing the graph twice. Here, we make the
@@ -751,6 +1386,8 @@ where
                         // dummy value. This requires `ValueType` to implement
                         // Default, but results in a totally safe alternative.
                         let mut value = std::mem::take(&mut self.graph[param_id].value);
+                        // values only change along with some input, frames without any skip the comparison.
+                        let before = ui.input(|i| !i.events.is_empty()).then(|| value.clone());
 
                         if !self.graph.connections(param_id).is_empty() {
                             let node_responses = value.value_widget_connected(
@@ -776,6 +1413,11 @@ where
                             responses.extend(node_responses.into_iter().map(NodeResponse::User));
                         }
 
+                        if let Some(before) = before.filter(|before| *before != value) {
+                            self.edits.push(GraphEdit::InputValue {
+                                before: InputParam { value: before, ..self.graph[param_id].clone() },
+                            });
+                        }
                         self.graph[param_id].value = value;
                     } else {
                         ui.label(param_name);
@@ -800,13 +1442,15 @@ where
                     //     ui.add_space(missing_space);
                     // }
 
-                    self.graph[self.node_id].user_data.separator(
-                        ui,
-                        self.node_id,
-                        AnyParameterId::Input(param_id),
-                        self.graph,
-                        user_state,
-                    );
+                    if !low_detail {
+                        self.graph[self.node_id].user_data.separator(
+                            ui,
+                            self.node_id,
+                            AnyParameterId::Input(param_id),
+                            self.graph,
+                            user_state,
+                        );
+                    }
 
                     let height_after = ui.min_rect().bottom();
 
@@ -816,54 +1460,66 @@ where
 
             let outputs = self.graph[self.node_id].outputs.clone();
             for (param_name, param_id) in outputs {
+                if let (true, Some(input_height)) = (knot, input_port_heights.first()) {
+                    output_port_heights.push(*input_height);
+                    continue;
+                }
                 let height_before = ui.min_rect().bottom();
-                responses.extend(
-                    self.graph[self.node_id]
-                        .user_data
-                        .output_ui(ui, self.node_id, self.graph, user_state, &param_name)
-                        .into_iter(),
-                );
+                if low_detail {
+                    ui.allocate_space(low_detail_row);
+                } else {
+                    responses.extend(
+                        self.graph[self.node_id]
+                            .user_data
+                            .output_ui(ui, self.node_id, self.graph, user_state, &param_name)
+                            .into_iter(),
+                    );
 
-                self.graph[self.node_id].user_data.separator(
-                    ui,
-                    self.node_id,
-                    AnyParameterId::Output(param_id),
-                    self.graph,
-                    user_state,
-                );
+                    self.graph[self.node_id].user_data.separator(
+                        ui,
+                        self.node_id,
+                        AnyParameterId::Output(param_id),
+                        self.graph,
+                        user_state,
+                    );
+                }
 
                 let height_after = ui.min_rect().bottom();
                 output_port_heights.push((height_before + height_after) / 2.0);
             }
 
-            responses.extend(self.graph[self.node_id].user_data.bottom_ui(
-                ui,
-                self.node_id,
-                self.graph,
-                user_state,
-            ));
+            if !low_detail {
+                responses.extend(self.graph[self.node_id].user_data.bottom_ui(
+                    ui,
+                    self.node_id,
+                    self.graph,
+                    user_state,
+                ));
+            }
         });
 
         // Add a small draggable interaction rect to the right of the node
-        let drag_box_width = 6.0 * pan_zoom.zoom;
-        let drag_box_rect = Rect::from_min_max(
-            pos2(interaction_rect.right() - drag_box_width, interaction_rect.top() + title_height),
-            pos2(interaction_rect.right(), interaction_rect.bottom() + title_height),
-        );
-        let drag_box_resp = ui.allocate_rect(drag_box_rect, Sense::click_and_drag())
-            .on_hover_cursor(
-                egui::CursorIcon::ResizeHorizontal,
+        if !knot {
+            let drag_box_width = 6.0 * pan_zoom.zoom;
+            let drag_box_rect = Rect::from_min_max(
+                pos2(interaction_rect.right() - drag_box_width, interaction_rect.top() + title_height),
+                pos2(interaction_rect.right(), interaction_rect.bottom() + title_height),
             );
+            let drag_box_resp = ui.allocate_rect(drag_box_rect, Sense::click_and_drag())
+                .on_hover_cursor(
+                    egui::CursorIcon::ResizeHorizontal,
+                );
 
-        if drag_box_resp.dragged() {
-            let drag_delta = drag_box_resp.drag_delta();
-            if drag_delta.length_sq() > 0.0 {
-                // Convert screen space drag delta to zoom-adjusted space
-                let zoom_adjusted_delta = drag_delta.x / pan_zoom.zoom;
-                responses.push(NodeResponse::ResizeNode {
-                    node: self.node_id,
-                    resize_delta: Vec2::new(zoom_adjusted_delta, 0.0),
-                });
+            if drag_box_resp.dragged() {
+                let drag_delta = drag_box_resp.drag_delta();
+                if drag_delta.length_sq() > 0.0 {
+                    // Convert screen space drag delta to zoom-adjusted space
+                    let zoom_adjusted_delta = drag_delta.x / pan_zoom.zoom;
+                    responses.push(NodeResponse::ResizeNode {
+                        node: self.node_id,
+                        resize_delta: Vec2::new(zoom_adjusted_delta, 0.0),
+                    });
+                }
             }
         }
 
@@ -1027,15 +1683,21 @@ where
                     // param_type is the port that is being hovered over
                     // Allow incompatible connections if one of the ports was defined as supporting any type by user.
                     let origin_type = graph.any_param_type(origin_param).unwrap();
-                    if (port_type.allow_any_type() || origin_type.allow_any_type() || origin_type == port_type)
-                        && close_enough {
+                    let compatible = port_type.allow_any_type()
+                        || origin_type.allow_any_type()
+                        || origin_type == port_type;
+                    if close_enough {
                         match (param_id, origin_param) {
                             (AnyParameterId::Input(input), AnyParameterId::Output(output))
                             | (AnyParameterId::Output(output), AnyParameterId::Input(input)) => {
                                 let input_hook =
                                     nearest_hook.unwrap_or(graph.connections(input).len());
 
-                                if ui.input(|i| i.pointer.any_released()) {
+                                if !compatible {
+                                    if ui.input(|i| i.pointer.any_released()) {
+                                        responses.push(NodeResponse::ConnectEventRejected { output, input });
+                                    }
+                                } else if ui.input(|i| i.pointer.any_released()) {
                                     responses.push(NodeResponse::ConnectEventEnded {
                                         output,
                                         input,
@@ -1122,7 +1784,11 @@ where
         // does not support drawing rectangles with asymmetrical round corners.
 
         let (shape, outline) = {
-            let rounding_radius = 4.0 * pan_zoom.zoom;
+            let rounding_radius = if knot {
+                outer_rect.size().min_elem() * 0.5
+            } else {
+                4.0 * pan_zoom.zoom
+            };
             let corner_radius = CornerRadiusF32::same(rounding_radius);
 
             let titlebar_height = title_height + margin.y;
@@ -1172,7 +1838,11 @@ where
                 brush: None,
             });
 
-            let node_rect = titlebar_rect.union(body_rect).union(bottom_body_rect);
+            let node_rect = if knot {
+                outer_rect
+            } else {
+                titlebar_rect.union(body_rect).union(bottom_body_rect)
+            };
             let mut outline = if self.selected {
                 Shape::Rect(RectShape {
                     rect: node_rect.expand(2.0 * pan_zoom.zoom),
@@ -1205,7 +1875,22 @@ where
             // Take note of the node rect, so the editor can use it later to compute intersections.
             self.node_rects.insert(self.node_id, node_rect);
 
-            (Shape::Vec(vec![titlebar, body, bottom_body]), outline)
+            let shape = if knot {
+                Shape::Rect(RectShape {
+                    rect: node_rect,
+                    corner_radius: corner_radius.into(),
+                    fill: background_color,
+                    stroke: Stroke::NONE,
+                    stroke_kind: StrokeKind::Middle,
+                    blur_width: 0.0,
+                    round_to_pixels: None,
+                    brush: None,
+                })
+            } else {
+                Shape::Vec(vec![titlebar, body, bottom_body])
+            };
+
+            (shape, outline)
         };
 
         ui.painter().set(background_shape, shape);
@@ -1220,9 +1905,17 @@ where
             user_state,
         );
 
-        if can_delete && Self::close_button(pan_zoom, ui, outer_rect).clicked() {
+        if can_delete && !knot && Self::close_button(pan_zoom, ui, outer_rect).clicked() {
             responses.push(NodeResponse::DeleteNodeUi(self.node_id));
         };
+        if can_delete && !knot {
+            window_response.context_menu(|ui| {
+                if ui.button("Replace with…").clicked() {
+                    responses.push(NodeResponse::ReplaceNodeUi(self.node_id));
+                    ui.close();
+                }
+            });
+        }
 
         // Movement
         let drag_delta = window_response.drag_delta();
@@ -0,0 +1,106 @@
+use super::*;
+use crate::geometry::{Pos2, Vec2};
+
+/// A change the editor made to the graph while it was drawn, along with what
+/// it replaced. User code can go through these to keep track of edits, for
+/// example to undo them, without comparing the whole graph every frame.
+///
+/// Changes are listed in the order they were made. A thing changed several
+/// times in a frame may be listed several times, the first entry holds what it
+/// was before the frame.
+#[derive(Clone, Debug)]
+pub enum GraphEdit<NodeData, DataType, ValueType> {
+    /// A node was created from the node finder.
+    NodeAdded(NodeId),
+    /// A node was deleted. The changes to its connections are listed before it.
+    NodeRemoved(Box<RemovedNode<NodeData, DataType, ValueType>>),
+    /// The connections of an input were changed, from `before`.
+    Connections { input: InputId, before: Vec<OutputId> },
+    /// A node was moved from `from`.
+    NodeMoved { node: NodeId, from: Pos2 },
+    /// The value of an input was edited, `before` is the input with its old value.
+    InputValue { before: InputParam<DataType, ValueType> },
+    /// The frames were changed, from `before`.
+    Frames { before: SlotMap<FrameId, Frame> },
+}
+
+/// Everything a deleted node left behind.
+#[derive(Clone, Debug)]
+pub struct RemovedNode<NodeData, DataType, ValueType> {
+    pub node: Node<NodeData>,
+    /// The input params, in the order of `node.inputs`.
+    pub inputs: Vec<InputParam<DataType, ValueType>>,
+    /// The output params, in the order of `node.outputs`.
+    pub outputs: Vec<OutputParam<DataType>>,
+    pub position: Pos2,
+    pub size: Option<Vec2>,
+    /// Place of the node in the drawing order.
+    pub order: usize,
+}
+
+impl<NodeData, DataType, ValueType, NodeTemplate, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+{
+    /// Lists the connections of an input, before changing them.
+    pub(crate) fn log_connections(&mut self, input: InputId) {
+        let before = self.graph.connections(input);
+        self.edits.push(GraphEdit::Connections { input, before });
+    }
+
+    /// Lists the positions of nodes, before moving them.
+    pub(crate) fn log_moves(&mut self, nodes: impl IntoIterator<Item = NodeId>) {
+        for node in nodes {
+            if let Some(from) = self.node_positions.get(node) {
+                self.edits.push(GraphEdit::NodeMoved { node, from: *from });
+            }
+        }
+    }
+
+    /// Lists the frames, before changing them.
+    pub(crate) fn log_frames(&mut self) {
+        self.edits.push(GraphEdit::Frames { before: self.frames.clone() });
+    }
+}
+
+impl<NodeData, DataType, ValueType, NodeTemplate, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+where
+    NodeData: Clone,
+    DataType: Clone,
+    ValueType: Clone,
+{
+    /// Lists a node along with its connections, before removing it.
+    pub(crate) fn log_removal(&mut self, node_id: NodeId) {
+        let Some(node) = self.graph.nodes.get(node_id) else {
+            return;
+        };
+        let outputs: Vec<OutputId> = node.output_ids().collect();
+        let connected: Vec<InputId> = self
+            .graph
+            .connections
+            .iter()
+            .filter(|(input, sources)| {
+                self.graph.get_input(*input).node == node_id
+                    || sources.iter().any(|output| outputs.contains(output))
+            })
+            .map(|(input, _)| input)
+            .collect();
+        for input in connected {
+            self.log_connections(input);
+        }
+        let node = &self.graph[node_id];
+        let removed = RemovedNode {
+            node: node.clone(),
+            inputs: node.input_ids().map(|id| self.graph.get_input(id).clone()).collect(),
+            outputs: node.output_ids().map(|id| self.graph.get_output(id).clone()).collect(),
+            position: self.node_positions.get(node_id).copied().unwrap_or_default(),
+            size: self.node_sizes.get(node_id).copied(),
+            order: self
+                .node_order
+                .iter()
+                .position(|id| *id == node_id)
+                .unwrap_or(self.node_order.len()),
+        };
+        self.edits.push(GraphEdit::NodeRemoved(Box::new(removed)));
+    }
+}
@@ -0,0 +1,367 @@
+#[cfg(feature = "gui")]
+use super::*;
+use crate::geometry::{Pos2, Vec2};
+#[cfg(feature = "persistence")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "gui")]
+use egui::*;
+#[cfg(feature = "gui")]
+use std::collections::HashSet;
+
+/// A titled box drawn behind the nodes, used to group them visually. Nodes
+/// inside of it move along when it's dragged and are hidden when it's collapsed.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct Frame {
+    pub title: String,
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub description: String,
+    /// Color of the title bar, in sRGB.
+    pub color: [u8; 3],
+    /// Top left corner, in the same space as the node positions.
+    pub position: Pos2,
+    /// Size when expanded, not scaled by the zoom, like the node sizes.
+    pub size: Vec2,
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub collapsed: bool,
+}
+
+impl Frame {
+    pub const DEFAULT_COLOR: [u8; 3] = [70, 110, 160];
+    pub const MIN_SIZE: Vec2 = Vec2 { x: 160.0, y: 80.0 };
+
+    pub fn new(title: impl Into<String>, position: Pos2, size: Vec2) -> Self {
+        Self {
+            title: title.into(),
+            description: String::new(),
+            color: Self::DEFAULT_COLOR,
+            position,
+            size,
+            collapsed: false,
+        }
+    }
+}
+
+/// Height of the title bar, before zooming.
+#[cfg(feature = "gui")]
+const FRAME_TITLE_HEIGHT: f32 = 28.0;
+/// Space left around the nodes when a frame is created around them.
+#[cfg(feature = "gui")]
+const FRAME_PADDING: f32 = 20.0;
+#[cfg(feature = "gui")]
+const FRAME_RESIZE_HANDLE: f32 = 12.0;
+
+/// What gets moved along while a frame is dragged, decided when the drag starts.
+#[cfg(feature = "gui")]
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FrameDrag {
+    nodes: Vec<NodeId>,
+    frames: Vec<FrameId>,
+}
+
+#[cfg(feature = "gui")]
+impl<NodeData, DataType, ValueType, NodeTemplate, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+{
+    /// Adds a frame around the given nodes, or in the middle of the editor if
+    /// there are none. The new frame is selected along with its contents.
+    pub fn add_frame(&mut self, title: impl Into<String>, nodes: &[NodeId]) -> FrameId {
+        let zoom = self.pan_zoom.zoom;
+        let bounds = nodes
+            .iter()
+            .filter_map(|node_id| self.node_rect(*node_id))
+            .reduce(Rect::union);
+        let (position, size) = match bounds {
+            Some(bounds) => {
+                let rect = Rect::from_min_max(
+                    bounds.min - vec2(FRAME_PADDING, FRAME_PADDING + FRAME_TITLE_HEIGHT) * zoom,
+                    bounds.max + Vec2::splat(FRAME_PADDING) * zoom,
+                );
+                (rect.min, (rect.size() / zoom).max(Frame::MIN_SIZE))
+            }
+            None => {
+                let size = vec2(400.0, 300.0);
+                let center = self.pan_zoom.clip_rect.size() / 2.0 - self.pan_zoom.pan;
+                (center.to_pos2() - size * zoom / 2.0, size)
+            }
+        };
+        let frame_id = self.frames.insert(Frame::new(title, position, size));
+        self.selected_frames = vec![frame_id];
+        self.selected_nodes = self.frame_contents(frame_id);
+        frame_id
+    }
+
+    /// Removes a frame, the nodes inside of it are left untouched.
+    pub fn remove_frame(&mut self, frame_id: FrameId) -> Option<Frame> {
+        self.selected_frames.retain(|id| *id != frame_id);
+        self.frames.remove(frame_id)
+    }
+
+    /// The rect covered by a frame when expanded, in the same space as the node positions.
+    pub fn frame_rect(&self, frame_id: FrameId) -> Option<Rect> {
+        let frame = self.frames.get(frame_id)?;
+        Some(Rect::from_min_size(frame.position, frame.size * self.pan_zoom.zoom))
+    }
+
+    /// Nodes whose center lies inside of the frame, collapsed or not.
+    pub fn frame_contents(&self, frame_id: FrameId) -> Vec<NodeId> {
+        let Some(frame_rect) = self.frame_rect(frame_id) else {
+            return Vec::new();
+        };
+        self.node_order
+            .iter()
+            .copied()
+            .filter(|node_id| {
+                self.node_rect(*node_id)
+                    .is_some_and(|rect| frame_rect.contains(rect.center()))
+            })
+            .collect()
+    }
+
+    /// Frames lying entirely inside of another frame.
+    fn nested_frames(&self, frame_id: FrameId) -> Vec<FrameId> {
+        let Some(frame_rect) = self.frame_rect(frame_id) else {
+            return Vec::new();
+        };
+        self.frames
+            .keys()
+            .filter(|other| {
+                *other != frame_id
+                    && self
+                        .frame_rect(*other)
+                        .is_some_and(|rect| frame_rect.contains_rect(rect))
+            })
+            .collect()
+    }
+
+    /// Nodes and frames hidden inside of collapsed frames, with the collapsed
+    /// frame hiding them. Collapsed frames inside of collapsed frames defer to
+    /// the outer one.
+    pub(crate) fn collapsed_contents(&self) -> (SecondaryMap<NodeId, FrameId>, SecondaryMap<FrameId, FrameId>) {
+        let mut hidden_nodes = SecondaryMap::new();
+        let mut hidden_frames = SecondaryMap::new();
+        for (frame_id, frame) in self.frames.iter() {
+            if !frame.collapsed {
+                continue;
+            }
+            for nested in self.nested_frames(frame_id) {
+                hidden_frames.insert(nested, frame_id);
+            }
+        }
+        for (frame_id, frame) in self.frames.iter() {
+            if !frame.collapsed || hidden_frames.contains_key(frame_id) {
+                continue;
+            }
+            for node_id in self.frame_contents(frame_id) {
+                hidden_nodes.insert(node_id, frame_id);
+            }
+        }
+        (hidden_nodes, hidden_frames)
+    }
+
+    /// Nodes inside of collapsed frames, which aren't drawn.
+    pub fn hidden_nodes(&self) -> HashSet<NodeId> {
+        self.collapsed_contents().0.keys().collect()
+    }
+
+    /// The rect a frame is drawn in, on screen. Only the title bar when collapsed.
+    pub(crate) fn frame_screen_rect(&self, frame_id: FrameId, pan: Vec2) -> Option<Rect> {
+        let frame = self.frames.get(frame_id)?;
+        let mut rect = self.frame_rect(frame_id)?.translate(pan);
+        if frame.collapsed {
+            rect.set_height(FRAME_TITLE_HEIGHT * self.pan_zoom.zoom);
+        }
+        Some(rect)
+    }
+
+    /// Draws the frames and handles their interaction. Must be called before
+    /// the nodes are drawn, so they end up on top.
+    ///
+    /// Returns whether the pointer is over a frame title bar, where a right
+    /// click opens the frame menu instead of the node finder.
+    pub(crate) fn draw_frames(
+        &mut self,
+        ui: &mut Ui,
+        pan: Vec2,
+        hidden_frames: &SecondaryMap<FrameId, FrameId>,
+    ) -> bool {
+        let zoom = self.pan_zoom.zoom;
+        let title_height = FRAME_TITLE_HEIGHT * zoom;
+        let text_color = ui.visuals().strong_text_color();
+        let mut pointer_over_title = false;
+        let mut removed = None;
+
+        // Larger frames first, so nested frames stay on top of the frames around them.
+        let mut frame_ids: Vec<FrameId> = self
+            .frames
+            .keys()
+            .filter(|frame_id| !hidden_frames.contains_key(*frame_id))
+            .collect();
+        frame_ids.sort_by(|a, b| {
+            let area = |frame_id: &FrameId| self.frames[*frame_id].size.x * self.frames[*frame_id].size.y;
+            area(b).total_cmp(&area(a))
+        });
+
+        for frame_id in frame_ids {
+            let Some(rect) = self.frame_screen_rect(frame_id, pan) else {
+                continue;
+            };
+            let selected = self.selected_frames.contains(&frame_id);
+            let frame = &self.frames[frame_id];
+            let [r, g, b] = frame.color;
+            let color = Color32::from_rgb(r, g, b);
+            let title_rect = Rect::from_min_size(rect.min, vec2(rect.width(), title_height));
+            let corner_radius = 4.0 * zoom;
+
+            let painter = ui.painter();
+            if !frame.collapsed {
+                painter.rect_filled(rect, corner_radius, color.gamma_multiply(0.15));
+            }
+            painter.rect_filled(title_rect, corner_radius, color.gamma_multiply(0.8));
+            let stroke = if selected {
+                Stroke::new(2.0 * zoom, Color32::from_rgb(184, 241, 255))
+            } else {
+                Stroke::new(1.0 * zoom, color)
+            };
+            painter.rect_stroke(rect, corner_radius, stroke, StrokeKind::Outside);
+
+            let arrow_rect = Rect::from_min_size(title_rect.min, Vec2::splat(title_height));
+            let arrow = arrow_rect.shrink(arrow_rect.width() * 0.35);
+            let points = if frame.collapsed {
+                vec![arrow.left_top(), arrow.right_center(), arrow.left_bottom()]
+            } else {
+                vec![arrow.left_top(), arrow.right_top(), arrow.center_bottom()]
+            };
+            painter.add(Shape::convex_polygon(points, text_color, Stroke::NONE));
+            let title_painter = painter.with_clip_rect(title_rect.intersect(painter.clip_rect()));
+            title_painter.text(
+                pos2(arrow_rect.right(), title_rect.center().y),
+                Align2::LEFT_CENTER,
+                &frame.title,
+                TextStyle::Button.resolve(ui.style()),
+                text_color,
+            );
+            if !frame.collapsed && !frame.description.is_empty() {
+                let body = Rect::from_min_max(pos2(rect.left(), title_rect.bottom()), rect.max)
+                    .shrink(6.0 * zoom);
+                let galley = painter.layout(
+                    frame.description.clone(),
+                    TextStyle::Body.resolve(ui.style()),
+                    ui.visuals().text_color(),
+                    body.width(),
+                );
+                painter
+                    .with_clip_rect(body.intersect(painter.clip_rect()))
+                    .galley(body.min, galley, ui.visuals().text_color());
+            }
+
+            // --- Interaction ---
+            let id = ui.id().with(("frame", frame_id));
+            let arrow_response = ui.interact(arrow_rect, id.with("collapse"), Sense::click());
+            let title_response = ui.interact(
+                title_rect.with_min_x(arrow_rect.right()),
+                id.with("title"),
+                Sense::click_and_drag(),
+            );
+            pointer_over_title |= title_response.hovered() || arrow_response.hovered();
+
+            if arrow_response.clicked() {
+                self.log_frames();
+                let frame = &mut self.frames[frame_id];
+                frame.collapsed = !frame.collapsed;
+            }
+            if title_response.clicked_by(PointerButton::Primary) {
+                self.selected_frames = vec![frame_id];
+                self.selected_nodes = self.frame_contents(frame_id);
+            }
+            if title_response.drag_started_by(PointerButton::Primary) {
+                let mut drag = FrameDrag {
+                    nodes: self.frame_contents(frame_id),
+                    frames: self.nested_frames(frame_id),
+                };
+                drag.frames.push(frame_id);
+                // Dragging a selected frame brings along the rest of the selection.
+                if selected {
+                    drag.nodes.extend(self.selected_nodes.iter().copied());
+                    drag.frames.extend(self.selected_frames.iter().copied());
+                }
+                drag.nodes.sort();
+                drag.nodes.dedup();
+                drag.frames.sort();
+                drag.frames.dedup();
+                self.frame_drag = Some(drag);
+            }
+            if title_response.dragged_by(PointerButton::Primary) {
+                let delta = title_response.drag_delta();
+                if let Some(drag) = self.frame_drag.clone() {
+                    self.log_moves(drag.nodes.iter().copied());
+                    self.log_frames();
+                    for node_id in &drag.nodes {
+                        if let Some(position) = self.node_positions.get_mut(*node_id) {
+                            *position += delta;
+                        }
+                    }
+                    for frame_id in &drag.frames {
+                        if let Some(frame) = self.frames.get_mut(*frame_id) {
+                            frame.position += delta;
+                        }
+                    }
+                }
+            }
+            if title_response.drag_stopped() {
+                self.frame_drag = None;
+            }
+            title_response.context_menu(|ui| {
+                let before = self.frames[frame_id].clone();
+                let frame = &mut self.frames[frame_id];
+                ui.horizontal(|ui| {
+                    ui.label("Title");
+                    ui.text_edit_singleline(&mut frame.title);
+                });
+                ui.label("Description");
+                ui.text_edit_multiline(&mut frame.description);
+                ui.horizontal(|ui| {
+                    ui.label("Color");
+                    ui.color_edit_button_srgb(&mut frame.color);
+                });
+                ui.checkbox(&mut frame.collapsed, "Collapsed");
+                ui.separator();
+                if ui.button("Delete frame").clicked() {
+                    removed = Some(frame_id);
+                    ui.close();
+                }
+                if self.frames[frame_id] != before {
+                    let mut frames = self.frames.clone();
+                    frames[frame_id] = before;
+                    self.edits.push(GraphEdit::Frames { before: frames });
+                }
+            });
+
+            if !self.frames[frame_id].collapsed {
+                let handle_rect = Rect::from_min_max(
+                    rect.max - Vec2::splat(FRAME_RESIZE_HANDLE * zoom),
+                    rect.max,
+                );
+                let handle_response = ui
+                    .interact(handle_rect, id.with("resize"), Sense::drag())
+                    .on_hover_cursor(CursorIcon::ResizeNwSe);
+                ui.painter().line_segment(
+                    [handle_rect.left_bottom(), handle_rect.right_top()],
+                    Stroke::new(1.0 * zoom, color),
+                );
+                if handle_response.dragged() {
+                    self.log_frames();
+                    let frame = &mut self.frames[frame_id];
+                    frame.size = (frame.size + handle_response.drag_delta() / zoom).max(Frame::MIN_SIZE);
+                }
+            }
+        }
+
+        if let Some(frame_id) = removed {
+            self.log_frames();
+            self.remove_frame(frame_id);
+        }
+        pointer_over_title
+    }
+}
@@ -0,0 +1,69 @@
+//! With the `gui` feature these are egui's own `Pos2` and `Vec2`. Without it,
+//! they're minimal replacements with the same fields and serialized form, so
+//! graphs saved by either build can be loaded by the other.
+
+#[cfg(feature = "gui")]
+pub use egui::{pos2, vec2, Pos2, Vec2};
+
+#[cfg(not(feature = "gui"))]
+pub use plain::*;
+
+#[cfg(not(feature = "gui"))]
+mod plain {
+    use std::ops::{Add, Sub};
+    #[cfg(feature = "persistence")]
+    use serde::{Deserialize, Serialize};
+
+    /// A position on the graph canvas.
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    #[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+    pub struct Pos2 {
+        pub x: f32,
+        pub y: f32,
+    }
+
+    /// A size or an offset on the graph canvas.
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    #[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+    pub struct Vec2 {
+        pub x: f32,
+        pub y: f32,
+    }
+
+    impl Pos2 {
+        pub const ZERO: Self = Self { x: 0.0, y: 0.0 };
+    }
+
+    impl Vec2 {
+        pub const ZERO: Self = Self { x: 0.0, y: 0.0 };
+    }
+
+    pub const fn pos2(x: f32, y: f32) -> Pos2 {
+        Pos2 { x, y }
+    }
+
+    pub const fn vec2(x: f32, y: f32) -> Vec2 {
+        Vec2 { x, y }
+    }
+
+    impl Add<Vec2> for Pos2 {
+        type Output = Pos2;
+        fn add(self, rhs: Vec2) -> Pos2 {
+            pos2(self.x + rhs.x, self.y + rhs.y)
+        }
+    }
+
+    impl Sub<Vec2> for Pos2 {
+        type Output = Pos2;
+        fn sub(self, rhs: Vec2) -> Pos2 {
+            pos2(self.x - rhs.x, self.y - rhs.y)
+        }
+    }
+
+    impl Sub for Pos2 {
+        type Output = Vec2;
+        fn sub(self, rhs: Pos2) -> Vec2 {
+            vec2(self.x - rhs.x, self.y - rhs.y)
+        }
+    }
+}
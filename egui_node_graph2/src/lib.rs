@@ -27,25 +27,55 @@ pub use error::*;
 pub mod structs;
 pub use structs::*;
 
+/// Positions and sizes of nodes, egui's types when the `gui` feature is enabled
+pub mod geometry;
+
+/// Frames drawn behind the nodes to group them
+pub mod frame;
+pub use frame::*;
+
 /// The main struct in the library, contains all the necessary state to draw the
 /// UI graph
 pub mod ui_state;
 pub use ui_state::*;
 
 /// The node finder is a tiny widget allowing to create new node types
+#[cfg(feature = "gui")]
 pub mod node_finder;
+#[cfg(feature = "gui")]
 pub use node_finder::*;
 
 /// The inner details of the egui implementation. Most egui code lives here.
+#[cfg(feature = "gui")]
 pub mod editor_ui;
+#[cfg(feature = "gui")]
 pub use editor_ui::*;
 
+/// Lining up nodes, and the grid they snap to
+#[cfg(feature = "gui")]
+pub mod arrange;
+#[cfg(feature = "gui")]
+pub use arrange::*;
+
+/// Changes made by the editor, for user code keeping track of them
+#[cfg(feature = "gui")]
+pub mod edits;
+#[cfg(feature = "gui")]
+pub use edits::*;
+
+/// An overview of the whole graph, used to move around large graphs.
+#[cfg(feature = "gui")]
+pub mod minimap;
+
 /// Several traits that must be implemented by the user to customize the
 /// behavior of this library.
 pub mod traits;
 pub use traits::*;
 
+#[cfg(feature = "gui")]
 mod utils;
 
+#[cfg(feature = "gui")]
 mod color_hex_utils;
+#[cfg(feature = "gui")]
 mod scale;
@@ -0,0 +1,95 @@
+use crate::color_hex_utils::*;
+use crate::utils::ColorUtils;
+
+use super::*;
+use egui::*;
+
+const MINIMAP_SIZE: Vec2 = Vec2::new(220.0, 160.0);
+/// Distance between the minimap and the corner of the editor.
+const MINIMAP_MARGIN: f32 = 10.0;
+
+impl<NodeData, DataType, ValueType, NodeTemplate, UserState>
+    GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserState>
+where
+    NodeData: NodeDataTrait<DataType = DataType, ValueType = ValueType, UserState = UserState>,
+{
+    /// Draws an overview of the whole graph in the bottom right corner of the
+    /// editor, with the visible part outlined. Clicking or dragging inside of it
+    /// moves the viewport there.
+    ///
+    /// Call this after [`Self::draw_graph_editor`], with the same `ui`.
+    pub fn draw_minimap(&mut self, ui: &Ui, user_state: &mut UserState) {
+        let editor_rect = ui.max_rect();
+        let map_rect = Rect::from_min_size(
+            editor_rect.right_bottom() - MINIMAP_SIZE - Vec2::splat(MINIMAP_MARGIN),
+            MINIMAP_SIZE,
+        )
+        .intersect(editor_rect);
+        // Registered after the nodes, so clicks land on the minimap instead of the nodes below it.
+        let response = ui.interact(map_rect, ui.id().with("minimap"), Sense::click_and_drag());
+
+        // Node rects in the same space as `node_positions`, i.e. without the pan.
+        let node_rects: Vec<(NodeId, Rect)> = self
+            .node_order
+            .iter()
+            .filter_map(|node_id| Some((*node_id, self.node_rect(*node_id)?)))
+            .collect();
+        // The viewport is left out of the bounds, otherwise the map would shift
+        // under the cursor while dragging it.
+        let Some(bounds) = node_rects.iter().map(|(_, rect)| *rect).reduce(Rect::union) else {
+            return;
+        };
+        let bounds = bounds.expand(bounds.size().max_elem() * 0.05);
+        let scale = (map_rect.width() / bounds.width()).min(map_rect.height() / bounds.height());
+        let to_map = |pos: Pos2| map_rect.center() + (pos - bounds.center()) * scale;
+        let from_map = |pos: Pos2| bounds.center() + (pos - map_rect.center()) / scale;
+
+        if response.is_pointer_button_down_on() {
+            if let Some(pointer_pos) = response.interact_pointer_pos() {
+                let target = from_map(pointer_pos);
+                self.pan_zoom.pan = editor_rect.size() / 2.0 - target.to_vec2();
+            }
+        }
+
+        let dark_mode = ui.visuals().dark_mode;
+        let default_color = if dark_mode {
+            color_from_hex("#3f3f3f").unwrap()
+        } else {
+            color_from_hex("#ffffff").unwrap()
+        }
+        .lighten(0.8);
+        let outline_color = if dark_mode {
+            Color32::WHITE
+        } else {
+            Color32::BLACK
+        };
+
+        let painter = ui.painter_at(map_rect);
+        painter.rect(
+            map_rect,
+            4.0,
+            ui.visuals().extreme_bg_color.gamma_multiply(0.85),
+            ui.visuals().widgets.noninteractive.bg_stroke,
+            StrokeKind::Inside,
+        );
+        for (node_id, rect) in node_rects {
+            let color = self.graph[node_id]
+                .user_data
+                .titlebar_color(ui, node_id, &self.graph, user_state)
+                .unwrap_or(default_color);
+            let rect = Rect::from_min_max(to_map(rect.min), to_map(rect.max));
+            painter.rect_filled(rect, 1.0, color);
+            if self.selected_nodes.contains(&node_id) {
+                painter.rect_stroke(rect, 1.0, Stroke::new(1.0, outline_color), StrokeKind::Outside);
+            }
+        }
+
+        let viewport = Rect::from_min_size((-self.pan_zoom.pan).to_pos2(), editor_rect.size());
+        painter.rect_stroke(
+            Rect::from_min_max(to_map(viewport.min), to_map(viewport.max)),
+            0.0,
+            Stroke::new(1.5, outline_color),
+            StrokeKind::Inside,
+        );
+    }
+}
@@ -1,9 +1,17 @@
 use std::{collections::BTreeMap, marker::PhantomData};
 
-use crate::{color_hex_utils::*, CategoryTrait, NodeTemplateIter, NodeTemplateTrait};
+use crate::{
+    color_hex_utils::*, AnyParameterId, CategoryTrait, DataTypeTrait, Graph, InputId,
+    InputParamKind, NodeId, NodeTemplateIter, NodeTemplateTrait, OutputId,
+};
 
 use egui::*;
 
+/// How many node kinds the "Recent" section of the finder keeps.
+const MAX_RECENT_KINDS: usize = 8;
+/// Width of the pane showing the description of the node kind under the cursor.
+const PREVIEW_WIDTH: f32 = 240.0;
+
 #[derive(Clone)]
 #[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeFinder<NodeTemplate> {
@@ -11,13 +19,161 @@ pub struct NodeFinder<NodeTemplate> {
     /// Reset every frame. When set, the node finder will be moved at that position
     pub position: Option<Pos2>,
     pub just_spawned: bool,
+    /// The port of a connection that was dropped on the background to open the
+    /// finder. Only node kinds that can be connected to it are listed, and the
+    /// created node gets connected to it.
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub connect_from: Option<(NodeId, AnyParameterId)>,
+    /// Node kinds that can be connected to `connect_from`, found when the
+    /// finder is first shown.
+    #[cfg_attr(feature = "persistence", serde(skip, default = "Option::default"))]
+    pub(crate) connectable_kinds: Option<Vec<NodeTemplate>>,
+    /// The node the picked kind replaces, when opened with "Replace with…".
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub replace: Option<NodeId>,
+    /// The connection the picked kind is inserted onto, when opened with
+    /// "Insert node…".
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub insert_on: Option<(OutputId, InputId)>,
+    /// Index of the result picked by Enter, moved with the arrow keys.
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    highlighted: usize,
     _phantom: PhantomData<NodeTemplate>,
 }
 
+/// Node kinds the user pinned or created lately, shown at the top of the
+/// finder while nothing is searched for. It outlives the finder and is shared
+/// between graphs, storing it between runs is left to the application.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeFinderMemory<NodeTemplate> {
+    pub favorites: Vec<NodeTemplate>,
+    /// Most recent first.
+    pub recent: Vec<NodeTemplate>,
+}
+
+impl<NodeTemplate> Default for NodeFinderMemory<NodeTemplate> {
+    fn default() -> Self {
+        Self {
+            favorites: Vec::new(),
+            recent: Vec::new(),
+        }
+    }
+}
+
+impl<NodeTemplate: PartialEq + Clone> NodeFinderMemory<NodeTemplate> {
+    pub fn is_favorite(&self, kind: &NodeTemplate) -> bool {
+        self.favorites.contains(kind)
+    }
+
+    pub fn toggle_favorite(&mut self, kind: &NodeTemplate) {
+        if self.is_favorite(kind) {
+            self.favorites.retain(|favorite| favorite != kind);
+        } else {
+            self.favorites.push(kind.clone());
+        }
+    }
+
+    pub fn add_recent(&mut self, kind: &NodeTemplate) {
+        self.recent.retain(|recent| recent != kind);
+        self.recent.insert(0, kind.clone());
+        self.recent.truncate(MAX_RECENT_KINDS);
+    }
+}
+
+/// Scores how well `query` matches `text`, ignoring case. Every character of
+/// the query has to appear in the text in order. Matches of the whole query,
+/// at the start of the text or of its words, and runs of consecutive
+/// characters score higher. Returns `None` if the text doesn't match.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let word_start = |idx: usize| {
+        idx == 0
+            || !chars[idx - 1].is_alphanumeric()
+            || (chars[idx].is_uppercase() && chars[idx - 1].is_lowercase())
+    };
+    // shorter texts are closer matches
+    let length_penalty = (chars.len() / 8) as i32;
+    if let Some(start) = lower.windows(query.len()).position(|window| window == query.as_slice()) {
+        let bonus = match start {
+            0 => 60,
+            _ if word_start(start) => 40,
+            _ => 20,
+        };
+        return Some(query.len() as i32 * 6 + bonus - length_penalty);
+    }
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for c in query {
+        let found = next + lower[next..].iter().position(|other| *other == c)?;
+        score += 1;
+        if word_start(found) {
+            score += 6;
+        }
+        match previous {
+            Some(previous) if previous + 1 == found => score += 4,
+            Some(previous) => score -= (found - previous - 1).min(4) as i32,
+            None => {}
+        }
+        previous = Some(found);
+        next = found + 1;
+    }
+    Some(score - length_penalty)
+}
+
+// One node kind as listed in the finder.
+struct FinderEntry<'a, NodeTemplate> {
+    kind: &'a NodeTemplate,
+    label: String,
+    description: Option<String>,
+}
+
+impl<'a, NodeTemplate: NodeTemplateTrait> FinderEntry<'a, NodeTemplate> {
+    fn new(kind: &'a NodeTemplate, user_state: &mut NodeTemplate::UserState) -> Self {
+        Self {
+            kind,
+            label: kind.node_finder_label(user_state).to_string(),
+            description: kind.node_finder_description(user_state).map(|text| text.into_owned()),
+        }
+    }
+}
+
+// What happened to a row of the finder.
+#[derive(Default)]
+struct RowResponse {
+    clicked: bool,
+    hovered: bool,
+    star_clicked: bool,
+}
+
+fn show_row(ui: &mut Ui, label: &str, is_favorite: bool, highlighted: bool, scroll_to: bool) -> RowResponse {
+    ui.horizontal(|ui| {
+        let star = ui
+            .add(Button::new(if is_favorite { "★" } else { "☆" }).frame(false).small())
+            .on_hover_text(if is_favorite { "Remove from favorites" } else { "Add to favorites" });
+        let response = ui.selectable_label(highlighted, label);
+        if scroll_to {
+            response.scroll_to_me(None);
+        }
+        RowResponse {
+            clicked: response.clicked(),
+            hovered: response.hovered(),
+            star_clicked: star.clicked(),
+        }
+    })
+    .inner
+}
+
 impl<NodeTemplate, NodeData, UserState, CategoryType> NodeFinder<NodeTemplate>
 where
     NodeTemplate:
-        NodeTemplateTrait<NodeData = NodeData, UserState = UserState, CategoryType = CategoryType>,
+        NodeTemplateTrait<NodeData = NodeData, UserState = UserState, CategoryType = CategoryType> + PartialEq,
     CategoryType: CategoryTrait,
 {
     pub fn new_at(pos: Pos2) -> Self {
@@ -25,18 +181,54 @@ where
             query: "".into(),
             position: Some(pos),
             just_spawned: true,
+            connect_from: None,
+            connectable_kinds: None,
+            replace: None,
+            insert_on: None,
+            highlighted: 0,
             _phantom: Default::default(),
         }
     }
 
+    /// Opens the finder for a connection dragged from `param` of `node_id`
+    /// that was released over the background.
+    pub fn new_for_connection(pos: Pos2, node_id: NodeId, param: AnyParameterId) -> Self {
+        NodeFinder {
+            connect_from: Some((node_id, param)),
+            ..Self::new_at(pos)
+        }
+    }
+
+    /// Opens the finder to pick a kind of node that replaces `node_id`.
+    pub fn new_for_replace(pos: Pos2, node_id: NodeId) -> Self {
+        NodeFinder {
+            replace: Some(node_id),
+            ..Self::new_at(pos)
+        }
+    }
+
+    /// Opens the finder to pick a kind of node that is inserted onto the
+    /// connection between `output` and `input`.
+    pub fn new_for_wire(pos: Pos2, output: OutputId, input: InputId) -> Self {
+        NodeFinder {
+            insert_on: Some((output, input)),
+            ..Self::new_at(pos)
+        }
+    }
+
     /// Shows the node selector panel with a search bar. Returns whether a node
     /// archetype was selected and, in that case, the finder should be hidden on
     /// the next frame.
+    ///
+    /// With an empty query, the favorites and recently created kinds of
+    /// `memory` are listed above the categories. Otherwise every kind matching
+    /// the query is listed, best matches first.
     pub fn show(
         &mut self,
         ui: &mut Ui,
         all_kinds: impl NodeTemplateIter<Item = NodeTemplate>,
         user_state: &mut UserState,
+        memory: &mut NodeFinderMemory<NodeTemplate>,
     ) -> Option<NodeTemplate> {
         let background_color;
         let text_color;
@@ -59,98 +251,528 @@ where
         let mut submitted_archetype = None;
         frame.show(ui, |ui| {
             ui.vertical(|ui| {
-                let resp = ui.text_edit_singleline(&mut self.query);
+                let resp = ui.add(TextEdit::singleline(&mut self.query).hint_text("Search nodes..."));
                 if self.just_spawned {
                     resp.request_focus();
                     self.just_spawned = false;
                 }
                 let update_open = resp.changed();
-
-                let mut query_submit = resp.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+                if update_open {
+                    self.highlighted = 0;
+                }
+                let (up, down) = ui.input(|i| (i.key_pressed(Key::ArrowUp), i.key_pressed(Key::ArrowDown)));
+                let query_submit = resp.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+                if query_submit {
+                    // keep typing after picking with Enter didn't work out, e.g. nothing matched
+                    resp.request_focus();
+                }
 
                 let max_height = ui.input(|i| i.screen_rect.height() * 0.5);
                 let scroll_area_width = resp.rect.width() - 30.0;
 
-                let all_kinds = all_kinds.all_kinds();
-                let mut categories: BTreeMap<String, Vec<&NodeTemplate>> = Default::default();
-                let mut orphan_kinds = Vec::new();
-
-                for kind in &all_kinds {
-                    let kind_categories = kind.node_finder_categories(user_state);
+                let all_kinds = match &self.connectable_kinds {
+                    Some(kinds) => kinds.clone(),
+                    None => all_kinds.all_kinds(),
+                };
+                // The rows Up, Down and Enter go through: the results of the query,
+                // or the favorites and recent kinds while there is none.
+                let terms: Vec<&str> = self.query.split_whitespace().collect();
+                let mut pinned: Vec<(&str, Vec<FinderEntry<'_, NodeTemplate>>)> = Vec::new();
+                let listed: Vec<FinderEntry<'_, NodeTemplate>> = if terms.is_empty() {
+                    let favorites: Vec<_> = memory
+                        .favorites
+                        .iter()
+                        .filter_map(|favorite| all_kinds.iter().find(|kind| *kind == favorite))
+                        .map(|kind| FinderEntry::new(kind, user_state))
+                        .collect();
+                    let recent: Vec<_> = memory
+                        .recent
+                        .iter()
+                        .filter(|recent| !memory.is_favorite(recent))
+                        .filter_map(|recent| all_kinds.iter().find(|kind| *kind == recent))
+                        .map(|kind| FinderEntry::new(kind, user_state))
+                        .collect();
+                    pinned.push(("Favorites", favorites));
+                    pinned.push(("Recent", recent));
+                    Vec::new()
+                } else {
+                    let mut ranked: Vec<(i32, FinderEntry<'_, NodeTemplate>)> = all_kinds
+                        .iter()
+                        .filter_map(|kind| {
+                            let entry = FinderEntry::new(kind, user_state);
+                            let keywords = kind.node_finder_keywords(user_state);
+                            let description = entry.description.as_deref().unwrap_or_default().to_lowercase();
+                            let mut score = 0;
+                            for term in terms.iter() {
+                                // the label counts the most, other names of the kind a bit less,
+                                // and descriptions only when they contain the term as it is.
+                                score += std::iter::once(fuzzy_score(term, &entry.label))
+                                    .chain(keywords.iter().map(|keyword| fuzzy_score(term, keyword).map(|score| score / 2)))
+                                    .chain(std::iter::once(description.contains(&term.to_lowercase()).then_some(1)))
+                                    .flatten()
+                                    .max()?;
+                            }
+                            Some((score, entry))
+                        })
+                        .collect();
+                    ranked.sort_by(|(score, entry), (other_score, other_entry)| {
+                        other_score.cmp(score).then_with(|| entry.label.cmp(&other_entry.label))
+                    });
+                    ranked.into_iter().map(|(_, entry)| entry).collect()
+                };
+                let navigable = if terms.is_empty() {
+                    pinned.iter().map(|(_, entries)| entries.len()).sum()
+                } else {
+                    listed.len()
+                };
+                let moved = up || down;
+                if navigable > 0 {
+                    if down {
+                        self.highlighted = (self.highlighted + 1).min(navigable - 1);
+                    } else if up {
+                        self.highlighted = self.highlighted.saturating_sub(1);
+                    }
+                } else {
+                    self.highlighted = 0;
+                }
 
-                    if kind_categories.is_empty() {
-                        orphan_kinds.push(kind);
-                    } else {
-                        for category in kind_categories {
-                            categories.entry(category.name()).or_default().push(kind);
+                let mut hovered: Option<usize> = None;
+                let mut picked: Option<usize> = None;
+                let mut toggled_favorite: Option<usize> = None;
+                let mut categories: BTreeMap<String, Vec<FinderEntry<'_, NodeTemplate>>> = Default::default();
+                let mut orphan_kinds = Vec::new();
+                if terms.is_empty() {
+                    for kind in &all_kinds {
+                        let kind_categories = kind.node_finder_categories(user_state);
+                        if kind_categories.is_empty() {
+                            orphan_kinds.push(FinderEntry::new(kind, user_state));
+                        } else {
+                            for category in kind_categories {
+                                categories.entry(category.name()).or_default().push(FinderEntry::new(kind, user_state));
+                            }
                         }
                     }
                 }
+                // the rows of the category tree are numbered after the navigable ones
+                let tree_rows: Vec<&FinderEntry<'_, NodeTemplate>> =
+                    categories.values().flatten().chain(orphan_kinds.iter()).collect();
 
-                Frame::default()
-                    .inner_margin(vec2(10.0, 10.0))
-                    .show(ui, |ui| {
-                        ScrollArea::vertical()
-                            .min_scrolled_height(max_height)
-                            .max_height(max_height)
-                            .show(ui, |ui| {
-                                ui.set_width(scroll_area_width);
-                                for (category, kinds) in categories {
-                                    let filtered_kinds: Vec<_> = kinds
-                                        .into_iter()
-                                        .map(|kind| {
-                                            let kind_name =
-                                                kind.node_finder_label(user_state).to_string();
-                                            (kind, kind_name)
-                                        })
-                                        .filter(|(_kind, kind_name)| {
-                                            kind_name
-                                                .to_lowercase()
-                                                .contains(self.query.to_lowercase().as_str())
-                                        })
-                                        .collect();
-
-                                    if !filtered_kinds.is_empty() {
-                                        let default_open = !self.query.is_empty();
-
-                                        CollapsingHeader::new(&category)
-                                            .default_open(default_open)
-                                            .open(update_open.then_some(default_open))
+                ui.horizontal_top(|ui| {
+                    Frame::default()
+                        .inner_margin(vec2(10.0, 10.0))
+                        .show(ui, |ui| {
+                            ScrollArea::vertical()
+                                .min_scrolled_height(max_height)
+                                .max_height(max_height)
+                                .show(ui, |ui| {
+                                    ui.set_width(scroll_area_width);
+                                    let mut row = 0;
+                                    let mut show = |ui: &mut Ui, entry: &FinderEntry<'_, NodeTemplate>, row: usize| {
+                                        let highlighted = row == self.highlighted && row < navigable;
+                                        let response = show_row(
+                                            ui,
+                                            &entry.label,
+                                            memory.is_favorite(entry.kind),
+                                            highlighted,
+                                            highlighted && moved,
+                                        );
+                                        if response.hovered {
+                                            hovered = Some(row);
+                                        }
+                                        if response.clicked {
+                                            picked = Some(row);
+                                        }
+                                        if response.star_clicked {
+                                            toggled_favorite = Some(row);
+                                        }
+                                    };
+                                    if !terms.is_empty() {
+                                        if listed.is_empty() {
+                                            ui.weak("No matching nodes");
+                                        }
+                                        for entry in listed.iter() {
+                                            show(ui, entry, row);
+                                            row += 1;
+                                        }
+                                        return;
+                                    }
+                                    for (title, entries) in pinned.iter() {
+                                        if entries.is_empty() {
+                                            continue;
+                                        }
+                                        ui.strong(*title);
+                                        for entry in entries.iter() {
+                                            show(ui, entry, row);
+                                            row += 1;
+                                        }
+                                        ui.separator();
+                                    }
+                                    for (category, entries) in categories.iter() {
+                                        CollapsingHeader::new(category)
+                                            .default_open(false)
+                                            .open(update_open.then_some(false))
                                             .show(ui, |ui| {
-                                                for (kind, kind_name) in filtered_kinds {
-                                                    let mut selectable_label = ui.selectable_label(false, kind_name);
-                                                    if let Some(description_text) = kind
-                                                        .node_finder_description(user_state)
-                                                    {
-                                                        selectable_label = selectable_label.on_hover_text_at_pointer(description_text);
-                                                    }
-                                                    if selectable_label.clicked()
-                                                    {
-                                                        submitted_archetype = Some(kind.clone());
-                                                    } else if query_submit {
-                                                        submitted_archetype = Some(kind.clone());
-                                                        query_submit = false;
-                                                    }
+                                                for (idx, entry) in entries.iter().enumerate() {
+                                                    show(ui, entry, row + idx);
                                                 }
                                             });
+                                        row += entries.len();
                                     }
-                                }
-
-                                for kind in orphan_kinds {
-                                    let kind_name = kind.node_finder_label(user_state).to_string();
+                                    for entry in orphan_kinds.iter() {
+                                        show(ui, entry, row);
+                                        row += 1;
+                                    }
+                                });
+                        });
 
-                                    if ui.selectable_label(false, kind_name).clicked() {
-                                        submitted_archetype = Some(kind.clone());
-                                    } else if query_submit {
-                                        submitted_archetype = Some(kind.clone());
-                                        query_submit = false;
+                    let row_entry = |row: usize| -> Option<&FinderEntry<'_, NodeTemplate>> {
+                        if !terms.is_empty() {
+                            listed.get(row)
+                        } else if row < navigable {
+                            pinned.iter().flat_map(|(_, entries)| entries.iter()).nth(row)
+                        } else {
+                            tree_rows.get(row - navigable).copied()
+                        }
+                    };
+                    let preview = hovered.or((navigable > 0).then_some(self.highlighted)).and_then(row_entry);
+                    if let Some(preview) = preview {
+                        ui.separator();
+                        ui.vertical(|ui| {
+                            ui.set_width(PREVIEW_WIDTH);
+                            ui.strong(&preview.label);
+                            ScrollArea::vertical()
+                                .id_salt("node_finder_preview")
+                                .max_height(max_height)
+                                .show(ui, |ui| match &preview.description {
+                                    Some(description) => {
+                                        ui.label(description);
                                     }
-                                }
-                            });
-                    });
+                                    None => {
+                                        ui.weak("No description");
+                                    }
+                                });
+                        });
+                    }
+                    if query_submit && navigable > 0 {
+                        picked = Some(self.highlighted);
+                    }
+                    if let Some(row) = toggled_favorite.and_then(row_entry) {
+                        memory.toggle_favorite(row.kind);
+                    } else if let Some(row) = picked.and_then(row_entry) {
+                        submitted_archetype = Some(row.kind.clone());
+                    }
+                });
             });
         });
 
+        if let Some(kind) = &submitted_archetype {
+            memory.add_recent(kind);
+        }
         submitted_archetype
     }
 }
+
+/// Finds the first port of the node that a connection from a port of type
+/// `from_type` can be attached to. When `from_output` is set, the connection
+/// starts at an output, so an input is searched for, and vice versa.
+pub(crate) fn find_connectable_port<NodeData, DataType, ValueType, UserState>(
+    graph: &Graph<NodeData, DataType, ValueType>,
+    node_id: NodeId,
+    from_type: &DataType,
+    from_output: bool,
+) -> Option<AnyParameterId>
+where
+    DataType: DataTypeTrait<UserState>,
+{
+    let node = &graph[node_id];
+    if from_output {
+        node.input_ids()
+            .find(|&input| {
+                let param = graph.get_input(input);
+                param.kind != InputParamKind::ConstantOnly
+                    && from_type.accepts_in_finder(&param.typ)
+            })
+            .map(AnyParameterId::Input)
+    } else {
+        node.output_ids()
+            .find(|&output| from_type.accepts_in_finder(&graph.get_output(output).typ))
+            .map(AnyParameterId::Output)
+    }
+}
+
+/// Finds the input and output of the node that a connection from a port of
+/// type `from_type` to one of type `to_type` goes through when the node is
+/// inserted onto it.
+pub(crate) fn wire_ports<NodeData, DataType, ValueType, UserState>(
+    graph: &Graph<NodeData, DataType, ValueType>,
+    node_id: NodeId,
+    from_type: &DataType,
+    to_type: &DataType,
+) -> Option<(InputId, OutputId)>
+where
+    DataType: DataTypeTrait<UserState>,
+{
+    let input = find_connectable_port::<_, _, _, UserState>(graph, node_id, from_type, true)?;
+    let output = find_connectable_port::<_, _, _, UserState>(graph, node_id, to_type, false)?;
+    match (input, output) {
+        (AnyParameterId::Input(input), AnyParameterId::Output(output)) => Some((input, output)),
+        _ => None,
+    }
+}
+
+/// Moves what is attached to the ports of `old` over to `new`, which replaces
+/// it. Ports are matched by name first, connected ones that have no namesake
+/// go to the first free port of the same type. Constant values are copied
+/// between ports of the same name and type.
+///
+/// Returns the connections to make, as (output, input, input hook), and the
+/// names of the ports whose connections have no place on the new node.
+pub(crate) fn carry_over_ports<NodeData, DataType, ValueType, UserState>(
+    graph: &mut Graph<NodeData, DataType, ValueType>,
+    old: NodeId,
+    new: NodeId,
+) -> (Vec<(OutputId, InputId, usize)>, Vec<String>)
+where
+    DataType: DataTypeTrait<UserState>,
+    ValueType: Clone,
+{
+    let compatible = |a: &DataType, b: &DataType| a == b || a.allow_any_type() || b.allow_any_type();
+    let mut connections = Vec::new();
+    let mut dropped = Vec::new();
+
+    let new_inputs = graph[new].inputs.clone();
+    let mut taken_inputs: Vec<InputId> = Vec::new();
+    for (name, old_input) in graph[old].inputs.clone() {
+        let sources = graph.connections(old_input);
+        let old_type = &graph.get_input(old_input).typ;
+        let free = |id: &InputId| !taken_inputs.contains(id);
+        let target = new_inputs
+            .iter()
+            .filter(|(_, id)| free(id))
+            .find(|(new_name, id)| *new_name == name && compatible(&graph.get_input(*id).typ, old_type))
+            .or_else(|| {
+                // only connections are worth moving to a port of another name
+                new_inputs.iter().filter(|(_, id)| free(id)).find(|(_, id)| {
+                    let param = graph.get_input(*id);
+                    !sources.is_empty() && param.kind != InputParamKind::ConstantOnly && param.typ == *old_type
+                })
+            })
+            .map(|(new_name, id)| (new_name.clone(), *id));
+        let Some((new_name, target)) = target else {
+            if !sources.is_empty() {
+                dropped.push(name);
+            }
+            continue;
+        };
+        taken_inputs.push(target);
+        if new_name == name && graph.get_input(target).typ == *old_type {
+            let value = graph.get_input(old_input).value.clone();
+            graph.get_input_mut(target).value = value;
+        }
+        let param = graph.get_input(target);
+        let max_connections = match param.kind {
+            InputParamKind::ConstantOnly => 0,
+            _ => param.max_connections.map_or(usize::MAX, |max| max.get() as usize),
+        };
+        // the old port may have taken any type, check against what is actually connected
+        let source_count = sources.len();
+        let sources: Vec<OutputId> = sources
+            .into_iter()
+            .filter(|output| compatible(&graph.get_output(*output).typ, &param.typ))
+            .collect();
+        if sources.len() < source_count || sources.len() > max_connections {
+            dropped.push(name);
+        }
+        connections.extend(
+            sources
+                .into_iter()
+                .take(max_connections)
+                .enumerate()
+                .map(|(hook, output)| (output, target, hook)),
+        );
+    }
+
+    let new_outputs = graph[new].outputs.clone();
+    let mut taken_outputs: Vec<OutputId> = Vec::new();
+    for (name, old_output) in graph[old].outputs.clone() {
+        let targets: Vec<(InputId, usize)> = graph
+            .iter_connection_groups()
+            .filter_map(|(input, outputs)| {
+                outputs.iter().position(|output| *output == old_output).map(|hook| (input, hook))
+            })
+            .collect();
+        if targets.is_empty() {
+            continue;
+        }
+        let old_type = &graph.get_output(old_output).typ;
+        let free = |id: &OutputId| !taken_outputs.contains(id);
+        let target = new_outputs
+            .iter()
+            .filter(|(_, id)| free(id))
+            .find(|(new_name, id)| *new_name == name && compatible(&graph.get_output(*id).typ, old_type))
+            .or_else(|| {
+                new_outputs
+                    .iter()
+                    .filter(|(_, id)| free(id))
+                    .find(|(_, id)| graph.get_output(*id).typ == *old_type)
+            })
+            .map(|(_, id)| *id);
+        let Some(target) = target else {
+            dropped.push(name);
+            continue;
+        };
+        taken_outputs.push(target);
+        let target_type = &graph.get_output(target).typ;
+        let (kept, mismatched): (Vec<_>, Vec<_>) = targets
+            .into_iter()
+            .partition(|(input, _)| compatible(target_type, &graph.get_input(*input).typ));
+        if !mismatched.is_empty() {
+            dropped.push(name);
+        }
+        connections.extend(kept.into_iter().map(|(input, hook)| (target, input, hook)));
+    }
+
+    (connections, dropped)
+}
+
+/// Keeps the node kinds that have a port a connection of type `from_type` can
+/// be attached to.
+pub(crate) fn connectable_kinds<NodeTemplate, NodeData, DataType, ValueType, UserState>(
+    kinds: Vec<NodeTemplate>,
+    from_type: &DataType,
+    from_output: bool,
+    user_state: &UserState,
+) -> Vec<NodeTemplate>
+where
+    NodeTemplate: NodeTemplateTrait<
+        NodeData = NodeData,
+        DataType = DataType,
+        ValueType = ValueType,
+        UserState = UserState,
+    >,
+    DataType: DataTypeTrait<UserState>,
+    UserState: Clone,
+{
+    filter_kinds(kinds, user_state, |graph, node_id| {
+        find_connectable_port::<_, _, _, UserState>(graph, node_id, from_type, from_output).is_some()
+    })
+}
+
+/// Keeps the node kinds that can be inserted onto a connection from a port of
+/// type `from_type` to one of type `to_type`.
+pub(crate) fn insertable_kinds<NodeTemplate, NodeData, DataType, ValueType, UserState>(
+    kinds: Vec<NodeTemplate>,
+    from_type: &DataType,
+    to_type: &DataType,
+    user_state: &UserState,
+) -> Vec<NodeTemplate>
+where
+    NodeTemplate: NodeTemplateTrait<
+        NodeData = NodeData,
+        DataType = DataType,
+        ValueType = ValueType,
+        UserState = UserState,
+    >,
+    DataType: DataTypeTrait<UserState>,
+    UserState: Clone,
+{
+    filter_kinds(kinds, user_state, |graph, node_id| {
+        wire_ports::<_, _, _, UserState>(graph, node_id, from_type, to_type).is_some()
+    })
+}
+
+// Each kind is built into a scratch graph to find out its ports, using a copy
+// of the user state so building has no side effects.
+fn filter_kinds<NodeTemplate, NodeData, DataType, ValueType, UserState>(
+    kinds: Vec<NodeTemplate>,
+    user_state: &UserState,
+    keep: impl Fn(&Graph<NodeData, DataType, ValueType>, NodeId) -> bool,
+) -> Vec<NodeTemplate>
+where
+    NodeTemplate: NodeTemplateTrait<
+        NodeData = NodeData,
+        DataType = DataType,
+        ValueType = ValueType,
+        UserState = UserState,
+    >,
+    UserState: Clone,
+{
+    let mut scratch_state = user_state.clone();
+    let mut scratch_graph = Graph::new();
+    kinds
+        .into_iter()
+        .filter(|kind| {
+            let node_id = scratch_graph.add_node(
+                String::new(),
+                kind.user_data(&mut scratch_state),
+                |graph, node_id| kind.build_node(graph, &mut scratch_state, node_id),
+            );
+            let kept = keep(&scratch_graph, node_id);
+            scratch_graph.remove_node(node_id);
+            kept
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_fuzzy_score() {
+        assert!(fuzzy_score("wait", "Wait").is_some());
+        assert!(fuzzy_score("fndent", "Find entity by name").is_some());
+        assert!(fuzzy_score("fbn", "Find entity by name").is_some());
+        assert!(fuzzy_score("xyz", "Find entity by name").is_none());
+        assert!(fuzzy_score("ent", "Entity").is_some());
+        // prefixes beat matches inside of words, which beat scattered characters
+        assert!(fuzzy_score("ent", "Entity") > fuzzy_score("ent", "Parent"));
+        assert!(fuzzy_score("ent", "Parent") > fuzzy_score("ent", "Event Handler"));
+        // word starts are preferred when the characters are scattered
+        assert!(fuzzy_score("fbn", "Find entity by name") > fuzzy_score("fbn", "fabulous band"));
+        // shorter texts are closer matches
+        assert!(fuzzy_score("wait", "Wait") > fuzzy_score("wait", "Wait for entity output"));
+    }
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    enum TestType {
+        Number,
+        Text,
+        Any,
+    }
+
+    impl DataTypeTrait<()> for TestType {
+        fn data_type_color(&self, _user_state: &mut ()) -> Color32 {
+            Color32::WHITE
+        }
+
+        fn name(&self) -> std::borrow::Cow<'_, str> {
+            std::borrow::Cow::Owned(format!("{self:?}"))
+        }
+
+        fn allow_any_type(&self) -> bool {
+            *self == TestType::Any
+        }
+    }
+
+    #[test]
+    pub fn test_carry_over_checks_peer_types() {
+        let mut graph: Graph<(), TestType, ()> = Graph::new();
+        let port = |graph: &mut Graph<(), TestType, ()>, node, typ| {
+            graph.add_input_param(node, "value".into(), typ, (), InputParamKind::ConnectionOnly, true);
+            graph.add_output_param(node, "result".into(), typ);
+        };
+        let text = graph.add_node("text".into(), (), |graph, node| port(graph, node, TestType::Text));
+        let old = graph.add_node("old".into(), (), |graph, node| port(graph, node, TestType::Any));
+        let new = graph.add_node("new".into(), (), |graph, node| port(graph, node, TestType::Number));
+        let text_input = graph[text].get_input("value").unwrap();
+        let text_output = graph[text].get_output("result").unwrap();
+        graph.add_connection(text_output, graph[old].get_input("value").unwrap(), 0);
+        graph.add_connection(graph[old].get_output("result").unwrap(), text_input, 0);
+
+        // the old ports took any type, but the text ports can't go on the number ones
+        let (connections, dropped) = carry_over_ports::<_, _, _, ()>(&mut graph, old, new);
+        assert!(connections.is_empty());
+        assert_eq!(dropped, vec!["value".to_string(), "result".to_string()]);
+    }
+}
@@ -9,6 +9,7 @@ use super::*;
 /// `node_data` parameter during `value_widget`. The default value is never
 /// used, so the implementation is not important, but it should be reasonably
 /// cheap to construct.
+#[cfg(feature = "gui")]
 pub trait WidgetValueTrait: Default {
     type Response;
     type UserState;
@@ -56,6 +57,7 @@ pub trait WidgetValueTrait: Default {
 /// to the user.
 pub trait DataTypeTrait<UserState>: PartialEq + Eq {
     /// The associated port color of this datatype
+    #[cfg(feature = "gui")]
     fn data_type_color(&self, user_state: &mut UserState) -> egui::Color32;
 
     /// The name of this datatype. Return type is specified as Cow<str> because
@@ -92,10 +94,18 @@ pub trait DataTypeTrait<UserState>: PartialEq + Eq {
     fn allow_any_type(&self) -> bool {
         false
     }
+
+    /// Whether the node finder, opened by dropping a connection of this type on
+    /// the background, should offer node kinds with a port of type `other`.
+    /// Defaults to the ports the connection could be attached to by hand.
+    fn accepts_in_finder(&self, other: &Self) -> bool {
+        self == other || self.allow_any_type() || other.allow_any_type()
+    }
 }
 
 /// This trait must be implemented for the `NodeData` generic parameter of the
 /// [`Graph`]. This trait allows customizing some aspects of the node drawing.
+#[cfg(feature = "gui")]
 pub trait NodeDataTrait
 where
     Self: Sized,
@@ -191,6 +201,12 @@ where
     ) -> bool {
         true
     }
+
+    /// Draw the node as a small knot with only its ports, without a titlebar or
+    /// any widgets. Meant for nodes that only exist to route wires.
+    fn is_knot(&self) -> bool {
+        false
+    }
 }
 
 /// This trait can be implemented by any user type. The trait tells the library
@@ -275,6 +291,13 @@ pub trait NodeTemplateTrait: Clone {
     /// Returns a description that's displayed upon hovering the chosen node kind.
     fn node_finder_description<'a>(&'a self, user_state: &'a Self::UserState) -> Option<std::borrow::Cow<'a, str>>;
 
+    /// Other names the node kind can be found by in the node finder, besides
+    /// its label. For example the name of the function it calls, or the names
+    /// of its parameters.
+    fn node_finder_keywords(&self, _user_state: &Self::UserState) -> Vec<String> {
+        Vec::new()
+    }
+
     /// Returns the user data for this node kind.
     fn user_data(&self, user_state: &mut Self::UserState) -> Self::NodeData;
 
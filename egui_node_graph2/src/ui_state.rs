@@ -1,19 +1,27 @@
 use super::*;
-use egui::{Rect, Style, Ui, Vec2};
+use crate::geometry::{Pos2, Vec2};
+#[cfg(feature = "gui")]
+use egui::{Rect, Style, Ui};
 use std::marker::PhantomData;
+#[cfg(feature = "gui")]
+use std::collections::HashMap;
+#[cfg(feature = "gui")]
 use std::sync::Arc;
 
+#[cfg(feature = "gui")]
 use crate::scale::Scale;
 #[cfg(feature = "persistence")]
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "gui")]
 const MIN_ZOOM: f32 = 0.2;
+#[cfg(feature = "gui")]
 const MAX_ZOOM: f32 = 2.0;
 
 #[derive(Clone, Debug)]
 pub struct GraphUndoState<NodeData, DataType, ValueType> {
     pub graph: Graph<NodeData, DataType, ValueType>,
-    pub node_positions: SecondaryMap<NodeId, egui::Pos2>,
+    pub node_positions: SecondaryMap<NodeId, Pos2>,
     pub node_sizes: SecondaryMap<NodeId, Vec2>,
 }
 
@@ -42,24 +50,78 @@ pub struct GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, UserSta
     pub selected_nodes: Vec<NodeId>,
     /// Some selection colors can be customized by the user per node
     /// For example when there's an issue with a node it can have different selection color to indicate that to the user
+    #[cfg(feature = "gui")]
     #[cfg_attr(feature = "persistence", serde(skip))]
     pub selection_colors: SecondaryMap<NodeId, egui::Color32>,
     /// The mouse drag start position for an ongoing box selection.
-    pub ongoing_box_selection: Option<egui::Pos2>,
+    pub ongoing_box_selection: Option<Pos2>,
     /// The position of each node.
-    pub node_positions: SecondaryMap<NodeId, egui::Pos2>,
+    pub node_positions: SecondaryMap<NodeId, Pos2>,
     /// The size of each node.
     #[cfg_attr(feature = "persistence", serde(default))]
     pub node_sizes: SecondaryMap<NodeId, Vec2>,
+    /// Frames grouping nodes together, drawn behind them.
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub frames: SlotMap<FrameId, Frame>,
+    /// The currently selected frames, their contents are selected along with them.
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub selected_frames: Vec<FrameId>,
+    /// The nodes and frames moving with the frame being dragged.
+    #[cfg(feature = "gui")]
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    pub(crate) frame_drag: Option<FrameDrag>,
+    /// The node being dragged, and where it would be without snapping to the
+    /// grid. When dragged on its own it may be dropped on a wire.
+    #[cfg(feature = "gui")]
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    pub(crate) dragged_node: Option<(NodeId, Pos2)>,
+    /// Whether the connection in progress was dragged from a port rather
+    /// than pulled off an input it was connected to.
+    #[cfg(feature = "gui")]
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    pub(crate) connection_is_new: bool,
+    /// The open context menu of a connection.
+    #[cfg(feature = "gui")]
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    pub(crate) wire_menu: Option<WireMenu>,
     /// The node finder is used to create new nodes.
+    #[cfg(feature = "gui")]
     pub node_finder: Option<NodeFinder<NodeTemplate>>,
     /// The panning of the graph viewport.
+    #[cfg(feature = "gui")]
+    #[cfg_attr(feature = "persistence", serde(default))]
     pub pan_zoom: PanZoom,
-    pub _user_state: PhantomData<fn() -> UserState>,
+    /// Connections to make stand out, by their output and input. Set by the
+    /// user code, for example to show a path through the graph.
+    #[cfg(feature = "gui")]
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    pub wire_highlights: HashMap<(OutputId, InputId), WireHighlight>,
+    /// The grid behind the nodes, set by the user code.
+    #[cfg(feature = "gui")]
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    pub grid: Grid,
+    /// A point of the grid. It moves along with the nodes when zooming, so
+    /// nodes snapped to the grid stay on it.
+    #[cfg(feature = "gui")]
+    #[cfg_attr(feature = "persistence", serde(default))]
+    pub(crate) grid_origin: Pos2,
+    /// Where each node was drawn last, used to route wires to nodes outside
+    /// of the viewport without drawing them.
+    #[cfg(feature = "gui")]
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    pub(crate) node_metrics: SecondaryMap<NodeId, NodeMetrics>,
+    /// The changes made to the graph the last time the editor was drawn, see
+    /// [`GraphEdit`]. Cleared every time the editor is drawn.
+    #[cfg(feature = "gui")]
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    pub edits: Vec<GraphEdit<NodeData, DataType, ValueType>>,
+    // the node template is only used by the node finder, which needs the `gui` feature.
+    pub _user_state: PhantomData<fn() -> (NodeTemplate, UserState)>,
     // #[serde(skip)]
     // pub undo_memory: CircularStack<Graph<NodeData, DataType, ValueType>>,
 }
 
+#[cfg(feature = "gui")]
 impl<NodeData, DataType, ValueType, NodeKind, UserState>
     GraphEditorState<NodeData, DataType, ValueType, NodeKind, UserState>
 {
@@ -69,6 +131,28 @@ impl<NodeData, DataType, ValueType, NodeKind, UserState>
             ..Default::default()
         }
     }
+
+    /// Size of the node on screen when it was last drawn, at the current zoom level.
+    /// `None` if the node hasn't been drawn yet.
+    pub fn drawn_node_size(&self, node_id: NodeId) -> Option<Vec2> {
+        self.node_metrics
+            .get(node_id)
+            .map(|metrics| metrics.rect_at(Pos2::ZERO, self.pan_zoom.zoom).size())
+    }
+
+    /// The rect of a node in the same space as the node positions, estimated
+    /// from its size if it hasn't been drawn yet.
+    pub fn node_rect(&self, node_id: NodeId) -> Option<Rect> {
+        let position = *self.node_positions.get(node_id)?;
+        let zoom = self.pan_zoom.zoom;
+        Some(match self.node_metrics.get(node_id) {
+            Some(metrics) => metrics.rect_at(position, zoom),
+            None => Rect::from_min_size(
+                position,
+                *self.node_sizes.get(node_id).unwrap_or(&NODE_INITIAL_SIZE) * zoom,
+            ),
+        })
+    }
 }
 impl<NodeData, DataType, ValueType, NodeKind, UserState> Default
     for GraphEditorState<NodeData, DataType, ValueType, NodeKind, UserState>
@@ -79,22 +163,46 @@ impl<NodeData, DataType, ValueType, NodeKind, UserState> Default
             node_order: Default::default(),
             connection_in_progress: Default::default(),
             selected_nodes: Default::default(),
+            #[cfg(feature = "gui")]
             selection_colors: Default::default(),
             ongoing_box_selection: Default::default(),
             node_positions: Default::default(),
             node_sizes: Default::default(),
+            frames: Default::default(),
+            selected_frames: Default::default(),
+            #[cfg(feature = "gui")]
+            frame_drag: Default::default(),
+            #[cfg(feature = "gui")]
+            dragged_node: Default::default(),
+            #[cfg(feature = "gui")]
+            connection_is_new: Default::default(),
+            #[cfg(feature = "gui")]
+            wire_menu: Default::default(),
+            #[cfg(feature = "gui")]
             node_finder: Default::default(),
+            #[cfg(feature = "gui")]
             pan_zoom: Default::default(),
+            #[cfg(feature = "gui")]
+            wire_highlights: Default::default(),
+            #[cfg(feature = "gui")]
+            grid: Default::default(),
+            #[cfg(feature = "gui")]
+            grid_origin: Default::default(),
+            #[cfg(feature = "gui")]
+            node_metrics: Default::default(),
+            #[cfg(feature = "gui")]
+            edits: Default::default(),
             _user_state: Default::default(),
         }
     }
 }
 
-#[cfg(feature = "persistence")]
+#[cfg(all(feature = "gui", feature = "persistence"))]
 fn _default_clip_rect() -> Rect {
     Rect::NOTHING
 }
 
+#[cfg(feature = "gui")]
 #[derive(Clone)]
 #[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
 pub struct PanZoom {
@@ -108,6 +216,7 @@ pub struct PanZoom {
     pub started: bool,
 }
 
+#[cfg(feature = "gui")]
 impl Default for PanZoom {
     fn default() -> Self {
         PanZoom {
@@ -120,6 +229,7 @@ impl Default for PanZoom {
     }
 }
 
+#[cfg(feature = "gui")]
 impl PanZoom {
     pub fn new(zoom: f32) -> PanZoom {
         let style: Style = Default::default();
@@ -140,6 +250,7 @@ impl PanZoom {
     }
 }
 
+#[cfg(feature = "gui")]
 pub fn show_zoomed<R, F>(
     default_style: Arc<Style>,
     zoomed_style: Arc<Style>,
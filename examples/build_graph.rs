@@ -0,0 +1,43 @@
+// Builds a small graph from code and prints the compiled KV3.
+// Run from the repository root, so that the bindings can be found: `cargo run --example build_graph`
+use libpulseedit::builder::GraphBuilder;
+use libpulseedit::types::{PulseGraphValueType, PulseNodeTemplate};
+use libpulseedit::typing::PulseValueType;
+
+fn main() -> anyhow::Result<()> {
+    let mut builder = GraphBuilder::from_bindings_file("bindings/bindings_manifest.json".as_ref())?;
+    builder
+        .add_variable("last_message", PulseValueType::PVAL_STRING(None))
+        .add_public_output("OnLogged", PulseValueType::PVAL_STRING(None));
+
+    // entity input "Log", that prints its argument, remembers it and fires an output with it.
+    let method = builder.add_node(PulseNodeTemplate::CellPublicMethod);
+    builder.set_input_value(method, "name", PulseGraphValueType::String { value: "Log".into() })?;
+
+    let log = builder.add_node(PulseNodeTemplate::DebugLog);
+    builder.connect(method, "outAction", log, "ActionIn")?;
+    builder.connect(method, "argument1", log, "pMessage")?;
+
+    let set_var = builder.add_node(PulseNodeTemplate::SetVar);
+    builder.set_input_value(set_var, "variableName", PulseGraphValueType::InternalVariableName {
+        prevvalue: String::new(),
+        value: "last_message".into(),
+    })?;
+    builder.connect(log, "outAction", set_var, "ActionIn")?;
+    builder.connect(method, "argument1", set_var, "value")?;
+
+    let fire = builder.add_node(PulseNodeTemplate::FireOutput);
+    builder.set_input_value(fire, "outputName", PulseGraphValueType::InternalOutputName {
+        prevvalue: String::new(),
+        value: "OnLogged".into(),
+    })?;
+    builder.connect(set_var, "outAction", fire, "ActionIn")?;
+    builder.connect(method, "argument1", fire, "param")?;
+
+    println!("{}", builder.compile_to_string()?);
+    // the same graph can be opened in the editor
+    let path = std::env::temp_dir().join("build_graph_example.ron");
+    builder.save(&path)?;
+    println!("Saved to {}", path.display());
+    Ok(())
+}
@@ -2,62 +2,48 @@ mod impls;
 // contains help text
 mod help;
 mod migrations;
+#[cfg(feature = "gui")]
 mod appwidgets;
+#[cfg(feature = "gui")]
+mod compile_task;
+#[cfg(feature = "gui")]
+mod conversions;
+#[cfg(feature = "gui")]
+mod editor;
+#[cfg(feature = "gui")]
+mod layout;
+#[cfg(feature = "gui")]
+mod flow;
+#[cfg(feature = "gui")]
+mod search;
+#[cfg(feature = "gui")]
+mod references;
+#[cfg(feature = "gui")]
+mod history;
+#[cfg(feature = "gui")]
+mod keymap;
+#[cfg(feature = "gui")]
+mod recovery;
 
 pub mod types;
 
-use delegate::delegate;
-use std::collections::VecDeque;
-use std::time::UNIX_EPOCH;
-use std::{path::PathBuf, fs, thread};
-use core::panic;
-use eframe::egui::util::undoer::{Settings, Undoer};
-use eframe::egui::{Button, Vec2};
+use std::{path::PathBuf, fs};
 use serde::{Deserialize, Serialize};
-use rfd::{FileDialog, MessageDialog};
-use anyhow::anyhow;
-use eframe::egui::{self, Modal, Id, RichText};
 use egui_node_graph2::*;
+use egui_node_graph2::geometry::{vec2, Pos2};
 use crate::bindings::*;
-use crate::compiler::{compile_graph, CompileError};
-use crate::pulsetypes::*;
 use crate::typing::*;
-use crate::utils::get_node_ids_connected_to_output;
 use types::*;
+#[cfg(feature = "gui")]
+pub use editor::PulseGraphEditor;
+#[cfg(feature = "gui")]
+pub use recovery::setup_panic_hook;
 
-static APP_NAME: &str = "Pulse Graph Editor";
 #[derive(Default, Clone)]
-#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
-pub enum ModalWindowType {
-    #[default]
-    None,
-    ConfirmSave
-}
-
-#[derive(Default, Clone)]
-#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
-pub struct ModalWindow {
-    pub window_type: ModalWindowType,
-    pub is_open: bool,
-}
-#[derive(Default, Clone)]
-enum ConsoleMessageType {
-    #[default]
-    Info,
-    Warning,
-    Error,
-}
-#[derive(Default, Clone)]
-struct ConsoleLine {
-    text: String,
-    message_type: ConsoleMessageType,
-}
-
-#[derive(Default, Clone)]
-#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+#[derive(Serialize, Deserialize)]
 pub struct FullGraphState {
     pub state: MyEditorState,
-    pub user_state: PulseGraphState
+    pub user_state: PulseGraphState,
 }
 
 impl FullGraphState {
@@ -73,11 +59,19 @@ impl FullGraphState {
     pub fn user_state_mut(&mut self) -> &mut PulseGraphState {
         &mut self.user_state
     }
-    pub fn load_from_state(&mut self, state: FullGraphState) {
-        self.state = state.state;
-        self.user_state.load_from(state.user_state);
+    /// Adds a node built from the template at the given position, on top of other nodes.
+    pub fn add_node(&mut self, template: PulseNodeTemplate, position: Pos2) -> NodeId {
+        let user_state = &mut self.user_state;
+        let node_id = self.state.graph.add_node(
+            template.node_graph_label(user_state),
+            template.user_data(user_state),
+            |graph, node_id| template.build_node(graph, user_state, node_id),
+        );
+        self.state.node_positions.insert(node_id, position);
+        self.state.node_sizes.insert(node_id, vec2(200.0, 200.0));
+        self.state.node_order.push(node_id);
+        node_id
     }
-
     pub fn load_state(&mut self, filepath: &PathBuf) -> Result<(), anyhow::Error> {
         let contents = fs::read_to_string(filepath)?;
         let loaded_graph: FullGraphState = ron::from_str(&contents).map_err(|e| {
@@ -93,7 +87,7 @@ impl FullGraphState {
         Ok(())
     }
 
-    fn save_graph(&self, filepath: &PathBuf) -> Result<(), anyhow::Error> {
+    pub(crate) fn save_graph(&self, filepath: &PathBuf) -> Result<(), anyhow::Error> {
         let res = ron::ser::to_string_pretty::<FullGraphState>(
             self,
             ron::ser::PrettyConfig::default(),
@@ -101,150 +95,7 @@ impl FullGraphState {
         fs::write(filepath, res)?;
         Ok(())
     }
-}
 
-#[derive(Default, Clone)]
-pub struct PulseGraphEditor {
-    #[allow(unused)]
-    version: FileVersion,
-    full_state: FullGraphState,
-    #[cfg(feature = "nongame_asset_build")]
-    editor_config: EditorConfig,
-    current_modal_dialog: ModalWindow,
-    undoer: Undoer<FullGraphState>,
-    console_lines: VecDeque<ConsoleLine>,
-}
-
-impl PulseGraphEditor {
-    delegate! {
-        to self.full_state {
-            pub fn state(&self) -> &MyEditorState;
-            pub fn state_mut(&mut self) -> &mut MyEditorState;
-            pub fn user_state(&self) -> &PulseGraphState;
-            pub fn user_state_mut(&mut self) -> &mut PulseGraphState;
-        }
-    }
-    fn save_graph(&self, filepath: &PathBuf) -> Result<(), anyhow::Error> {
-        self.full_state.save_graph(filepath)
-    }
-    // perform a save including including some cleanup
-    fn perform_save(&mut self, filepath: Option<&PathBuf>) -> anyhow::Result<()> {
-        let dest_path;
-        // remove the path on manual save (we don't use serde skip because we want to save it within autosaves)
-        let save_path = self.full_state.user_state.save_file_path.take();
-        if let Some(filepath) = filepath {
-            dest_path = filepath;
-        } else {
-            // if no filepath is provided, assume the one in saved state
-            if let Some(filepath) = save_path.as_ref() {
-                dest_path = filepath;
-            } else {
-                return Err(anyhow!(
-                    "No file path provided for saving the graph. This should not happen"
-                ));
-            }
-        }
-        self.save_graph(dest_path)?;
-        // restore the path info to memory.
-        self.full_state.user_state.save_file_path = save_path;
-        Ok(())
-    }
-    // promts user to choose a file to save the graph to and remembers the location for saving.
-    fn dialog_change_save_file(&mut self) -> bool {
-        let chosen_file = FileDialog::new()
-            .add_filter("Pulse Graph Editor State", &["ron"])
-            .save_file();
-        let did_pick = chosen_file.as_ref().is_some(); // if not, the user cancelled so we should note that
-        if did_pick {
-            self.full_state.user_state.save_file_path = chosen_file;
-        }
-        did_pick
-    }
-   
-    fn load_graph(&mut self, filepath: &PathBuf) -> Result<(), anyhow::Error> {
-        self.clear_console();
-        let res = self.full_state.load_state(filepath);
-        if res.is_ok() {
-            self.undoer = Self::get_new_undoer();
-        }
-        res
-    }
-    fn new_graph(&mut self, ctx: &egui::Context) {
-        self.clear_console();
-        self.undoer = Self::get_new_undoer();
-        self.full_state.state = MyEditorState::default();
-        self.user_state_mut().load_from(PulseGraphState::default());
-        self.user_state_mut().save_file_path = None;
-        self.update_titlebar(ctx);
-    }
-    // pub fn update_output_node_param(&mut self, node_id: NodeId, name: &String, input_name: &str) {
-    //     let param = self
-    //         .state_mut()
-    //         .graph
-    //         .nodes
-    //         .get_mut(node_id)
-    //         .unwrap()
-    //         .get_input(input_name);
-    //     if let Ok(param) = param {
-    //         self.state_mut().graph.remove_input_param(param);
-    //     }
-    //     let public_outputs: Vec<_> = self.user_state().public_outputs.to_vec();
-    //     for output in public_outputs {
-    //         if output.name == *name {
-    //             match output.typ {
-    //                 PulseValueType::PVAL_FLOAT(_) | PulseValueType::PVAL_INT(_) => {
-    //                     self.state_mut().graph.add_input_param(
-    //                         node_id,
-    //                         String::from(input_name),
-    //                         PulseDataType::Scalar,
-    //                         PulseGraphValueType::Scalar { value: 0f32 },
-    //                         InputParamKind::ConnectionOrConstant,
-    //                         true,
-    //                     );
-    //                 }
-    //                 PulseValueType::PVAL_STRING(_) => {
-    //                     self.state_mut().graph.add_input_param(
-    //                         node_id,
-    //                         String::from(input_name),
-    //                         PulseDataType::String,
-    //                         PulseGraphValueType::String {
-    //                             value: String::default(),
-    //                         },
-    //                         InputParamKind::ConnectionOrConstant,
-    //                         true,
-    //                     );
-    //                 }
-    //                 PulseValueType::PVAL_VEC3(_) => {
-    //                     self.state_mut().graph.add_input_param(
-    //                         node_id,
-    //                         String::from(input_name),
-    //                         PulseDataType::Vec3,
-    //                         PulseGraphValueType::Vec3 {
-    //                             value: Vec3 {
-    //                                 x: 0.0,
-    //                                 y: 0.0,
-    //                                 z: 0.0,
-    //                             },
-    //                         },
-    //                         InputParamKind::ConnectionOrConstant,
-    //                         true,
-    //                     );
-    //                 }
-    //                 PulseValueType::PVAL_EHANDLE(_) => {
-    //                     self.state_mut().graph.add_input_param(
-    //                         node_id,
-    //                         String::from(input_name),
-    //                         PulseDataType::EHandle,
-    //                         PulseGraphValueType::EHandle,
-    //                         InputParamKind::ConnectionOnly,
-    //                         true,
-    //                     );
-    //                 }
-    //                 _ => {}
-    //             }
-    //         }
-    //     }
-    // }
     fn add_node_input_simple(
         &mut self,
         node_id: NodeId,
@@ -272,14 +123,13 @@ impl PulseGraphEditor {
             .graph
             .add_output_param(node_id, String::from(output_name), data_typ);
     }
-
     pub fn update_node_public_output_types(
         &mut self,
         node_id: NodeId,
         output_idx: PublicOutputIndex,
     ) {
-        let node = self.full_state.state.graph.nodes.get(node_id).unwrap();
-        let Some(output) = self.full_state.user_state.get_public_output_from_index(output_idx) else {
+        let node = self.state.graph.nodes.get(node_id).unwrap();
+        let Some(output) = self.user_state.get_public_output_from_index(output_idx) else {
             return;
         };
 
@@ -288,7 +138,7 @@ impl PulseGraphEditor {
         };
         
         // Bail if node's variable does not match
-        let output_ref_inp_val = self.full_state.state.graph.get_input(output_ref_inp);
+        let output_ref_inp_val = self.state.graph.get_input(output_ref_inp);
         if let PulseGraphValueType::InternalVariableName { prevvalue: _, value } = &output_ref_inp_val.value {
             if *value != output.name {
                 return;
@@ -298,7 +148,7 @@ impl PulseGraphEditor {
         if matches!(node.user_data.template, PulseNodeTemplate::FireOutput) {
             let param = node.get_input("param");
             if let Ok(param) = param {
-                self.full_state.state.graph.remove_input_param(param);
+                self.state.graph.remove_input_param(param);
             }
             self.add_node_input_simple(
                 node_id,
@@ -309,14 +159,13 @@ impl PulseGraphEditor {
             );
         }
     }
-
     pub fn update_node_variable_types(
         &mut self,
         node_id: NodeId,
         variable_idx: VariableIndex,
     ) {
-        let node = self.full_state.state.graph.nodes.get(node_id).unwrap();
-        let Some(variable) = self.full_state.user_state.get_variable_from_index(variable_idx) else {
+        let node = self.state.graph.nodes.get(node_id).unwrap();
+        let Some(variable) = self.user_state.get_variable_from_index(variable_idx) else {
             return;
         };
 
@@ -325,7 +174,7 @@ impl PulseGraphEditor {
         };
         
         // Bail if node's variable does not match
-        let var_ref_inp_val = self.full_state.state.graph.get_input(var_ref_inp);
+        let var_ref_inp_val = self.state.graph.get_input(var_ref_inp);
         if let PulseGraphValueType::InternalVariableName { prevvalue: _, value } = &var_ref_inp_val.value {
             if *value != variable.name {
                 return;
@@ -336,14 +185,14 @@ impl PulseGraphEditor {
             PulseNodeTemplate::GetVar => {
                 let param = node.get_output("value");
                 if let Ok(param) = param {
-                    self.full_state.state.graph.remove_output_param(param);
+                    self.state.graph.remove_output_param(param);
                 }
                 self.add_node_output_simple(node_id, variable.data_type.clone(), "value");
             }
             PulseNodeTemplate::SetVar => {
                 let param = node.get_input("value");
                 if let Ok(param) = param {
-                    self.full_state.state.graph.remove_input_param(param);
+                    self.state.graph.remove_input_param(param);
                 }
                 self.add_node_input_simple(
                     node_id,
@@ -356,154 +205,8 @@ impl PulseGraphEditor {
             _ => {}
         }
     }
-    pub fn update_node_inputs_outputs_types<'a>(
-        &mut self,
-        node_id: NodeId,
-        name: std::borrow::Cow<'a, str>,
-        new_type: Option<PulseValueType>,
-    ) {
-        let node = self.state().graph.nodes.get(node_id).unwrap();
-        match node.user_data.template {
-            PulseNodeTemplate::Operation => {
-                if new_type.is_none() {
-                    panic!("update_node_inputs_outputs() ended up on node that requires new value type from response, but it was not provided");
-                }
-                let new_type = new_type.unwrap();
-                let param_a = node.get_input("A");
-                let param_b = node.get_input("B");
-                let param_out = node.get_output("out");
-                if param_a.is_err() || param_b.is_err() || param_out.is_err() {
-                    panic!("node that requires inputs 'A', 'B' and output 'out', but one of them was not found");
-                }
-                self.state_mut().graph.remove_input_param(param_a.unwrap());
-                self.state_mut().graph.remove_input_param(param_b.unwrap());
-                self.state_mut().graph.remove_output_param(param_out.unwrap());
-
-                let types = pulse_value_type_to_node_types(&new_type);
-                self.add_node_input_simple(
-                    node_id,
-                    types.0.clone(),
-                    types.1.clone(),
-                    "A",
-                    InputParamKind::ConnectionOrConstant,
-                );
-                self.add_node_input_simple(
-                    node_id,
-                    types.0.clone(),
-                    types.1,
-                    "B",
-                    InputParamKind::ConnectionOrConstant,
-                );
-                self.add_node_output_simple(node_id, types.0, "out");
-            }
-            PulseNodeTemplate::Convert => {
-                if name == "typefrom" {
-                    let param_input = node.get_input("input");
-                    if let Ok(param_input) = param_input {
-                        self.state_mut().graph.remove_input_param(param_input);
-                        let types = pulse_value_type_to_node_types(&new_type.unwrap());
-                        self.add_node_input_simple(
-                            node_id,
-                            types.0,
-                            types.1,
-                            "input",
-                            InputParamKind::ConnectionOrConstant,
-                        );
-                    }
-                } else if name == "typeto" {
-                    let param_output = node.get_output("out");
-                    if let Ok(param_output) = param_output {
-                        self.state_mut().graph.remove_output_param(param_output);
-                        let types = pulse_value_type_to_node_types(&new_type.unwrap());
-                        self.add_node_output_simple(node_id, types.0, "out");
-                    }
-                }
-            }
-            PulseNodeTemplate::Compare | PulseNodeTemplate::CompareOutput => {
-                if new_type.is_none() {
-                    panic!("update_node_inputs_outputs() ended up on node that requires new value type from response, but it was not provided");
-                }
-                let new_type = new_type.unwrap();
-                let param_a = node.get_input("A");
-                let param_b = node.get_input("B");
-                if param_a.is_err() || param_b.is_err() {
-                    panic!("node that requires inputs 'A' and 'B', but one of them was not found");
-                }
-                self.state_mut().graph.remove_input_param(param_a.unwrap());
-                self.state_mut().graph.remove_input_param(param_b.unwrap());
-
-                let types = pulse_value_type_to_node_types(&new_type);
-                self.add_node_input_simple(
-                    node_id,
-                    types.0.clone(),
-                    types.1.clone(),
-                    "A",
-                    InputParamKind::ConnectionOrConstant,
-                );
-                self.add_node_input_simple(
-                    node_id,
-                    types.0.clone(),
-                    types.1,
-                    "B",
-                    InputParamKind::ConnectionOrConstant,
-                );
-            }
-            PulseNodeTemplate::GetArrayElement => {
-                if new_type.is_none() {
-                    panic!("update_node_inputs_outputs() ended up on node that requires new value type from response, but it was not provided");
-                }
-                let new_type = new_type.unwrap();
-                let param_a = node.get_input("expectedType");
-                if param_a.is_err() {
-                    panic!("node that requires input 'expectedType', but it was not found");
-                }
-                let param_output = node.get_output("out");
-                if let Ok(param_output) = param_output {
-                    self.state_mut().graph.remove_output_param(param_output);
-                    let types = pulse_value_type_to_node_types(&new_type);
-                    self.add_node_output_simple(node_id, types.0, "out");
-                }
-            }
-            PulseNodeTemplate::ScaleVector => {
-                if new_type.is_none() {
-                    panic!("update_node_inputs_outputs() ended up on node that requires new value type from response, but it was not provided");
-                }
-                let new_type = new_type.unwrap();
-                let types = pulse_value_type_to_node_types(&new_type);
-                let output_id = node.get_output("out");
-                let param_vec = node.get_input("vector");
-                if output_id.is_err() {
-                    panic!("node requires output 'out', but it was not found");
-                }
-                let output = self.state_mut().graph.get_output_mut(output_id.unwrap());
-                output.typ = types.0.clone();
-                if param_vec.is_err(){
-                    panic!("node that requires inputs 'A' and 'B', but one of them was not found");
-                }
-                self.state_mut().graph.remove_input_param(param_vec.unwrap());
-
-                self.add_node_input_simple(
-                    node_id,
-                    types.0,
-                    types.1,
-                    "vector",
-                    InputParamKind::ConnectionOrConstant,
-                );
-            }
-            PulseNodeTemplate::NewArray => {
-                let types = pulse_value_type_to_node_types(&new_type.unwrap_or_default());
-                let inputs = node.user_data.added_inputs.clone();
-                for inp in inputs {
-                    let param = self.state_mut().graph.get_input_mut(inp);
-                    param.typ = types.0.clone();
-                    param.value = types.1.clone();
-                }
-            }
-            _ => {}
-        }
-    }
-
-    fn update_library_binding_params(&mut self, node_id: &NodeId, binding: &FunctionBinding) {
+    #[cfg(feature = "gui")]
+    pub(crate) fn update_library_binding_params(&mut self, node_id: &NodeId, binding: &FunctionBinding) {
         let output_ids: Vec<_> = {
             let node = self.state().graph.nodes.get(*node_id).unwrap();
             node.output_ids().collect()
@@ -524,48 +227,9 @@ impl PulseGraphEditor {
                 self.state_mut().graph.remove_input_param(input);
             }
         }
-        // If it's action type (nodes that usually don't provide a value) make it have in and out actions.
-        if binding.typ == LibraryBindingType::Action {
-            self.state_mut().graph.add_output_param(
-                *node_id,
-                "outAction".to_string(),
-                PulseDataType::Action,
-            );
-            self.state_mut().graph.add_input_param(
-                *node_id,
-                "ActionIn".to_string(),
-                PulseDataType::Action,
-                PulseGraphValueType::Action,
-                InputParamKind::ConnectionOrConstant,
-                true,
-            );
-        }
-        if let Some(inparams) = &binding.inparams {
-            for param in inparams {
-                let connection_kind = get_preffered_inputparamkind_from_type(&param.pulsetype);
-                let graph_types = pulse_value_type_to_node_types(&param.pulsetype);
-                self.state_mut().graph.add_input_param(
-                    *node_id,
-                    param.name.clone(),
-                    graph_types.0,
-                    graph_types.1,
-                    connection_kind,
-                    true,
-                );
-            }
-        }
-        if let Some(outparams) = &binding.outparams {
-            for param in outparams {
-                self.state_mut().graph.add_output_param(
-                    *node_id,
-                    param.name.clone(),
-                    pulse_value_type_to_node_types(&param.pulsetype).0,
-                );
-            }
-        }
+        add_library_binding_params(&mut self.state.graph, *node_id, binding);
     }
-
-    fn update_event_binding_params(&mut self, node_id: &NodeId, binding: &EventBinding) {
+    pub(crate) fn update_event_binding_params(&mut self, node_id: &NodeId, binding: &EventBinding) {
         let output_ids: Vec<_> = {
             let node = self.state().graph.nodes.get(*node_id).unwrap();
             node.output_ids().collect()
@@ -588,943 +252,47 @@ impl PulseGraphEditor {
             }
         }
     }
+}
 
-    // Update inputs on "Call Node" depending on the type of referenced node.
-    fn update_remote_node_params(&mut self, node_id: &NodeId, node_id_refrence: &NodeId) {
-        let node = self.state_mut().graph.nodes.get_mut(*node_id).unwrap();
-        // remove all inputs
-        let input_ids: Vec<_> = node.input_ids().collect();
-        let output_ids: Vec<_> = node.output_ids().collect();
-        let input_node_chooser = node
-            .get_input("nodeId")
-            .expect("Expected 'Call Node' node to have 'nodeId' input param");
-        for input in input_ids {
-            // don't remove the node chooser input
-            if input != input_node_chooser {
-                self.state_mut().graph.remove_input_param(input);
-            }
-        }
-        for output in output_ids {
-            self.state_mut().graph.remove_output_param(output);
-        }
-        if let Some(reference_node) = self.state().graph.nodes.get(*node_id_refrence) {
-            let reference_node_template = reference_node.user_data.template;
-            match reference_node_template {
-                PulseNodeTemplate::ListenForEntityOutput => {
-                    self.state_mut().graph.add_input_param(
-                        *node_id,
-                        "hEntity".into(),
-                        PulseDataType::EHandle,
-                        PulseGraphValueType::EHandle,
-                        InputParamKind::ConnectionOnly,
-                        true,
-                    );
-                    self.state_mut().graph.add_input_param(
-                        *node_id,
-                        "Run".into(),
-                        PulseDataType::Action,
-                        PulseGraphValueType::Action,
-                        InputParamKind::ConnectionOnly,
-                        true,
-                    );
-                    self.state_mut().graph.add_input_param(
-                        *node_id,
-                        "Cancel".into(),
-                        PulseDataType::Action,
-                        PulseGraphValueType::Action,
-                        InputParamKind::ConnectionOnly,
-                        true,
-                    );
-                }
-                PulseNodeTemplate::Function => {
-                    self.state_mut().graph.add_input_param(
-                        *node_id,
-                        "ActionIn".into(),
-                        PulseDataType::Action,
-                        PulseGraphValueType::Action,
-                        InputParamKind::ConnectionOnly,
-                        true,
-                    );
-                    self.state_mut().graph.add_input_param(*node_id,
-                        "Async".into(),
-                        PulseDataType::Bool,
-                        PulseGraphValueType::Bool { value: Default::default() },
-                        InputParamKind::ConstantOnly,
-                        true,
-                    );
-                    self.state_mut().graph.add_output_param(
-                        *node_id,
-                        "outAction".into(),
-                        PulseDataType::Action,
-                    );
-                }
-                PulseNodeTemplate::Timeline => {
-                    self.state_mut().graph.add_input_param(
-                        *node_id,
-                        "Start".into(),
-                        PulseDataType::Action,
-                        PulseGraphValueType::Action,
-                        InputParamKind::ConnectionOnly,
-                        true,
-                    );
-                    self.state_mut().graph.add_input_param(
-                        *node_id,
-                        "Stop".into(),
-                        PulseDataType::Action,
-                        PulseGraphValueType::Action,
-                        InputParamKind::ConnectionOnly,
-                        true,
-                    );
-                    self.state_mut().graph.add_output_param(
-                        *node_id,
-                        "outAction".into(),
-                        PulseDataType::Action,
-                    );
-                }
-                _ => {
-                    panic!(
-                        "update_remote_node_params() called on unsupported node type: {:?}",
-                        reference_node_template
-                    );
-                }
-            }
-        } else {
-            self.write_console_line(
-                "update_remote_node_params() called on node that does not exist in the graph anymore!".into(),
-                ConsoleMessageType::Warning
-            );
-        }
-    }
-    async fn check_for_updates() -> anyhow::Result<()> {
-        let releases = self_update::backends::github::ReleaseList::configure()
-            .repo_owner("liondoge")
-            .repo_name("vpulse-editor")
-            .build()?
-            .fetch()?;
-        let rel = releases.first().ok_or(anyhow::anyhow!(
-            "No releases present after fetching from GitHub"
-        ))?;
-        let mut msg_box = rfd::AsyncMessageDialog::new()
-            .set_level(rfd::MessageLevel::Info);
-        if self_update::version::bump_is_greater(env!("CARGO_PKG_VERSION"), &rel.version)? {
-            msg_box = msg_box
-                .set_title("Update Available")
-                .set_buttons(rfd::MessageButtons::YesNo)
-                .set_description(format!(
-                    "A new version of Pulse Graph Editor is available: {}.\nDo you want to update?",
-                    rel.version
-                ));
-        } else {
-            msg_box = msg_box
-                .set_title("Up to date")
-                .set_buttons(rfd::MessageButtons::Ok)
-                .set_description("Pulse Graph Editor is up to date.");
-        }
-        let response = msg_box.show().await;
-        if response == rfd::MessageDialogResult::Yes {
-            open::that("https://github.com/LionDoge/vpulse-editor/releases/latest")?;
-        }
-        Ok(())
-    }
-    // traverse forward to nodes connected to THIS node's output recursively
-    // until we reach a node that doesn't depend on polymorphic return type.
-    fn update_polymorphic_output_types(&mut self, node_id: NodeId, source_type: Option<PulseValueType>, source_input_name: Option<&str>) -> anyhow::Result<()> {
-        // if the node is a "Make Array" node, we need to update the output type based on the array type
-        let node_data = self.state().graph.nodes.get(node_id)
-            .ok_or(anyhow::anyhow!("Node with id {:?} not found in the graph", node_id))?;
-        if !has_polymorhpic_dependent_return(&node_data.user_data.template, self.user_state()) {
-            return Ok(());
-        };
-        
-        let opt_new_type = match node_data.user_data.template {
-            PulseNodeTemplate::NewArray => {
-                let graph = &self.state().graph;
-                // TODO: get_constant_graph_input_value should probably be moved out of the compiler module
-                let typ = crate::compiler::get_constant_graph_input_value!(
-                    graph,
-                    node_data,
-                    "arrayType",
-                    try_pulse_type
-                );
-                Some(PulseValueType::PVAL_ARRAY(Box::new(typ)))
-            }
-            PulseNodeTemplate::GetArrayElement
-            | PulseNodeTemplate::ForEach => {
-                // if the source type is not None, we can update the output type
-                if let Some(source_type) = &source_type {
-                    // if the source type is an array, we need to update the output type to the inner type
-                    if let PulseValueType::PVAL_ARRAY(inner) = source_type {
-                        let node_data_mut = self.state_mut().graph.nodes.get_mut(node_id).unwrap(); // already checked, can unwrap.
-                        let out_id = node_data_mut.get_output("out")?;
-                        node_data_mut.user_data.custom_output_type = Some((**inner).clone());
-                        // update output in UI
-                        self.state_mut().graph.get_output_mut(out_id).typ = pulse_value_type_to_node_types(inner).0;
-                        Some((**inner).clone())
-                    } else {
-                        let source_type_str = source_type.get_enum_string(&self.user_state().bindings);
-                        self.write_console_line(
-                            format!("[UI] Expected array type from source node, but got {source_type_str}. The node setup might not work correctly in this state."),
-                            ConsoleMessageType::Warning
-                        );
-                        None
-                    }
-                } else {
-                    None
-                }
-            }
-            PulseNodeTemplate::LibraryBindingAssigned { binding } => {
-                let binding = self.user_state().bindings
-                    .find_function_by_id(binding)
-                    .ok_or(anyhow::anyhow!("Library binding node can't find saved binding {:?}! Likely the bindings file is not correct.", binding))?;
-                let new_type = if let Some(typ) = source_type {
-                    Some(typ)
-                } else {
-                   node_data.user_data.custom_output_type.clone()
-                };
-                if let Some(polymorphic_return) = &binding.polymorphic_return {
-                    match &polymorphic_return {
-                        // full type means that we copy over the return type from the binding
-                        PolimorphicTypeInfo::FullType (param_name) => {
-                            println!("source input name: {}", source_input_name.unwrap_or_default());
-                            if source_input_name.is_some_and(|f| f == param_name) || source_input_name.is_none() {
-                                let node_data_mut = self.state_mut().graph.nodes.get_mut(node_id).unwrap();
-                                node_data_mut.user_data.custom_output_type = new_type.clone();
-                                new_type
-                            } else {
-                                None
-                            }
-                        }
-                        PolimorphicTypeInfo::TypeParam (param_name) => {
-                            // type param means that we use the type parameter from the binding
-                            // Must be an array, otherwise the definition is wrong!
-                            println!("source input name: {}", source_input_name.unwrap_or_default());
-                            if source_input_name.is_some_and(|f| f == param_name) || source_input_name.is_none() {
-                                new_type.map(|new_type| {
-                                    if let PulseValueType::PVAL_ARRAY(inner) = new_type {
-                                        let node_data_mut = self.state_mut().graph.nodes.get_mut(node_id).unwrap();
-                                        node_data_mut.user_data.custom_output_type = Some(*inner.clone());
-                                        *inner
-                                    } else {
-                                        panic!("Polymorphic return type requested inner type from param, but that param was not Array type, which seems wrong!");
-                                    }
-                                })
-                            } else {
-                                None
-                            }
-                        }
-                        PolimorphicTypeInfo::ToSubtype(_param_name) => {
-                            // FUTURE: to_subtype requests a specific return subtype described in the provided parameter
-                            // However it's only really used for EHandles, and if a method requests a specific subtype of EHandle
-                            // then it will be upcasted anyways, so doing anything here is not really worth the effort.
-                            if let Some(param) = binding.find_outparam_by_name("retval") {
-                                Some(param.pulsetype.clone())
-                            } else {
-                                Some(PulseValueType::PVAL_ARRAY(Box::new(PulseValueType::PVAL_EHANDLE(None))))
-                            }
-                        }
-                    }
-                } else {
-                    None
-                }
-            }
-            PulseNodeTemplate::GetVar => {
-                // compiler should handle the register generation fine without any info from here
-                // we only just provide info to connected nodes from this one
-                let name_id = node_data
-                    .get_input("variableName")
-                    .map_err(|e: EguiGraphError| anyhow!(e).context("Can not get variableName field"))?;
-                let var_name = self.state().graph
-                    .get_input(name_id)
-                    .value()
-                    .clone()
-                    .try_variable_name()
-                    .map_err(|e| anyhow!(e).context("Can not get variableName field"))?;
-                
-                self.user_state().get_variable_from_name(var_name.as_str())
-                    .map(|var| pulsevaluetype_from_valuetype(var.stored_value.clone()))
-            }
-            _ => None
-        };
-        // now update the custom type in user data so the compiler can use it
-        // this will only happen if the resulting type is not None
-        if let Some(new_type) = &opt_new_type {
-            let node_data_mut = self.state_mut().graph.nodes.get_mut(node_id).unwrap();
-            node_data_mut.user_data.custom_output_type = opt_new_type.clone();
-            self.write_console_line(
-                format!("[UI] Updating polymorphic output type of node {:?} to {:?}", node_id, new_type),
-                ConsoleMessageType::Info
-            );
-            // LOL, this needs to be slightly improved to make it more generic.
-            let outnodes = get_node_ids_connected_to_output(
-                self.state().graph.nodes.get(node_id).unwrap(), &self.state().graph, "out")
-                .unwrap_or_default();
-            let outnodes2 = get_node_ids_connected_to_output(
-                self.state().graph.nodes.get(node_id).unwrap(), &self.state().graph, "retval")
-                .unwrap_or_default();
-            let outnodes3 = get_node_ids_connected_to_output(
-                self.state().graph.nodes.get(node_id).unwrap(), &self.state().graph, "value")
-                .unwrap_or_default();
-            for node_and_input_name in outnodes.iter().chain(outnodes2.iter()).chain(outnodes3.iter()) {
-                // recursively update the output type of connected nodes
-                self.update_polymorphic_output_types(
-                    node_and_input_name.0,
-                     opt_new_type.clone(),
-                      Some(&node_and_input_name.1)
-                )?;
-            }
-        }
-        Ok(())
-    }
-    fn clone_node(&mut self, source_node_id: NodeId, pos_offset: egui::Vec2) -> NodeId {
-        let source_node_data = self.state().graph.nodes.get(source_node_id).unwrap();
-        let source_label = source_node_data.label.clone();
-        let source_user_data = source_node_data.user_data.clone();
-        let inputs = source_node_data.inputs.clone();
-        let outputs = source_node_data.outputs.clone();
-        let new_node = self.state_mut().graph.add_node(
-            source_label,
-            source_user_data,
-            |grph, node_id| {
-                // clone inputs
-                // no input names in InputParam directly, they're stored directly in the node as vec of tuples
-                for (input_name, input_id) in inputs {
-                    let input_param = grph.get_input(input_id);
-                    grph.add_input_param(
-                        node_id,
-                        input_name,
-                        input_param.typ.clone(),
-                        input_param.value.clone(),
-                        input_param.kind,
-                        true,
-                    );
-                }
-                // clone outputs
-                for (output_name, output_id) in outputs {
-                    let output_param = grph.get_output(output_id);
-                    grph.add_output_param(
-                        node_id,
-                        output_name,
-                        output_param.typ.clone(),
-                    );
-                }
-            }
+/// Adds the ports of a game function to its node, right after the binding choice input.
+pub(crate) fn add_library_binding_params(graph: &mut PulseGraph, node_id: NodeId, binding: &FunctionBinding) {
+    // If it's action type (nodes that usually don't provide a value) make it have in and out actions.
+    if binding.typ == LibraryBindingType::Action {
+        graph.add_output_param(
+            node_id,
+            "outAction".to_string(),
+            PulseDataType::Action,
         );
-        // unwraps should basically never fail, otherwise there would be bigger issues.
-        let orig_pos = self.full_state.state.node_positions.get(source_node_id).unwrap();
-        self.full_state.state.node_positions.insert(new_node,*orig_pos + pos_offset);
-        self.full_state.state.node_sizes.insert(new_node, *self.state().node_sizes.get(source_node_id).unwrap());
-        self.full_state.state.node_order.push(new_node);
-        // make sure exposed node info gets cloned as well (like function node)
-        if let Some(name) = self.user_state().exposed_nodes.get(source_node_id).cloned() {
-            self.user_state_mut().exposed_nodes.insert(new_node, format!("{name} clone"));
-        }
-        new_node
-    }
-
-    fn update_titlebar(&self, ctx: &egui::Context) {
-        let file_name = if let Some(file_path) = &self.user_state().save_file_path {
-            file_path
-                .file_name()
-                .and_then(|name| name.to_str())
-                .unwrap_or("<UNSAVED>")
-                .to_string()
-        } else {
-            "<UNSAVED>".to_string()
-        };
-        ctx.send_viewport_cmd(egui::ViewportCommand::Title(
-            format!("{APP_NAME} - {}", file_name)
-        ));
-    }
-
-    fn feed_undo_state(&mut self) {
-        self.undoer.feed_state(
-            std::time::SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .expect("time went backwards")
-                .as_secs_f64(), 
-            &self.full_state
+        graph.add_input_param(
+            node_id,
+            "ActionIn".to_string(),
+            PulseDataType::Action,
+            PulseGraphValueType::Action,
+            InputParamKind::ConnectionOrConstant,
+            true,
         );
     }
-
-    fn do_undo(&mut self) {
-        if let Some(state) = self.undoer.undo(&self.full_state) {
-            self.full_state.load_from_state(state.clone());
-            self.state_mut().connection_in_progress = None;
-        }
-    }
-
-    fn do_redo(&mut self) {
-        if let Some(state) = self.undoer.redo(&self.full_state) {
-            self.full_state.load_from_state(state.clone());
-            self.state_mut().connection_in_progress = None;
-        }
-    }
-
-    fn write_console_line(&mut self, line: String, message_type: ConsoleMessageType) {
-        let time = chrono::Local::now().format("[%H:%M:%S]");
-        self.console_lines.push_back(ConsoleLine {
-            text: format!("{} {}", time, line),
-            message_type,
-        });
-        if self.console_lines.len() > 100 {
-            self.console_lines.pop_front();
-        }
-    }
-
-    fn clear_console(&mut self) {
-        self.console_lines.clear();
-    }
-}
-
-impl PulseGraphEditor{
-    /// If the persistence feature is enabled, Called once before the first frame.
-    /// Load previous app state (if any).
-    pub fn new(cc: &eframe::CreationContext<'_>) -> anyhow::Result<Self> {
-        let mut grph = Self {
-            full_state: cc.storage
-                .and_then(|storage| eframe::get_value(storage, PERSISTENCE_KEY))
-                .unwrap_or_default(),
-            undoer: Self::get_new_undoer(),
-            current_modal_dialog: ModalWindow::default(),
-            version: FileVersion::default(),
-            console_lines: VecDeque::new()
-        };
-
-        grph.update_titlebar(&cc.egui_ctx);
-        #[cfg(feature = "nongame_asset_build")] {
-            let cfg_res: anyhow::Result<EditorConfig> = {
-                let cfg_str = std::fs::read_to_string("config.json");
-                match cfg_str {
-                    Ok(cfg_str) => serde_json::from_str(&cfg_str)
-                        .map_err(|e| anyhow::anyhow!("Failed to parse config.json: {}", e)),
-                    Err(e) => Err(anyhow::anyhow!("Failed to read config.json: {}", e)),
-                }
-            };
-            if let Err(e) = &cfg_res {
-                MessageDialog::new()
-                .set_level(rfd::MessageLevel::Error)
-                .set_title("Failed to load config file")
-                .set_buttons(rfd::MessageButtons::Ok)
-                .set_description(format!("Failed to load config.json, compiling will not work fully. Refer to the documentation on how to set up valid configuration.\n {e}"))
-                .show();
-            };
-            grph.editor_config = cfg_res.unwrap_or_default();
-        }
-
-        let bindings = load_bindings(std::path::Path::new("bindings/bindings_manifest.json"));
-        match bindings {
-            Ok(bindings) => {
-                grph.user_state_mut().bindings = bindings;
-            }
-            Err(e) => {
-                MessageDialog::new()
-                    .set_level(rfd::MessageLevel::Error)
-                    .set_title("Failed to load Pulse bindings")
-                    .set_buttons(rfd::MessageButtons::Ok)
-                    .set_description(e.to_string())
-                    .show();
-                return Err(e);
-            }
-        };
-        migrations::verify_compat(&mut grph.full_state);
-        Ok(grph)
-    }
-
-    fn handle_open_file(&mut self, filepath: &PathBuf) -> anyhow::Result<()> {
-        if let Err(e) = self.load_graph(filepath) {
-            MessageDialog::new()
-                .set_level(rfd::MessageLevel::Error)
-                .set_title("Load failed")
-                .set_buttons(rfd::MessageButtons::Ok)
-                .set_description(e.to_string())
-                .show();
-            return Err(e);
-        }
-        Ok(())
-    }
-
-    fn get_new_undoer() -> Undoer<FullGraphState> {
-        Undoer::with_settings(Settings {
-            max_undos: 100,
-            stable_time: 0.2,
-            auto_save_interval: 30.0,
-        })
-    }
-}
-
-#[cfg(feature = "persistence")]
-const PERSISTENCE_KEY: &str = "egui_node_graph";
-
-pub fn has_polymorhpic_dependent_return(
-    template: &PulseNodeTemplate,
-    user_state: &PulseGraphState,
-) -> bool {
-    match template {
-        PulseNodeTemplate::GetArrayElement
-        | PulseNodeTemplate::NewArray
-        | PulseNodeTemplate::GetVar
-        | PulseNodeTemplate::ForEach => true,
-        PulseNodeTemplate::LibraryBindingAssigned { binding: idx } => {
-            let binding = match user_state.bindings.find_function_by_id(*idx) {
-                Some(binding) => binding,
-                None => return false,
-            };
-            binding.polymorphic_return.is_some()
+    if let Some(inparams) = &binding.inparams {
+        for param in inparams {
+            let connection_kind = get_preffered_inputparamkind_from_type(&param.pulsetype);
+            let graph_types = pulse_value_type_to_node_types(&param.pulsetype);
+            graph.add_input_param(
+                node_id,
+                param.name.clone(),
+                graph_types.0,
+                graph_types.1,
+                connection_kind,
+                true,
+            );
         }
-        _ => false
     }
-}
-
-impl eframe::App for PulseGraphEditor {
-    #[cfg(feature = "persistence")]
-    /// If the persistence function is enabled,
-    /// Called by the frame work to save state before shutdown.
-    fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        eframe::set_value(storage, PERSISTENCE_KEY, &self.full_state);
-    }
-    /// Called each time the UI needs repainting, which may be many times per second.
-    /// Put your widgets into a `SidePanel`, `TopPanel`, `CentralPanel`, `Window` or `Area`.
-    fn update(&mut self, ctx: &eframe::egui::Context, _frame: &mut eframe::Frame) {
-        let mut prepended_responses: Vec<NodeResponse<PulseGraphResponse, PulseNodeData>> = vec![];
-        let mut center_on_node: Option<NodeId> = None;
-        ctx.set_visuals(egui::Visuals::dark());
-        ctx.style_mut(|s| s.interaction.selectable_labels = false);
-        self.undoer.feed_state(
-            std::time::SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .expect("time went backwards")
-                .as_secs_f64(), 
-            &self.full_state
-        );
-        if self.current_modal_dialog.is_open {
-            let modal = Modal::new(Id::new("MainModal")).show(ctx, |ui| {
-                match self.current_modal_dialog.window_type {
-                    ModalWindowType::ConfirmSave => {
-                        ui.set_width(400.0);
-                        
-                        ui.label(RichText::new("Create new graph").size(24.0));
-                        ui.label(RichText::new("Are you sure you want to create a new graph? Unsaved changes will be lost.").size(16.0));
-
-                        egui::Sides::new().show(
-                            ui,
-                |_ui| {},
-                |ui| {
-                            let btn_no = ui.add_sized([120., 30.], Button::new(RichText::new("No").size(18.0)));
-                            let btn_yes = ui.add_sized([120., 30.], Button::new(RichText::new("Yes").size(18.0)));
-                            if btn_no.clicked() {
-                                ui.close();
-                            }
-                            if btn_yes.clicked() {
-                                self.new_graph(ctx);
-                                ui.close();
-                            }
-                        });
-                    }
-                    ModalWindowType::None => {}
-                }
-            });
-            if modal.should_close() {
-                self.current_modal_dialog.is_open = false;
-            }
-        }
-        egui::TopBottomPanel::top("top").show(ctx, |ui| {
-            egui::MenuBar::new().ui(ui, |ui: &mut egui::Ui| {
-                if ui.button("Compile").clicked()
-                    || ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::R)) {
-                    if let Err(e) =
-                        compile_graph(&self.state().graph, self.user_state(), 
-                            #[cfg(feature = "nongame_asset_build")]&self.editor_config)
-                    {
-                        if let CompileError::Node(node_id, _) = e {
-                            prepended_responses.push(NodeResponse::ChangeSelectionColor(node_id, Some(egui::Color32::RED)));
-                            self.state_mut().reset_zoom(ui);
-                            center_on_node = Some(node_id);
-                        }
-                        self.write_console_line(format!("Compile error: {e}"), ConsoleMessageType::Error);
-                    } else {
-                        self.write_console_line("Graph compiled successfully".into(), ConsoleMessageType::Info);
-                    }
-                }
-                // User pressed the "Save" button or
-                if ui.button("Save").clicked()
-                    || ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::S))
-                    
-                {
-                    // is path set? if yes then save, if not promt the user first
-                    let mut perform_save: bool = true;
-                    if self.user_state().save_file_path.is_none() {
-                        perform_save = self.dialog_change_save_file();
-                        self.update_titlebar(ctx);
-                    }
-                    if perform_save {
-                        if let Err(e) = self.perform_save(None) {
-                            MessageDialog::new()
-                                .set_level(rfd::MessageLevel::Error)
-                                .set_title("Save failed")
-                                .set_buttons(rfd::MessageButtons::Ok)
-                                .set_description(e.to_string())
-                                .show();
-                        }
-                    }
-                    // else it was most likely cancelled.
-                }
-                if (ui.button("Save as...").clicked()
-                    || ctx.input(|i| {
-                        i.modifiers.command && i.modifiers.shift && i.key_pressed(egui::Key::S)
-                    }))
-                    && self.dialog_change_save_file()
-                {
-                    // TODO: DRY
-                    if let Err(e) = self.perform_save(None) {
-                        MessageDialog::new()
-                            .set_level(rfd::MessageLevel::Error)
-                            .set_title("Save failed")
-                            .set_buttons(rfd::MessageButtons::Ok)
-                            .set_description(e.to_string())
-                            .show();
-                    }
-                    self.update_titlebar(ctx);
-                }
-                if ui.button("Open").clicked() {
-                    let chosen_file = FileDialog::new()
-                        .add_filter("Pulse Graph Editor State", &["ron"])
-                        .pick_file();
-                    if let Some(filepath) = &chosen_file {
-                        if self.handle_open_file(filepath).is_ok() {
-                            self.update_titlebar(ctx);
-                        }
-                    }
-                }
-                let mut should_update_title = false;
-                ctx.input(|i| {
-                    if let Some(dropped_file) = i.raw.dropped_files.first() {
-                        if let Some(path) = &dropped_file.path {
-                            if self.handle_open_file(path).is_ok() {
-                                // defer title update after handling the DND event, otherwise we freeze due to Windows OLE bug.
-                                should_update_title = true;
-                            }
-                        }
-                    }
-                });
-                if should_update_title {
-                    self.update_titlebar(ctx);
-                }
-                if ui.button("New").clicked()
-                    && !self.state().graph.nodes.is_empty() {
-                        self.current_modal_dialog.is_open = true;
-                        self.current_modal_dialog.window_type = ModalWindowType::ConfirmSave;
-                    }
-
-                if ui.add_enabled(
-                    self.undoer.has_undo(&self.full_state), egui::Button::new("⟲")
-                    ).clicked() ||
-                    ctx.input(|i| {
-                        i.modifiers.command && i.key_pressed(egui::Key::Z)
-                    })
-                {
-                    self.do_undo();
-                }
-                else if ui.add_enabled(
-                    self.undoer.has_redo(&self.full_state), egui::Button::new("⟳")
-                    ).clicked() ||
-                    ctx.input(|i| {
-                        i.modifiers.command && i.key_pressed(egui::Key::Y)
-                    })
-                {
-                    self.do_redo();
-                }
-                if !ctx.wants_keyboard_input() 
-                    && ctx.input(|i| i.modifiers.shift && i.key_pressed(egui::Key::D)) {
-                    let selected_nodes: Vec<_> = self.state().selected_nodes.to_vec();
-                    let mut new_nodes: Vec<_> = vec![];
-                    for node_id in selected_nodes {
-                        new_nodes.push(
-                            self.clone_node(node_id, Vec2::new(20.0, 20.0))
-                        );
-                    }
-                    self.state_mut().selected_nodes = new_nodes;
-                }
-                ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
-                    if ui.button("Check for updates").clicked() {
-                        thread::spawn(move || {
-                            if let Err(e) = smol::block_on(PulseGraphEditor::check_for_updates()) {
-                                MessageDialog::new()
-                                    .set_level(rfd::MessageLevel::Error)
-                                    .set_title("Update check failed")
-                                    .set_buttons(rfd::MessageButtons::Ok)
-                                    .set_description(e.to_string())
-                                    .show();
-                            }
-                        });
-                    }
-                    ui.label(env!("CARGO_PKG_VERSION"));
-                });
-            });
-        });
-        let mut output_scheduled_for_deletion: Option<usize> = None; // we can get away with just one reference (it's not like the user can click more than one at once)
-        let mut variable_scheduled_for_deletion: Option<usize> = None;
-        egui::SidePanel::left("left_panel").show(ctx, |ui| {
-            egui::CollapsingHeader::new("Advanced")
-                .default_open(false)
-                .show(ui, |ui| {
-                    ui.horizontal(|ui| {
-                        ui.label("Graph domain").on_hover_text("Suggests which context the graph can be used in, and what features will be available.");
-                        ui.text_edit_singleline(&mut self.user_state_mut().graph_domain);
-                    });
-                    ui.horizontal(|ui| {
-                        ui.label("Graph sub-type").on_hover_text("The type on which the graph will be ran on eg. point entity/model entity/panel.");
-                        ui.text_edit_singleline(&mut self.user_state_mut().graph_subtype);
-                    });
-                });
-            egui::ScrollArea::vertical().show(ui, |ui| {
-                ui.label("Public outputs:");
-                if ui.button("Add output").clicked() {
-                    self.user_state_mut().public_outputs.push(OutputDefinition {
-                        name: String::default(),
-                        data_type: PulseDataType::default(),
-                        value_type: PulseGraphValueType::default(),
-                        typ: PulseValueType::PVAL_INT(None),
-                        typ_old: PulseValueType::PVAL_INT(None),
-                    });
-                }
-                let (out_scheduled_for_deletion, updated_output_idx) = appwidgets::public_output_list_widget(
-                    ui, &mut self.full_state.user_state.public_outputs, &self.full_state.user_state.bindings
-                );
-                output_scheduled_for_deletion = out_scheduled_for_deletion;
-                if let Some(output_idx) = updated_output_idx {
-                    for node_id in self.full_state.state.graph.nodes.keys().collect::<Vec<_>>() {
-                        self.update_node_public_output_types(node_id, output_idx);
-                    }
-                }
-
-                ui.separator();
-
-                ui.label("Variables:");
-                if ui.button("Add variable").clicked() {
-                    self.full_state.user_state.variables.push(PulseVariable {
-                        name: "variable".to_string(),
-                        data_type: PulseDataType::default(),
-                        stored_value: PulseGraphValueType::default(),
-                        typ_and_default_value: PulseValueType::PVAL_INVALID,
-                        default_value_buffer: String::default(),
-                    });
-                }
-                let variable_type_list = PulseDataType::get_variable_supported_types();
-                let (var_scheduled_for_deletion, updated_var_idx) = appwidgets::variable_list_widget(
-                    ui,
-                    &mut self.full_state.user_state.variables,
-                    variable_type_list,
-                    &self.full_state.user_state.bindings
-                );
-                variable_scheduled_for_deletion = var_scheduled_for_deletion;
-                if let Some(var_idx) = updated_var_idx {
-                    for node_id in self.full_state.state.graph.nodes.keys().collect::<Vec<_>>() {
-                        self.update_node_variable_types(node_id, var_idx);
-                    }
-                }
-            });
-        });
-        if let Some(output_scheduled_for_deletion) = output_scheduled_for_deletion {
-            self.user_state_mut()
-                .public_outputs
-                .remove(output_scheduled_for_deletion);
-        }
-        if let Some(variable_scheduled_for_deletion) = variable_scheduled_for_deletion {
-            self.user_state_mut()
-                .variables
-                .remove(variable_scheduled_for_deletion);
-        }
-
-        if ctx.input(|i| i.key_released(egui::Key::Delete)) {
-            // delete selected nodes
-            for node_id in self.state().selected_nodes.iter() {
-                prepended_responses.push(NodeResponse::DeleteNodeUi(*node_id));
-            }
-        }
-
-        egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
-            egui::ScrollArea::vertical()
-            .auto_shrink(false)
-            .stick_to_bottom(true)
-            .show(ui, |ui| {
-                for line in &self.console_lines {
-                    let color = match line.message_type {
-                        ConsoleMessageType::Info => egui::Color32::WHITE,
-                        ConsoleMessageType::Warning => egui::Color32::YELLOW,
-                        ConsoleMessageType::Error => egui::Color32::RED,
-                    };
-                    ui.label(RichText::new(&line.text).color(color));
-                }
-            });
-        });
-
-        let graph_response = egui::CentralPanel::default()
-            .show(ctx, |ui| {
-                let graph_response = self.full_state.state.draw_graph_editor(
-                    ui,
-                    AllMyNodeTemplates {
-                        game_function_count: self.user_state().bindings.gamefunctions.len()
-                    },
-                    &mut self.full_state.user_state,
-                    prepended_responses,
-                );
-
-                if let Some(node_id) = center_on_node.take() {
-                    if let Some(node_pos) = self.state().node_positions.get(node_id) {
-                        let node_size = self
-                            .state()
-                            .node_sizes
-                            .get(node_id)
-                            .copied()
-                            .unwrap_or(Vec2::new(200.0, 200.0));
-                        let editor_size = ui.max_rect().size();
-                        let node_center = node_pos.to_vec2() + node_size / 2.0;
-                        self.state_mut().pan_zoom.pan = editor_size / 2.0 - node_center;
-                    }
-                }
-
-                graph_response
-            })
-            .inner;
-
-        for node_response in graph_response.node_responses {
-            // handle all responses generated by the graph ui...
-            match node_response {
-                NodeResponse::User(user_event) => {
-                    match user_event {
-                        // node that supports adding parameters is trying to add one
-                        PulseGraphResponse::AddOutputParam(node_id, name, datatype) => {
-                            self.state_mut().graph.add_output_param(
-                                node_id,
-                                name,
-                                datatype,
-                            );
-                        }
-                        PulseGraphResponse::AddCustomInputParam(
-                            node_id,
-                            name,
-                            datatype,
-                            valuetype,
-                            paramkind,
-                            autoindex
-                        ) => {
-                            let param_list = &mut self.state_mut().graph.nodes.get_mut(node_id).unwrap().user_data.added_inputs;
-                            let idx = param_list.len();
-                            let name = if autoindex {
-                                format!("{name}{}", idx)
-                            } else {
-                                name
-                            };
-                            let input_id = self.state_mut().graph.add_input_param(
-                                node_id,
-                                name,
-                                datatype,
-                                valuetype,
-                                paramkind,
-                                true
-                            );
-                            self.state_mut().graph.nodes.get_mut(node_id).unwrap().user_data.added_inputs.push(input_id);
-                        }
-                        PulseGraphResponse::RemoveCustomInputParam(node_id, input_id) => {
-                            let param_list = &mut self.state_mut().graph.nodes.get_mut(node_id).unwrap().user_data.added_inputs;
-                            if let Some(pos) = param_list.iter().position(|x| *x == input_id) {
-                                param_list.remove(pos);
-                            }
-                            self.state_mut().graph.remove_input_param(input_id);
-                        }
-                        PulseGraphResponse::RemoveOutputParam(node_id, name) => {
-                            // node that supports adding parameters is removing one
-                            let param = self
-                                .state()
-                                .graph
-                                .nodes
-                                .get(node_id)
-                                .unwrap()
-                                .get_output(&name)
-                                .unwrap();
-                            self.state_mut().graph.remove_output_param(param);
-                        }
-                        PulseGraphResponse::ChangeOutputParamType(node_id, out_idx) => {
-                            match node_id {
-                                Some(node_id) => { 
-                                    self.update_node_public_output_types(node_id, out_idx); 
-                                }
-                                None => {
-                                    // send update to all nodes
-                                    for node_id in self.full_state.state.graph.nodes.keys().collect::<Vec<_>>() {
-                                        self.update_node_public_output_types(node_id, out_idx);
-                                    }
-                                }
-                            }
-                        }
-                        PulseGraphResponse::ChangeVariableParamType(node_id, var_idx) => {
-                            match node_id {
-                                Some(node_id) => { 
-                                    self.update_node_variable_types(node_id, var_idx); 
-                                }
-                                None => {
-                                    // send update to all nodes
-                                    for node_id in self.full_state.state.graph.nodes.keys().collect::<Vec<_>>() {
-                                        self.update_node_variable_types(node_id, var_idx);
-                                    }
-                                }
-                            }
-                        }
-                        PulseGraphResponse::ChangeParamType(node_id, name, typ) => {
-                            self.update_node_inputs_outputs_types(node_id, name.into(), Some(typ));
-                        }
-                        PulseGraphResponse::ChangeEventBinding(node_id, bindings) => {
-                            //let node = self.state.graph.nodes.get_mut(node_id).unwrap();
-                            self.update_event_binding_params(&node_id, &bindings);
-                        }
-                        PulseGraphResponse::ChangeFunctionBinding(node_id, bindings) => {
-                            //let node = self.state.graph.nodes.get_mut(node_id).unwrap();
-                            self.update_library_binding_params(&node_id, &bindings);
-                        }
-                        PulseGraphResponse::ChangeRemoteNodeId(node_id, node_id_refrence) => {
-                            self.update_remote_node_params(&node_id, &node_id_refrence);
-                        }
-                        PulseGraphResponse::UpdatePolymorphicTypes(node_id) => {
-                            if let Err(e) = self.update_polymorphic_output_types(node_id, None, None) {
-                                self.write_console_line(
-                                    format!("[UI] Failed to update polymorphic output types: {e}"), 
-                                    ConsoleMessageType::Warning
-                                );
-                            }
-                        }
-                    }
-                }
-                NodeResponse::DeleteNodeFull { node_id, .. } => {
-                    self.user_state_mut().exposed_nodes.remove(node_id);
-                }
-                NodeResponse::CreatedNode(node_id) => {
-                    // This stuff is actually insane btw.
-                    // if the node is a library binding, then update the parameters
-                    if let PulseNodeTemplate::LibraryBindingAssigned { binding } 
-                        = self.state().graph.nodes.get(node_id).unwrap().user_data.template {
-                        let binding_opt = self.user_state().bindings.find_function_by_id(binding).cloned();
-                        if let Some(binding) = binding_opt {
-                            self.update_library_binding_params(&node_id, &binding);
-                        }
-                    }
-                    self.feed_undo_state();
-                }
-                NodeResponse::ConnectEventEnded { output, input: _ , input_hook: _} => {
-                    let graph = &self.state().graph;
-                    let node_id = graph.get_output(output).node;
-                    if let Err(e) = self.update_polymorphic_output_types(node_id, None, None) {
-                        self.write_console_line(
-                            format!("[UI] Warning: Failed to update polymorphic output types: {e}"),
-                            ConsoleMessageType::Warning
-                        );
-                    }
-                }
-                _ => {}
-            }
+    if let Some(outparams) = &binding.outparams {
+        for param in outparams {
+            graph.add_output_param(
+                node_id,
+                param.name.clone(),
+                pulse_value_type_to_node_types(&param.pulsetype).0,
+            );
         }
-        // for (nodeid, name) in output_node_updates {
-        //     self.update_output_node_param(nodeid, &name, "param");
-        // }
     }
 }
-
@@ -178,10 +178,34 @@ pub fn inner_type_choice_widget_from_datatype(ui: &mut Ui, value: &mut PulseGrap
     changed
 }
 
+// Name field that only reports the new name once editing is done, so renames can update
+// the nodes using the name in one go. Escape cancels the edit.
+fn name_edit(ui: &mut Ui, id_salt: impl std::hash::Hash, name: &str, hint: &str) -> Option<String> {
+    let id = ui.id().with(id_salt);
+    let mut edited = ui.data(|data| data.get_temp::<String>(id)).unwrap_or_else(|| name.to_string());
+    let response = ui.add(egui::TextEdit::singleline(&mut edited)
+        .id(id)
+        .font(TextStyle::Heading)
+        .hint_text(hint)
+    );
+    if response.lost_focus() {
+        ui.data_mut(|data| data.remove::<String>(id));
+        let cancelled = ui.input(|i| i.key_pressed(egui::Key::Escape));
+        (!cancelled && edited != name).then_some(edited)
+    } else {
+        if response.has_focus() {
+            ui.data_mut(|data| data.insert_temp(id, edited));
+        }
+        None
+    }
+}
+
+/// Returns the index of a variable to delete, of one that changed type, and a rename that was entered.
 pub fn variable_list_widget(ui: &mut Ui, variable_list: &mut [PulseVariable], type_choices: Vec<PulseDataType>, bindings: &GraphBindings)
- -> (Option<usize>, Option<VariableIndex>) {
+ -> (Option<usize>, Option<VariableIndex>, Option<(usize, String)>) {
     let mut variable_idx_scheduled_for_deletion: Option<usize> = None;
     let mut variable_idx_to_update: Option<VariableIndex> = None;
+    let mut rename: Option<(usize, String)> = None;
     for (idx, var) in variable_list.iter_mut().enumerate() {
         ui.add_space(4.0);
         egui::Frame::default()
@@ -193,10 +217,9 @@ pub fn variable_list_widget(ui: &mut Ui, variable_list: &mut [PulseVariable], ty
                 if ui.button("X").clicked() {
                     variable_idx_scheduled_for_deletion = Some(idx);
                 }
-                ui.add(egui::TextEdit::singleline(&mut var.name)
-                    .font(TextStyle::Heading)
-                    .hint_text("Variable name")
-                );
+                if let Some(new_name) = name_edit(ui, ("varname", idx), &var.name, "Variable name") {
+                    rename = Some((idx, new_name));
+                }
             });
             ui.horizontal(|ui| {
                 ui.label("Param type");
@@ -223,13 +246,15 @@ pub fn variable_list_widget(ui: &mut Ui, variable_list: &mut [PulseVariable], ty
             });
         });
     }
-    (variable_idx_scheduled_for_deletion, variable_idx_to_update)
+    (variable_idx_scheduled_for_deletion, variable_idx_to_update, rename)
 }
 
+/// Returns the index of an output to delete, of one that changed type, and a rename that was entered.
 pub fn public_output_list_widget(ui: &mut Ui, output_list: &mut [OutputDefinition], bindings: &GraphBindings)
--> (Option<usize>, Option<PublicOutputIndex>) {
+-> (Option<usize>, Option<PublicOutputIndex>, Option<(usize, String)>) {
     let mut output_scheduled_for_deletion: Option<usize> = None;
     let mut output_idx_to_update: Option<PublicOutputIndex> = None;
+    let mut rename: Option<(usize, String)> = None;
     for (idx, outputdef) in output_list.iter_mut().enumerate() {
         ui.add_space(4.0);
         egui::Frame::default()
@@ -241,10 +266,9 @@ pub fn public_output_list_widget(ui: &mut Ui, output_list: &mut [OutputDefinitio
                 if ui.button("X").clicked() {
                     output_scheduled_for_deletion = Some(idx);
                 }
-                ui.add(egui::TextEdit::singleline(&mut outputdef.name)
-                    .font(TextStyle::Heading)
-                    .hint_text("Output name")
-                );
+                if let Some(new_name) = name_edit(ui, ("outputname", idx), &outputdef.name, "Output name") {
+                    rename = Some((idx, new_name));
+                }
             });
             ui.horizontal(|ui| {
                 ui.label("Param type");
@@ -267,5 +291,5 @@ pub fn public_output_list_widget(ui: &mut Ui, output_list: &mut [OutputDefinitio
             });
         });
     }
-    (output_scheduled_for_deletion, output_idx_to_update)
+    (output_scheduled_for_deletion, output_idx_to_update, rename)
 }
\ No newline at end of file
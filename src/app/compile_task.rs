@@ -0,0 +1,113 @@
+use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+use std::time::{Duration, Instant};
+use smol::channel::{Receiver, Sender, TryRecvError};
+use crate::compiler::{compile_graph, CompileError, CompileObserver};
+#[cfg(feature = "nongame_asset_build")]
+use super::types::EditorConfig;
+use super::FullGraphState;
+
+pub enum CompileMessage {
+    Progress(String),
+    Finished(Result<(), CompileError>),
+}
+
+struct ChannelObserver {
+    sender: Sender<CompileMessage>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CompileObserver for ChannelObserver {
+    fn progress(&self, message: String) {
+        // the receiver might be gone if the editor dropped the task, nothing to do then.
+        let _ = self.sender.try_send(CompileMessage::Progress(message));
+    }
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Compilation running on a background thread, on a snapshot of the graph taken when it was started.
+pub struct CompileTask {
+    receiver: Receiver<CompileMessage>,
+    cancelled: Arc<AtomicBool>,
+    started: Instant,
+    // revision of the graph in the editor when the snapshot was taken
+    revision: u64,
+    // set when the graph was edited after the snapshot was taken
+    pub stale: bool,
+}
+
+impl CompileTask {
+    pub fn start(
+        snapshot: FullGraphState,
+        revision: u64,
+        #[cfg(feature = "nongame_asset_build")]
+        config: EditorConfig,
+    ) -> Self {
+        let (sender, receiver) = smol::channel::unbounded();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let observer = ChannelObserver { sender, cancelled: cancelled.clone() };
+        smol::unblock(move || {
+            let res = compile_graph(&snapshot.state.graph, &snapshot.user_state,
+                #[cfg(feature = "nongame_asset_build")] &config,
+                &observer);
+            let _ = observer.sender.try_send(CompileMessage::Finished(res));
+        }).detach();
+        Self {
+            receiver,
+            cancelled,
+            started: Instant::now(),
+            revision,
+            stale: false,
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelling(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Marks the result as stale if the graph was edited since the snapshot was taken.
+    pub fn check_stale(&mut self, revision: u64) {
+        if revision != self.revision {
+            self.stale = true;
+        }
+    }
+
+    /// Moves to a new revision of a graph that wasn't edited, like when its tab is shown again.
+    pub fn rebase(&mut self, revision: u64) {
+        self.revision = revision;
+    }
+
+    /// Returns all messages received since the last call.
+    pub fn poll(&self) -> Vec<CompileMessage> {
+        let mut messages = vec![];
+        loop {
+            match self.receiver.try_recv() {
+                Ok(msg) => {
+                    let finished = matches!(msg, CompileMessage::Finished(_));
+                    messages.push(msg);
+                    if finished {
+                        break;
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Closed) => {
+                    // worker went away without reporting a result (most likely panicked)
+                    messages.push(CompileMessage::Finished(Err(CompileError::Generic(
+                        anyhow::anyhow!("Compilation stopped unexpectedly")
+                    ))));
+                    break;
+                }
+            }
+        }
+        messages
+    }
+}
@@ -0,0 +1,30 @@
+// Conversions the editor inserts between ports of different types,
+// when the user tries to connect them directly.
+use super::types::{PulseDataType, PulseNodeTemplate};
+use crate::typing::PulseValueType;
+
+pub(super) enum Conversion {
+    /// A `Convert` node set to this output type, it compiles to CONVERT_VALUE.
+    Cast(PulseValueType),
+    /// A dedicated conversion node.
+    Node(PulseNodeTemplate),
+    /// A game function, by its library name.
+    Function(&'static str),
+}
+
+pub(super) fn find_conversion(from: &PulseDataType, to: &PulseDataType) -> Option<Conversion> {
+    let conversion = match (from, to) {
+        (PulseDataType::Integer, PulseDataType::Scalar) => Conversion::Cast(PulseValueType::PVAL_FLOAT(None)),
+        (PulseDataType::Scalar, PulseDataType::Integer) => Conversion::Cast(PulseValueType::PVAL_INT(None)),
+        (PulseDataType::Integer | PulseDataType::Scalar, PulseDataType::String) => {
+            Conversion::Cast(PulseValueType::PVAL_STRING(None))
+        }
+        (PulseDataType::String, PulseDataType::EntityName) => Conversion::Node(PulseNodeTemplate::StringToEntityName),
+        (PulseDataType::EntityName, PulseDataType::EHandle) => Conversion::Function("CPulseServerFuncs::FindEntity"),
+        (PulseDataType::EHandle, PulseDataType::EntityName) => Conversion::Function("CPulseServerFuncs::GetEntityName"),
+        (PulseDataType::Scalar, PulseDataType::GameTime) => Conversion::Function("CPulseMathlib::FloatToGameTime"),
+        (PulseDataType::GameTime, PulseDataType::Scalar) => Conversion::Function("CPulseMathlib::GameTimeToFloat"),
+        _ => return None,
+    };
+    Some(conversion)
+}
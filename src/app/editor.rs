@@ -0,0 +1,2783 @@
+
+use delegate::delegate;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::panic::resume_unwind;
+use std::sync::Arc;
+use std::{path::{Path, PathBuf}, fs, thread};
+use core::panic;
+use eframe::egui::{Button, Pos2, Vec2};
+#[cfg(feature = "persistence")]
+use serde::{Deserialize, Serialize};
+use rfd::{FileDialog, MessageDialog};
+use anyhow::anyhow;
+use eframe::egui::{self, Modal, Id, RichText};
+use egui_node_graph2::*;
+use crate::bindings::*;
+use crate::compiler::CompileError;
+use crate::compiler::binary::read_compiled_resource;
+use crate::pulsetypes::*;
+use crate::typing::*;
+use crate::utils::get_node_ids_connected_to_output;
+use super::types::*;
+use super::search::GraphSearch;
+use super::flow::{ExecutionOrder, FlowOverlay};
+use super::history::History;
+use super::keymap::{Action, Keymap};
+use super::recovery::{self, Autosaver, RecoveredSession};
+use super::references::NamedItem;
+use super::{appwidgets, migrations, FullGraphState};
+use super::compile_task::{CompileMessage, CompileTask};
+use super::conversions::{find_conversion, Conversion};
+use crate::watch::{self, FileWatcher};
+
+static APP_NAME: &str = "Pulse Graph Editor";
+
+// Name of the graph shown in the title bar and on its tab.
+fn graph_title(user_state: &PulseGraphState) -> String {
+    user_state
+        .save_file_path
+        .as_ref()
+        .and_then(|file_path| file_path.file_name())
+        .and_then(|name| name.to_str())
+        .unwrap_or("<UNSAVED>")
+        .to_string()
+}
+#[derive(Default, Clone)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub enum ModalWindowType {
+    #[default]
+    None,
+    /// Save, discard or cancel before doing something that would lose unsaved changes.
+    ConfirmSave(UnsavedAction),
+    /// Graphs autosaved by a session that didn't exit cleanly.
+    RestoreRecovered,
+    /// Deleting a variable or public output that nodes still refer to.
+    DeleteInUse {
+        item: NamedItem,
+        index: usize,
+        users: Vec<NodeId>,
+        retarget: Option<usize>,
+    },
+}
+
+// Things that throw away the graph of a tab, or every tab.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub enum UnsavedAction {
+    NewGraph,
+    CloseTab(usize),
+    Exit,
+}
+
+// What to do with the nodes still using a deleted variable or public output.
+enum DeleteInUseAction {
+    RemoveUsers,
+    Retarget(usize),
+}
+
+#[derive(Default, Clone)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct ModalWindow {
+    pub window_type: ModalWindowType,
+    pub is_open: bool,
+}
+#[derive(Default, Clone)]
+enum ConsoleMessageType {
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
+#[derive(Default, Clone)]
+struct ConsoleLine {
+    text: String,
+    message_type: ConsoleMessageType,
+}
+// Decompiled contents of a .vpulse_c file, shown in a separate window.
+#[derive(Default, Clone)]
+struct CompiledInspector {
+    source_path: PathBuf,
+    text: String,
+}
+
+// A graph open in a tab. The active tab's document lives in the editor itself (full_state, history, ...),
+// its slot in `PulseGraphEditor::tabs` only holds it while another tab is active.
+#[derive(Default)]
+struct GraphTab {
+    full_state: FullGraphState,
+    history: History,
+    console_lines: VecDeque<ConsoleLine>,
+    compile_task: Option<CompileTask>,
+    pending_watch_snapshot: Option<FullGraphState>,
+}
+
+impl GraphTab {
+    fn new(full_state: FullGraphState) -> Self {
+        Self {
+            full_state,
+            ..Default::default()
+        }
+    }
+}
+
+
+#[derive(Default)]
+pub struct PulseGraphEditor {
+    #[allow(unused)]
+    version: FileVersion,
+    full_state: FullGraphState,
+    #[cfg(feature = "nongame_asset_build")]
+    editor_config: EditorConfig,
+    current_modal_dialog: ModalWindow,
+    history: History,
+    show_history: bool,
+    console_lines: VecDeque<ConsoleLine>,
+    compiled_inspector: Option<CompiledInspector>,
+    compile_task: Option<CompileTask>,
+    watch_mode: bool,
+    file_watcher: FileWatcher,
+    // what watch mode compiles once the running compilation is done
+    pending_watch_snapshot: Option<FullGraphState>,
+    show_minimap: bool,
+    flow_overlay: FlowOverlay,
+    // grid settings, applied to the graph of whichever tab is shown
+    grid: Grid,
+    keymap: Keymap,
+    search: GraphSearch,
+    // goes up whenever the shown graph changes, or another one is shown
+    graph_revision: u64,
+    tabs: Vec<GraphTab>,
+    active_tab: usize,
+    // loaded once and shared by the graphs of every tab
+    bindings: Arc<GraphBindings>,
+    autosaver: Autosaver,
+    // last title sent to the window, it changes along with the modified marker
+    titlebar: String,
+    // most recently opened or saved first
+    recent_files: Vec<PathBuf>,
+    // favorite and recently created node kinds, shared by every tab
+    finder_memory: NodeFinderMemory<PulseNodeTemplate>,
+    // left behind by crashed sessions, waiting for the user to restore or discard them
+    recovered: Vec<RecoveredSession>,
+}
+
+impl PulseGraphEditor {
+    delegate! {
+        to self.full_state {
+            pub fn state(&self) -> &MyEditorState;
+            pub fn state_mut(&mut self) -> &mut MyEditorState;
+            pub fn user_state(&self) -> &PulseGraphState;
+            pub fn user_state_mut(&mut self) -> &mut PulseGraphState;
+            fn add_node_input_simple(
+                &mut self,
+                node_id: NodeId,
+                data_typ: PulseDataType,
+                value_typ: PulseGraphValueType,
+                input_name: &str,
+                kind: InputParamKind,
+            );
+            fn add_node_output_simple(&mut self, node_id: NodeId, data_typ: PulseDataType, output_name: &str);
+        }
+    }
+    // Retyping ports replaces them, so the nodes are noted for the undo history first.
+    // Only nodes referring to an output or variable can be affected.
+    pub fn update_node_public_output_types(&mut self, node_id: NodeId, output_idx: PublicOutputIndex) {
+        if self.state().graph.nodes.get(node_id).is_some_and(|node| node.get_input("outputName").is_ok()) {
+            self.history.touch_node(&self.full_state, node_id);
+            self.full_state.update_node_public_output_types(node_id, output_idx);
+        }
+    }
+    pub fn update_node_variable_types(&mut self, node_id: NodeId, variable_idx: VariableIndex) {
+        if self.state().graph.nodes.get(node_id).is_some_and(|node| node.get_input("variableName").is_ok()) {
+            self.history.touch_node(&self.full_state, node_id);
+            self.full_state.update_node_variable_types(node_id, variable_idx);
+        }
+    }
+    fn update_library_binding_params(&mut self, node_id: &NodeId, binding: &FunctionBinding) {
+        self.history.touch_node(&self.full_state, *node_id);
+        self.full_state.update_library_binding_params(node_id, binding);
+    }
+    fn update_event_binding_params(&mut self, node_id: &NodeId, binding: &EventBinding) {
+        self.history.touch_node(&self.full_state, *node_id);
+        self.full_state.update_event_binding_params(node_id, binding);
+    }
+    fn save_graph(&self, filepath: &PathBuf) -> Result<(), anyhow::Error> {
+        self.full_state.save_graph(filepath)
+    }
+    // perform a save including including some cleanup
+    fn perform_save(&mut self, filepath: Option<&PathBuf>) -> anyhow::Result<()> {
+        let dest_path;
+        // remove the path on manual save (we don't use serde skip because we want to save it within autosaves)
+        let save_path = self.full_state.user_state.save_file_path.take();
+        if let Some(filepath) = filepath {
+            dest_path = filepath;
+        } else {
+            // if no filepath is provided, assume the one in saved state
+            if let Some(filepath) = save_path.as_ref() {
+                dest_path = filepath;
+            } else {
+                return Err(anyhow!(
+                    "No file path provided for saving the graph. This should not happen"
+                ));
+            }
+        }
+        self.save_graph(dest_path)?;
+        self.add_recent_file(dest_path);
+        // restore the path info to memory.
+        self.full_state.user_state.save_file_path = save_path;
+        self.history.mark_saved();
+        // the recovery copy isn't needed anymore
+        self.autosaver.pending = true;
+        Ok(())
+    }
+    // saves the active graph, asking where to first if it was never saved. Returns false if it wasn't saved.
+    fn save_active(&mut self) -> bool {
+        if self.user_state().save_file_path.is_none() && !self.dialog_change_save_file() {
+            // most likely cancelled.
+            return false;
+        }
+        if let Err(e) = self.perform_save(None) {
+            MessageDialog::new()
+                .set_level(rfd::MessageLevel::Error)
+                .set_title("Save failed")
+                .set_buttons(rfd::MessageButtons::Ok)
+                .set_description(e.to_string())
+                .show();
+            return false;
+        }
+        true
+    }
+    // promts user to choose a file to save the graph to and remembers the location for saving.
+    fn dialog_change_save_file(&mut self) -> bool {
+        let chosen_file = FileDialog::new()
+            .add_filter("Pulse Graph Editor State", &["ron"])
+            .save_file();
+        let did_pick = chosen_file.as_ref().is_some(); // if not, the user cancelled so we should note that
+        if did_pick {
+            self.full_state.user_state.save_file_path = chosen_file;
+        }
+        did_pick
+    }
+   
+    fn load_graph(&mut self, filepath: &PathBuf) -> Result<(), anyhow::Error> {
+        self.clear_console();
+        let res = self.full_state.load_state(filepath);
+        if res.is_ok() {
+            self.history = History::default();
+            self.autosaver.pending = true;
+            self.graph_revision += 1;
+        }
+        res
+    }
+    fn new_graph(&mut self, ctx: &egui::Context) {
+        self.clear_console();
+        self.history = History::default();
+        self.autosaver.pending = true;
+        self.graph_revision += 1;
+        self.full_state.state = MyEditorState::default();
+        self.user_state_mut().load_from(PulseGraphState::default());
+        self.user_state_mut().save_file_path = None;
+        self.update_titlebar(ctx);
+    }
+    // pub fn update_output_node_param(&mut self, node_id: NodeId, name: &String, input_name: &str) {
+    //     let param = self
+    //         .state_mut()
+    //         .graph
+    //         .nodes
+    //         .get_mut(node_id)
+    //         .unwrap()
+    //         .get_input(input_name);
+    //     if let Ok(param) = param {
+    //         self.state_mut().graph.remove_input_param(param);
+    //     }
+    //     let public_outputs: Vec<_> = self.user_state().public_outputs.to_vec();
+    //     for output in public_outputs {
+    //         if output.name == *name {
+    //             match output.typ {
+    //                 PulseValueType::PVAL_FLOAT(_) | PulseValueType::PVAL_INT(_) => {
+    //                     self.state_mut().graph.add_input_param(
+    //                         node_id,
+    //                         String::from(input_name),
+    //                         PulseDataType::Scalar,
+    //                         PulseGraphValueType::Scalar { value: 0f32 },
+    //                         InputParamKind::ConnectionOrConstant,
+    //                         true,
+    //                     );
+    //                 }
+    //                 PulseValueType::PVAL_STRING(_) => {
+    //                     self.state_mut().graph.add_input_param(
+    //                         node_id,
+    //                         String::from(input_name),
+    //                         PulseDataType::String,
+    //                         PulseGraphValueType::String {
+    //                             value: String::default(),
+    //                         },
+    //                         InputParamKind::ConnectionOrConstant,
+    //                         true,
+    //                     );
+    //                 }
+    //                 PulseValueType::PVAL_VEC3(_) => {
+    //                     self.state_mut().graph.add_input_param(
+    //                         node_id,
+    //                         String::from(input_name),
+    //                         PulseDataType::Vec3,
+    //                         PulseGraphValueType::Vec3 {
+    //                             value: Vec3 {
+    //                                 x: 0.0,
+    //                                 y: 0.0,
+    //                                 z: 0.0,
+    //                             },
+    //                         },
+    //                         InputParamKind::ConnectionOrConstant,
+    //                         true,
+    //                     );
+    //                 }
+    //                 PulseValueType::PVAL_EHANDLE(_) => {
+    //                     self.state_mut().graph.add_input_param(
+    //                         node_id,
+    //                         String::from(input_name),
+    //                         PulseDataType::EHandle,
+    //                         PulseGraphValueType::EHandle,
+    //                         InputParamKind::ConnectionOnly,
+    //                         true,
+    //                     );
+    //                 }
+    //                 _ => {}
+    //             }
+    //         }
+    //     }
+    // }
+
+
+    pub fn update_node_inputs_outputs_types<'a>(
+        &mut self,
+        node_id: NodeId,
+        name: std::borrow::Cow<'a, str>,
+        new_type: Option<PulseValueType>,
+    ) {
+        self.history.touch_node(&self.full_state, node_id);
+        let node = self.state().graph.nodes.get(node_id).unwrap();
+        match node.user_data.template {
+            PulseNodeTemplate::Operation => {
+                if new_type.is_none() {
+                    panic!("update_node_inputs_outputs() ended up on node that requires new value type from response, but it was not provided");
+                }
+                let new_type = new_type.unwrap();
+                let param_a = node.get_input("A");
+                let param_b = node.get_input("B");
+                let param_out = node.get_output("out");
+                if param_a.is_err() || param_b.is_err() || param_out.is_err() {
+                    panic!("node that requires inputs 'A', 'B' and output 'out', but one of them was not found");
+                }
+                self.state_mut().graph.remove_input_param(param_a.unwrap());
+                self.state_mut().graph.remove_input_param(param_b.unwrap());
+                self.state_mut().graph.remove_output_param(param_out.unwrap());
+
+                let types = pulse_value_type_to_node_types(&new_type);
+                self.add_node_input_simple(
+                    node_id,
+                    types.0.clone(),
+                    types.1.clone(),
+                    "A",
+                    InputParamKind::ConnectionOrConstant,
+                );
+                self.add_node_input_simple(
+                    node_id,
+                    types.0.clone(),
+                    types.1,
+                    "B",
+                    InputParamKind::ConnectionOrConstant,
+                );
+                self.add_node_output_simple(node_id, types.0, "out");
+            }
+            PulseNodeTemplate::Convert => {
+                if name == "typefrom" {
+                    let param_input = node.get_input("input");
+                    if let Ok(param_input) = param_input {
+                        self.state_mut().graph.remove_input_param(param_input);
+                        let types = pulse_value_type_to_node_types(&new_type.unwrap());
+                        self.add_node_input_simple(
+                            node_id,
+                            types.0,
+                            types.1,
+                            "input",
+                            InputParamKind::ConnectionOrConstant,
+                        );
+                    }
+                } else if name == "typeto" {
+                    let param_output = node.get_output("out");
+                    if let Ok(param_output) = param_output {
+                        self.state_mut().graph.remove_output_param(param_output);
+                        let types = pulse_value_type_to_node_types(&new_type.unwrap());
+                        self.add_node_output_simple(node_id, types.0, "out");
+                    }
+                }
+            }
+            PulseNodeTemplate::Compare | PulseNodeTemplate::CompareOutput => {
+                if new_type.is_none() {
+                    panic!("update_node_inputs_outputs() ended up on node that requires new value type from response, but it was not provided");
+                }
+                let new_type = new_type.unwrap();
+                let param_a = node.get_input("A");
+                let param_b = node.get_input("B");
+                if param_a.is_err() || param_b.is_err() {
+                    panic!("node that requires inputs 'A' and 'B', but one of them was not found");
+                }
+                self.state_mut().graph.remove_input_param(param_a.unwrap());
+                self.state_mut().graph.remove_input_param(param_b.unwrap());
+
+                let types = pulse_value_type_to_node_types(&new_type);
+                self.add_node_input_simple(
+                    node_id,
+                    types.0.clone(),
+                    types.1.clone(),
+                    "A",
+                    InputParamKind::ConnectionOrConstant,
+                );
+                self.add_node_input_simple(
+                    node_id,
+                    types.0.clone(),
+                    types.1,
+                    "B",
+                    InputParamKind::ConnectionOrConstant,
+                );
+            }
+            PulseNodeTemplate::GetArrayElement => {
+                if new_type.is_none() {
+                    panic!("update_node_inputs_outputs() ended up on node that requires new value type from response, but it was not provided");
+                }
+                let new_type = new_type.unwrap();
+                let param_a = node.get_input("expectedType");
+                if param_a.is_err() {
+                    panic!("node that requires input 'expectedType', but it was not found");
+                }
+                let param_output = node.get_output("out");
+                if let Ok(param_output) = param_output {
+                    self.state_mut().graph.remove_output_param(param_output);
+                    let types = pulse_value_type_to_node_types(&new_type);
+                    self.add_node_output_simple(node_id, types.0, "out");
+                }
+            }
+            PulseNodeTemplate::ScaleVector => {
+                if new_type.is_none() {
+                    panic!("update_node_inputs_outputs() ended up on node that requires new value type from response, but it was not provided");
+                }
+                let new_type = new_type.unwrap();
+                let types = pulse_value_type_to_node_types(&new_type);
+                let output_id = node.get_output("out");
+                let param_vec = node.get_input("vector");
+                if output_id.is_err() {
+                    panic!("node requires output 'out', but it was not found");
+                }
+                let output = self.state_mut().graph.get_output_mut(output_id.unwrap());
+                output.typ = types.0.clone();
+                if param_vec.is_err(){
+                    panic!("node that requires inputs 'A' and 'B', but one of them was not found");
+                }
+                self.state_mut().graph.remove_input_param(param_vec.unwrap());
+
+                self.add_node_input_simple(
+                    node_id,
+                    types.0,
+                    types.1,
+                    "vector",
+                    InputParamKind::ConnectionOrConstant,
+                );
+            }
+            PulseNodeTemplate::NewArray => {
+                let types = pulse_value_type_to_node_types(&new_type.unwrap_or_default());
+                let inputs = node.user_data.added_inputs.clone();
+                for inp in inputs {
+                    let param = self.state_mut().graph.get_input_mut(inp);
+                    param.typ = types.0.clone();
+                    param.value = types.1.clone();
+                }
+            }
+            _ => {}
+        }
+    }
+
+
+
+    // Update inputs on "Call Node" depending on the type of referenced node.
+    fn update_remote_node_params(&mut self, node_id: &NodeId, node_id_refrence: &NodeId) {
+        self.history.touch_node(&self.full_state, *node_id);
+        let node = self.state_mut().graph.nodes.get_mut(*node_id).unwrap();
+        // remove all inputs
+        let input_ids: Vec<_> = node.input_ids().collect();
+        let output_ids: Vec<_> = node.output_ids().collect();
+        let input_node_chooser = node
+            .get_input("nodeId")
+            .expect("Expected 'Call Node' node to have 'nodeId' input param");
+        for input in input_ids {
+            // don't remove the node chooser input
+            if input != input_node_chooser {
+                self.state_mut().graph.remove_input_param(input);
+            }
+        }
+        for output in output_ids {
+            self.state_mut().graph.remove_output_param(output);
+        }
+        if let Some(reference_node) = self.state().graph.nodes.get(*node_id_refrence) {
+            let reference_node_template = reference_node.user_data.template;
+            match reference_node_template {
+                PulseNodeTemplate::ListenForEntityOutput => {
+                    self.state_mut().graph.add_input_param(
+                        *node_id,
+                        "hEntity".into(),
+                        PulseDataType::EHandle,
+                        PulseGraphValueType::EHandle,
+                        InputParamKind::ConnectionOnly,
+                        true,
+                    );
+                    self.state_mut().graph.add_input_param(
+                        *node_id,
+                        "Run".into(),
+                        PulseDataType::Action,
+                        PulseGraphValueType::Action,
+                        InputParamKind::ConnectionOnly,
+                        true,
+                    );
+                    self.state_mut().graph.add_input_param(
+                        *node_id,
+                        "Cancel".into(),
+                        PulseDataType::Action,
+                        PulseGraphValueType::Action,
+                        InputParamKind::ConnectionOnly,
+                        true,
+                    );
+                }
+                PulseNodeTemplate::Function => {
+                    self.state_mut().graph.add_input_param(
+                        *node_id,
+                        "ActionIn".into(),
+                        PulseDataType::Action,
+                        PulseGraphValueType::Action,
+                        InputParamKind::ConnectionOnly,
+                        true,
+                    );
+                    self.state_mut().graph.add_input_param(*node_id,
+                        "Async".into(),
+                        PulseDataType::Bool,
+                        PulseGraphValueType::Bool { value: Default::default() },
+                        InputParamKind::ConstantOnly,
+                        true,
+                    );
+                    self.state_mut().graph.add_output_param(
+                        *node_id,
+                        "outAction".into(),
+                        PulseDataType::Action,
+                    );
+                }
+                PulseNodeTemplate::Timeline => {
+                    self.state_mut().graph.add_input_param(
+                        *node_id,
+                        "Start".into(),
+                        PulseDataType::Action,
+                        PulseGraphValueType::Action,
+                        InputParamKind::ConnectionOnly,
+                        true,
+                    );
+                    self.state_mut().graph.add_input_param(
+                        *node_id,
+                        "Stop".into(),
+                        PulseDataType::Action,
+                        PulseGraphValueType::Action,
+                        InputParamKind::ConnectionOnly,
+                        true,
+                    );
+                    self.state_mut().graph.add_output_param(
+                        *node_id,
+                        "outAction".into(),
+                        PulseDataType::Action,
+                    );
+                }
+                _ => {
+                    panic!(
+                        "update_remote_node_params() called on unsupported node type: {:?}",
+                        reference_node_template
+                    );
+                }
+            }
+        } else {
+            self.write_console_line(
+                "update_remote_node_params() called on node that does not exist in the graph anymore!".into(),
+                ConsoleMessageType::Warning
+            );
+        }
+    }
+    async fn check_for_updates() -> anyhow::Result<()> {
+        let releases = self_update::backends::github::ReleaseList::configure()
+            .repo_owner("liondoge")
+            .repo_name("vpulse-editor")
+            .build()?
+            .fetch()?;
+        let rel = releases.first().ok_or(anyhow::anyhow!(
+            "No releases present after fetching from GitHub"
+        ))?;
+        let mut msg_box = rfd::AsyncMessageDialog::new()
+            .set_level(rfd::MessageLevel::Info);
+        if self_update::version::bump_is_greater(env!("CARGO_PKG_VERSION"), &rel.version)? {
+            msg_box = msg_box
+                .set_title("Update Available")
+                .set_buttons(rfd::MessageButtons::YesNo)
+                .set_description(format!(
+                    "A new version of Pulse Graph Editor is available: {}.\nDo you want to update?",
+                    rel.version
+                ));
+        } else {
+            msg_box = msg_box
+                .set_title("Up to date")
+                .set_buttons(rfd::MessageButtons::Ok)
+                .set_description("Pulse Graph Editor is up to date.");
+        }
+        let response = msg_box.show().await;
+        if response == rfd::MessageDialogResult::Yes {
+            open::that("https://github.com/LionDoge/vpulse-editor/releases/latest")?;
+        }
+        Ok(())
+    }
+    // traverse forward to nodes connected to THIS node's output recursively
+    // until we reach a node that doesn't depend on polymorphic return type.
+    fn update_polymorphic_output_types(&mut self, node_id: NodeId, source_type: Option<PulseValueType>, source_input_name: Option<&str>) -> anyhow::Result<()> {
+        // if the node is a "Make Array" node, we need to update the output type based on the array type
+        let node_data = self.state().graph.nodes.get(node_id)
+            .ok_or(anyhow::anyhow!("Node with id {:?} not found in the graph", node_id))?;
+        if !has_polymorhpic_dependent_return(&node_data.user_data.template, self.user_state()) {
+            return Ok(());
+        };
+        self.history.touch_node(&self.full_state, node_id);
+        let node_data = &self.state().graph[node_id];
+        
+        let opt_new_type = match node_data.user_data.template {
+            PulseNodeTemplate::NewArray => {
+                let graph = &self.state().graph;
+                // TODO: get_constant_graph_input_value should probably be moved out of the compiler module
+                let typ = crate::compiler::get_constant_graph_input_value!(
+                    graph,
+                    node_data,
+                    "arrayType",
+                    try_pulse_type
+                );
+                Some(PulseValueType::PVAL_ARRAY(Box::new(typ)))
+            }
+            PulseNodeTemplate::GetArrayElement
+            | PulseNodeTemplate::ForEach => {
+                // if the source type is not None, we can update the output type
+                if let Some(source_type) = &source_type {
+                    // if the source type is an array, we need to update the output type to the inner type
+                    if let PulseValueType::PVAL_ARRAY(inner) = source_type {
+                        let node_data_mut = self.state_mut().graph.nodes.get_mut(node_id).unwrap(); // already checked, can unwrap.
+                        let out_id = node_data_mut.get_output("out")?;
+                        node_data_mut.user_data.custom_output_type = Some((**inner).clone());
+                        // update output in UI
+                        self.state_mut().graph.get_output_mut(out_id).typ = pulse_value_type_to_node_types(inner).0;
+                        Some((**inner).clone())
+                    } else {
+                        let source_type_str = source_type.get_enum_string(&self.user_state().bindings);
+                        self.write_console_line(
+                            format!("[UI] Expected array type from source node, but got {source_type_str}. The node setup might not work correctly in this state."),
+                            ConsoleMessageType::Warning
+                        );
+                        None
+                    }
+                } else {
+                    None
+                }
+            }
+            PulseNodeTemplate::LibraryBindingAssigned { binding } => {
+                let binding = self.user_state().bindings
+                    .find_function_by_id(binding)
+                    .ok_or(anyhow::anyhow!("Library binding node can't find saved binding {:?}! Likely the bindings file is not correct.", binding))?;
+                let new_type = if let Some(typ) = source_type {
+                    Some(typ)
+                } else {
+                   node_data.user_data.custom_output_type.clone()
+                };
+                if let Some(polymorphic_return) = &binding.polymorphic_return {
+                    match &polymorphic_return {
+                        // full type means that we copy over the return type from the binding
+                        PolimorphicTypeInfo::FullType (param_name) => {
+                            println!("source input name: {}", source_input_name.unwrap_or_default());
+                            if source_input_name.is_some_and(|f| f == param_name) || source_input_name.is_none() {
+                                let node_data_mut = self.state_mut().graph.nodes.get_mut(node_id).unwrap();
+                                node_data_mut.user_data.custom_output_type = new_type.clone();
+                                new_type
+                            } else {
+                                None
+                            }
+                        }
+                        PolimorphicTypeInfo::TypeParam (param_name) => {
+                            // type param means that we use the type parameter from the binding
+                            // Must be an array, otherwise the definition is wrong!
+                            println!("source input name: {}", source_input_name.unwrap_or_default());
+                            if source_input_name.is_some_and(|f| f == param_name) || source_input_name.is_none() {
+                                new_type.map(|new_type| {
+                                    if let PulseValueType::PVAL_ARRAY(inner) = new_type {
+                                        let node_data_mut = self.state_mut().graph.nodes.get_mut(node_id).unwrap();
+                                        node_data_mut.user_data.custom_output_type = Some(*inner.clone());
+                                        *inner
+                                    } else {
+                                        panic!("Polymorphic return type requested inner type from param, but that param was not Array type, which seems wrong!");
+                                    }
+                                })
+                            } else {
+                                None
+                            }
+                        }
+                        PolimorphicTypeInfo::ToSubtype(_param_name) => {
+                            // FUTURE: to_subtype requests a specific return subtype described in the provided parameter
+                            // However it's only really used for EHandles, and if a method requests a specific subtype of EHandle
+                            // then it will be upcasted anyways, so doing anything here is not really worth the effort.
+                            if let Some(param) = binding.find_outparam_by_name("retval") {
+                                Some(param.pulsetype.clone())
+                            } else {
+                                Some(PulseValueType::PVAL_ARRAY(Box::new(PulseValueType::PVAL_EHANDLE(None))))
+                            }
+                        }
+                    }
+                } else {
+                    None
+                }
+            }
+            PulseNodeTemplate::GetVar => {
+                // compiler should handle the register generation fine without any info from here
+                // we only just provide info to connected nodes from this one
+                let name_id = node_data
+                    .get_input("variableName")
+                    .map_err(|e: EguiGraphError| anyhow!(e).context("Can not get variableName field"))?;
+                let var_name = self.state().graph
+                    .get_input(name_id)
+                    .value()
+                    .clone()
+                    .try_variable_name()
+                    .map_err(|e| anyhow!(e).context("Can not get variableName field"))?;
+                
+                self.user_state().get_variable_from_name(var_name.as_str())
+                    .map(|var| pulsevaluetype_from_valuetype(var.stored_value.clone()))
+            }
+            _ => None
+        };
+        // now update the custom type in user data so the compiler can use it
+        // this will only happen if the resulting type is not None
+        if let Some(new_type) = &opt_new_type {
+            let node_data_mut = self.state_mut().graph.nodes.get_mut(node_id).unwrap();
+            node_data_mut.user_data.custom_output_type = opt_new_type.clone();
+            self.write_console_line(
+                format!("[UI] Updating polymorphic output type of node {:?} to {:?}", node_id, new_type),
+                ConsoleMessageType::Info
+            );
+            // LOL, this needs to be slightly improved to make it more generic.
+            let outnodes = get_node_ids_connected_to_output(
+                self.state().graph.nodes.get(node_id).unwrap(), &self.state().graph, "out")
+                .unwrap_or_default();
+            let outnodes2 = get_node_ids_connected_to_output(
+                self.state().graph.nodes.get(node_id).unwrap(), &self.state().graph, "retval")
+                .unwrap_or_default();
+            let outnodes3 = get_node_ids_connected_to_output(
+                self.state().graph.nodes.get(node_id).unwrap(), &self.state().graph, "value")
+                .unwrap_or_default();
+            for node_and_input_name in outnodes.iter().chain(outnodes2.iter()).chain(outnodes3.iter()) {
+                // recursively update the output type of connected nodes
+                self.update_polymorphic_output_types(
+                    node_and_input_name.0,
+                     opt_new_type.clone(),
+                      Some(&node_and_input_name.1)
+                )?;
+            }
+        }
+        Ok(())
+    }
+    fn clone_node(&mut self, source_node_id: NodeId, pos_offset: egui::Vec2) -> NodeId {
+        let source_node_data = self.state().graph.nodes.get(source_node_id).unwrap();
+        let source_label = source_node_data.label.clone();
+        let source_user_data = source_node_data.user_data.clone();
+        let inputs = source_node_data.inputs.clone();
+        let outputs = source_node_data.outputs.clone();
+        let new_node = self.state_mut().graph.add_node(
+            source_label,
+            source_user_data,
+            |grph, node_id| {
+                // clone inputs
+                // no input names in InputParam directly, they're stored directly in the node as vec of tuples
+                for (input_name, input_id) in inputs {
+                    let input_param = grph.get_input(input_id);
+                    grph.add_input_param(
+                        node_id,
+                        input_name,
+                        input_param.typ.clone(),
+                        input_param.value.clone(),
+                        input_param.kind,
+                        true,
+                    );
+                }
+                // clone outputs
+                for (output_name, output_id) in outputs {
+                    let output_param = grph.get_output(output_id);
+                    grph.add_output_param(
+                        node_id,
+                        output_name,
+                        output_param.typ.clone(),
+                    );
+                }
+            }
+        );
+        // unwraps should basically never fail, otherwise there would be bigger issues.
+        let orig_pos = self.full_state.state.node_positions.get(source_node_id).unwrap();
+        self.full_state.state.node_positions.insert(new_node,*orig_pos + pos_offset);
+        self.full_state.state.node_sizes.insert(new_node, *self.state().node_sizes.get(source_node_id).unwrap());
+        self.full_state.state.node_order.push(new_node);
+        // make sure exposed node info gets cloned as well (like function node)
+        if let Some(name) = self.user_state().exposed_nodes.get(source_node_id).cloned() {
+            self.user_state_mut().exposed_nodes.insert(new_node, format!("{name} clone"));
+        }
+        self.history.node_added(new_node);
+        new_node
+    }
+
+    // Connects ports of different types through a node that converts between them, if there's a known conversion.
+    fn insert_conversion_node(&mut self, output: OutputId, input: InputId) {
+        let graph = &self.state().graph;
+        let from_type = graph.get_output(output).typ.clone();
+        let to_type = graph.get_input(input).typ.clone();
+        let Some(conversion) = find_conversion(&from_type, &to_type) else {
+            return;
+        };
+        let template = match &conversion {
+            Conversion::Cast(_) => PulseNodeTemplate::Convert,
+            Conversion::Node(template) => *template,
+            Conversion::Function(libname) => match self.user_state().bindings.find_function_by_libname(libname) {
+                Some(binding) => PulseNodeTemplate::LibraryBindingAssigned { binding: binding.id },
+                None => return,
+            },
+        };
+        // place it halfway between the nodes it connects.
+        let from_pos = self.state().node_positions[graph.get_output(output).node];
+        let to_pos = self.state().node_positions[graph.get_input(input).node];
+        let node_id = self.full_state.add_node(template, from_pos + (to_pos - from_pos) / 2.0);
+        self.history.node_added(node_id);
+        if let Conversion::Cast(typ) = conversion {
+            let typeto = self.state().graph[node_id].get_input("typeto").unwrap();
+            self.state_mut().graph.get_input_mut(typeto).value = PulseGraphValueType::Typ { value: typ.clone() };
+            self.update_node_inputs_outputs_types(node_id, "typeto".into(), Some(typ));
+        }
+
+        let graph = &self.state().graph;
+        let node = &graph[node_id];
+        let conv_input = node.input_ids().find(|id| {
+            let param = graph.get_input(*id);
+            param.kind != InputParamKind::ConstantOnly && (param.typ == from_type || param.typ.allow_any_type())
+        });
+        let conv_output = node.output_ids().find(|id| graph.get_output(*id).typ == to_type);
+        let (Some(conv_input), Some(conv_output)) = (conv_input, conv_output) else {
+            return;
+        };
+        let input_full = graph.get_input(input).max_connections
+            .is_some_and(|max| graph.connections(input).len() >= max.get() as usize);
+        let input_hook = if input_full { 0 } else { graph.connections(input).len() };
+        self.history.touch_connections(&self.full_state, input);
+        let graph = &mut self.state_mut().graph;
+        graph.add_connection(output, conv_input, 0);
+        graph.add_connection(conv_output, input, input_hook);
+        if let Err(e) = self.update_polymorphic_output_types(node_id, None, None) {
+            self.write_console_line(
+                format!("[UI] Warning: Failed to update polymorphic output types: {e}"),
+                ConsoleMessageType::Warning
+            );
+        }
+        self.write_console_line(
+            format!("Inserted a conversion from {} to {}", from_type.name(), to_type.name()),
+            ConsoleMessageType::Info
+        );
+    }
+
+    // Splits a connection with a reroute node placed at the given position.
+    fn insert_reroute(&mut self, output: OutputId, input: InputId, position: Pos2) {
+        let graph = &self.state().graph;
+        let Some(input_hook) = graph.connections(input).iter().position(|id| *id == output) else {
+            return;
+        };
+        // center the knot on the clicked point.
+        let offset = Vec2::splat(15.0) * self.state().pan_zoom.zoom;
+        let node_id = self.full_state.add_node(PulseNodeTemplate::Reroute, position - offset);
+        self.history.node_added(node_id);
+        let node = &self.state().graph[node_id];
+        let (Ok(reroute_in), Ok(reroute_out)) = (node.get_input("in"), node.get_output("out")) else {
+            return;
+        };
+        self.history.touch_connections(&self.full_state, input);
+        let graph = &mut self.state_mut().graph;
+        graph.remove_connection(input, output);
+        graph.add_connection(output, reroute_in, 0);
+        graph.add_connection(reroute_out, input, input_hook);
+        self.update_reroute_types(node_id);
+    }
+
+    // Reroutes take on the type of whatever is connected to their input, which carries over to the reroutes after them.
+    fn update_reroute_types(&mut self, node_id: NodeId) {
+        let mut pending = vec![node_id];
+        let mut visited = HashSet::new();
+        while let Some(node_id) = pending.pop() {
+            if !visited.insert(node_id) {
+                continue;
+            }
+            let graph = &self.state().graph;
+            let Some(node) = graph.nodes.get(node_id) else {
+                continue;
+            };
+            if node.user_data.template != PulseNodeTemplate::Reroute {
+                continue;
+            }
+            let (Ok(input), Ok(output)) = (node.get_input("in"), node.get_output("out")) else {
+                continue;
+            };
+            let typ = graph
+                .connection(input)
+                .map(|source| graph.get_output(source).typ.clone())
+                .unwrap_or(PulseDataType::Any);
+            let targets: Vec<InputId> = graph
+                .iter_connections()
+                .filter(|(_, source)| *source == output)
+                .map(|(target, _)| target)
+                .collect();
+            // inputs that can't take the new type anymore lose their connection, like they couldn't be connected by hand.
+            let (kept, dropped): (Vec<InputId>, Vec<InputId>) = targets.into_iter().partition(|target| {
+                let target_type = &graph.get_input(*target).typ;
+                *target_type == typ || target_type.allow_any_type() || typ.allow_any_type()
+            });
+            pending.extend(kept.iter().map(|target| graph.get_input(*target).node));
+            let dropped: Vec<(InputId, String)> = dropped
+                .into_iter()
+                .map(|target| {
+                    let target_node = &graph[graph.get_input(target).node];
+                    let name = target_node.inputs.iter()
+                        .find(|(_, id)| *id == target)
+                        .map(|(name, _)| name.as_str())
+                        .unwrap_or_default();
+                    (target, format!("'{name}' of '{}'", target_node.label))
+                })
+                .collect();
+            self.history.touch_node(&self.full_state, node_id);
+            let graph = &mut self.state_mut().graph;
+            graph.get_output_mut(output).typ = typ.clone();
+            for (target, _) in dropped.iter() {
+                graph.remove_connection(*target, output);
+            }
+            for (_, description) in dropped {
+                self.write_console_line(
+                    format!("Disconnected {description} from a reroute, it doesn't take {}", typ.name()),
+                    ConsoleMessageType::Warning,
+                );
+            }
+        }
+    }
+
+    // Renames a variable or public output along with the nodes using it.
+    fn rename_item(&mut self, item: NamedItem, index: usize, new_name: &str) {
+        let old_name = self.full_state.item_names(item).get(index).cloned().unwrap_or_default();
+        self.history.begin_step(format!("Rename {} '{old_name}'", item.describe()));
+        let users = self.full_state.item_users(item, index);
+        self.history.touch_data(&self.full_state);
+        self.history.touch_nodes(&self.full_state, &users);
+        match self.full_state.rename_item(item, index, new_name) {
+            Ok(updated) => self.write_console_line(
+                format!("Renamed {} '{old_name}' to '{}', updated {updated} nodes", item.describe(), new_name.trim()),
+                ConsoleMessageType::Info
+            ),
+            Err(e) => self.write_console_line(
+                format!("Can't rename {} '{old_name}': {e}", item.describe()),
+                ConsoleMessageType::Warning
+            ),
+        }
+    }
+
+    // Deletes a variable or public output right away if no node uses it, otherwise asks what to do with its users.
+    fn request_item_deletion(&mut self, item: NamedItem, index: usize) {
+        let Some(name) = self.full_state.item_names(item).get(index).cloned() else {
+            return;
+        };
+        let users = self.full_state.item_users(item, index);
+        if users.is_empty() {
+            self.history.begin_step(format!("Delete {} '{name}'", item.describe()));
+            self.history.touch_data(&self.full_state);
+            self.full_state.remove_item(item, index);
+            return;
+        }
+        self.current_modal_dialog = ModalWindow {
+            window_type: ModalWindowType::DeleteInUse { item, index, users, retarget: None },
+            is_open: true,
+        };
+    }
+
+    fn delete_item(
+        &mut self,
+        item: NamedItem,
+        index: usize,
+        action: DeleteInUseAction,
+        responses: &mut Vec<NodeResponse<PulseGraphResponse, PulseNodeData>>,
+    ) {
+        let name = self.full_state.item_names(item).get(index).cloned().unwrap_or_default();
+        self.history.begin_step(format!("Delete {} '{name}'", item.describe()));
+        self.history.touch_data(&self.full_state);
+        match action {
+            DeleteInUseAction::RemoveUsers => {
+                let users = self.full_state.item_users(item, index);
+                self.write_console_line(
+                    format!("Deleted {} '{name}' and the {} nodes using it", item.describe(), users.len()),
+                    ConsoleMessageType::Info
+                );
+                responses.extend(users.into_iter().map(NodeResponse::DeleteNodeUi));
+            }
+            DeleteInUseAction::Retarget(target) => {
+                let target_name = self.full_state.item_names(item).get(target).cloned().unwrap_or_default();
+                let users = self.full_state.item_users(item, index);
+                self.history.touch_nodes(&self.full_state, &users);
+                let users = self.full_state.retarget_item(item, index, target);
+                if item == NamedItem::Variable {
+                    for node_id in users.iter() {
+                        if let Err(e) = self.update_polymorphic_output_types(*node_id, None, None) {
+                            self.write_console_line(
+                                format!("[UI] Warning: Failed to update polymorphic output types: {e}"),
+                                ConsoleMessageType::Warning
+                            );
+                        }
+                    }
+                }
+                self.write_console_line(
+                    format!("Deleted {} '{name}', {} nodes now use '{target_name}'", item.describe(), users.len()),
+                    ConsoleMessageType::Info
+                );
+            }
+        }
+        self.full_state.remove_item(item, index);
+    }
+
+    fn update_titlebar(&mut self, ctx: &egui::Context) {
+        let mut title = format!("{APP_NAME} - {}", graph_title(self.user_state()));
+        if self.history.is_modified() {
+            title.push_str(" *");
+        }
+        if title != self.titlebar {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.titlebar = title;
+        }
+    }
+
+    fn tab_state(&self, idx: usize) -> &FullGraphState {
+        if idx == self.active_tab {
+            &self.full_state
+        } else {
+            &self.tabs[idx].full_state
+        }
+    }
+
+    fn tab_history(&self, idx: usize) -> &History {
+        if idx == self.active_tab {
+            &self.history
+        } else {
+            &self.tabs[idx].history
+        }
+    }
+
+    // Graphs that were never saved count as unsaved changes too, unless they're empty.
+    fn tab_has_unsaved_changes(&self, idx: usize) -> bool {
+        let state = self.tab_state(idx);
+        self.tab_history(idx).is_modified()
+            || (state.user_state.save_file_path.is_none() && !state.state.graph.nodes.is_empty())
+    }
+
+    // Exchanges the document of the active tab between the editor and its slot in `tabs`.
+    fn swap_active_tab(&mut self) {
+        let tab = &mut self.tabs[self.active_tab];
+        std::mem::swap(&mut self.full_state, &mut tab.full_state);
+        std::mem::swap(&mut self.history, &mut tab.history);
+        std::mem::swap(&mut self.console_lines, &mut tab.console_lines);
+        std::mem::swap(&mut self.compile_task, &mut tab.compile_task);
+        std::mem::swap(&mut self.pending_watch_snapshot, &mut tab.pending_watch_snapshot);
+    }
+
+    // Runs `f` with the document of another tab in place of the active one's.
+    fn with_tab<R>(&mut self, idx: usize, f: impl FnOnce(&mut Self) -> R) -> R {
+        let active = self.active_tab;
+        if idx == active {
+            return f(self);
+        }
+        self.swap_active_tab();
+        self.active_tab = idx;
+        self.swap_active_tab();
+        let result = f(self);
+        self.swap_active_tab();
+        self.active_tab = active;
+        self.swap_active_tab();
+        result
+    }
+
+    fn switch_tab(&mut self, idx: usize) {
+        if idx == self.active_tab || idx >= self.tabs.len() {
+            return;
+        }
+        self.swap_active_tab();
+        self.active_tab = idx;
+        self.swap_active_tab();
+        self.graph_revision += 1;
+        // edits made before the tab went to the background were already caught.
+        if let Some(task) = self.compile_task.as_mut() {
+            task.rebase(self.graph_revision);
+        }
+        self.state_mut().connection_in_progress = None;
+        // the bindings could've been reloaded while the tab was in the background.
+        if !Arc::ptr_eq(&self.user_state().bindings, &self.bindings) {
+            let old_bindings = std::mem::replace(&mut self.full_state.user_state.bindings, self.bindings.clone());
+            let updated = self.revalidate_binding_nodes(&old_bindings);
+            if updated > 0 {
+                self.write_console_line(
+                    format!("Bindings were reloaded, {updated} node(s) updated"),
+                    ConsoleMessageType::Info,
+                );
+            }
+        }
+    }
+
+    fn open_tab(&mut self, mut full_state: FullGraphState) {
+        full_state.user_state.bindings = self.bindings.clone();
+        self.tabs.push(GraphTab::new(full_state));
+        self.switch_tab(self.tabs.len() - 1);
+    }
+
+    fn request_close_tab(&mut self, idx: usize) {
+        if self.tab_has_unsaved_changes(idx) {
+            self.confirm_save(UnsavedAction::CloseTab(idx));
+        } else {
+            self.close_tab(idx);
+        }
+    }
+
+    fn confirm_save(&mut self, action: UnsavedAction) {
+        self.current_modal_dialog.is_open = true;
+        self.current_modal_dialog.window_type = ModalWindowType::ConfirmSave(action);
+    }
+
+    // Tabs whose changes the action would lose.
+    fn tabs_losing_changes(&self, action: UnsavedAction) -> Vec<usize> {
+        match action {
+            UnsavedAction::NewGraph => vec![self.active_tab],
+            UnsavedAction::CloseTab(idx) => vec![idx],
+            UnsavedAction::Exit => (0..self.tabs.len()).filter(|idx| self.tab_has_unsaved_changes(*idx)).collect(),
+        }
+    }
+
+    // Carries out the action after the user chose to save or discard the changes in the way.
+    // Stops if a save is cancelled or fails.
+    fn resolve_unsaved(&mut self, ctx: &egui::Context, action: UnsavedAction, save: bool) {
+        let affected = self.tabs_losing_changes(action);
+        if save {
+            let active_tab = self.active_tab;
+            for idx in affected.iter() {
+                self.switch_tab(*idx);
+                if !self.save_active() {
+                    return;
+                }
+            }
+            self.switch_tab(active_tab);
+        }
+        match action {
+            UnsavedAction::NewGraph => self.new_graph(ctx),
+            UnsavedAction::CloseTab(idx) => self.close_tab(idx),
+            UnsavedAction::Exit => {
+                if !save {
+                    // closed so that the discarded changes aren't kept with the persisted tabs either
+                    for idx in affected.into_iter().rev() {
+                        self.close_tab(idx);
+                    }
+                }
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+        }
+    }
+
+    fn close_tab(&mut self, idx: usize) {
+        if idx >= self.tabs.len() {
+            return;
+        }
+        if self.tabs.len() == 1 {
+            // there's always a tab open, closing the last one leaves an empty graph behind.
+            self.open_tab(FullGraphState::default());
+        }
+        if idx == self.active_tab {
+            let neighbour = if idx + 1 < self.tabs.len() { idx + 1 } else { idx - 1 };
+            self.switch_tab(neighbour);
+        }
+        let tab = self.tabs.remove(idx);
+        if let Some(task) = &tab.compile_task {
+            task.cancel();
+        }
+        self.autosaver.pending = true;
+        if idx < self.active_tab {
+            self.active_tab -= 1;
+        }
+    }
+
+    // An untouched new graph, opening a file can take its place instead of adding a tab.
+    fn active_tab_is_blank(&self) -> bool {
+        !self.history.is_modified()
+            && self.user_state().save_file_path.is_none()
+            && self.state().graph.nodes.is_empty()
+    }
+
+    fn find_tab(&self, filepath: &Path) -> Option<usize> {
+        (0..self.tabs.len()).find(|idx| {
+            self.tab_state(*idx).user_state.save_file_path.as_deref() == Some(filepath)
+        })
+    }
+
+    fn record_history(&mut self, ctx: &egui::Context) {
+        // dragging or typing keeps adding to the same undo step
+        let interacting = ctx.input(|i| i.pointer.any_down()) || ctx.wants_keyboard_input();
+        if self.history.record(&self.full_state, interacting) {
+            self.autosaver.pending = true;
+            self.graph_revision += 1;
+        }
+    }
+
+    // Writes every graph with unsaved changes to the recovery directory.
+    fn autosave(&mut self) -> anyhow::Result<()> {
+        let graphs: Vec<&FullGraphState> = (0..self.tabs.len())
+            .filter(|idx| self.tab_has_unsaved_changes(*idx))
+            .map(|idx| self.tab_state(idx))
+            .collect();
+        let res = self.autosaver.save(&graphs);
+        match res {
+            Ok(()) => self.autosaver.mark_saved(),
+            Err(_) => self.autosaver.retry_later(),
+        }
+        res
+    }
+
+    fn poll_autosave(&mut self, ctx: &egui::Context) {
+        // keeps the lock of the recovery files fresh even while idle
+        if let Some(next_heartbeat) = self.autosaver.heartbeat() {
+            ctx.request_repaint_after(next_heartbeat);
+        }
+        let Some(time_left) = self.autosaver.time_left() else {
+            return;
+        };
+        if !time_left.is_zero() {
+            ctx.request_repaint_after(time_left);
+        } else if let Err(e) = self.autosave() {
+            self.write_console_line(format!("Autosave failed: {e}"), ConsoleMessageType::Warning);
+        }
+    }
+
+    // Opens a recovered graph, replacing the tab of the same file if it's open. It's marked as modified
+    // since the recovered changes aren't in its file.
+    fn open_recovered(&mut self, mut full_state: FullGraphState) {
+        full_state.user_state.bindings = self.bindings.clone();
+        migrations::verify_compat(&mut full_state);
+        let replaced_idx = full_state.user_state.save_file_path
+            .as_deref()
+            .and_then(|filepath| self.find_tab(filepath))
+            .or_else(|| self.active_tab_is_blank().then_some(self.active_tab));
+        match replaced_idx {
+            Some(idx) => {
+                self.switch_tab(idx);
+                self.full_state = full_state;
+                self.history = History::default();
+                self.graph_revision += 1;
+                self.clear_console();
+            }
+            None => self.open_tab(full_state),
+        }
+        self.history.mark_modified();
+        self.autosaver.pending = true;
+    }
+
+    fn restore_recovered(&mut self) {
+        for mut session in std::mem::take(&mut self.recovered) {
+            for graph in session.graphs.drain(..) {
+                self.open_recovered(graph.state);
+            }
+            session.discard();
+        }
+    }
+
+    fn discard_recovered(&mut self) {
+        for session in std::mem::take(&mut self.recovered) {
+            session.discard();
+        }
+    }
+
+    // Arranges the selected nodes, or the whole graph if nothing is selected, as one undo step.
+    fn auto_layout(&mut self) {
+        let nodes = if self.state().selected_nodes.is_empty() {
+            self.state().node_order.clone()
+        } else {
+            self.state().selected_nodes.clone()
+        };
+        let zoom = self.state().pan_zoom.zoom;
+        let sizes: HashMap<NodeId, Vec2> = nodes
+            .iter()
+            .map(|node_id| {
+                let size = self.state().drawn_node_size(*node_id).unwrap_or_else(|| {
+                    self.state().node_sizes.get(*node_id).copied().unwrap_or(Vec2::new(200.0, 200.0)) * zoom
+                });
+                (*node_id, size)
+            })
+            .collect();
+        self.history.begin_step("Auto layout");
+        self.history.touch_positions(&self.full_state, &nodes);
+        self.full_state.auto_layout(&nodes, zoom, |node_id| sizes[&node_id]);
+    }
+
+    // Each of these is its own undo step, named after the menu entry.
+    fn align_selected(&mut self, alignment: Alignment, label: &str) {
+        let nodes = self.state().selected_nodes.clone();
+        self.history.begin_step(label);
+        self.history.touch_positions(&self.full_state, &nodes);
+        self.state_mut().align_nodes(&nodes, alignment);
+    }
+
+    fn distribute_selected(&mut self, distribution: Distribution, label: &str) {
+        let nodes = self.state().selected_nodes.clone();
+        self.history.begin_step(label);
+        self.history.touch_positions(&self.full_state, &nodes);
+        self.state_mut().distribute_nodes(&nodes, distribution);
+    }
+
+    fn do_undo(&mut self) {
+        if self.history.undo(&mut self.full_state) {
+            self.autosaver.pending = true;
+            self.graph_revision += 1;
+        }
+    }
+
+    fn do_redo(&mut self) {
+        if self.history.redo(&mut self.full_state) {
+            self.autosaver.pending = true;
+            self.graph_revision += 1;
+        }
+    }
+
+    // Brings the selected nodes, or the whole graph if nothing is selected, to the middle of the
+    // editor, zooming out if they don't fit.
+    fn focus_selection(&mut self, ui: &egui::Ui) {
+        let state = self.state();
+        let nodes: Vec<NodeId> = if state.selected_nodes.is_empty() {
+            state.graph.iter_nodes().collect()
+        } else {
+            state.selected_nodes.clone()
+        };
+        let Some(bounds) = nodes.iter().filter_map(|node_id| state.node_rect(*node_id)).reduce(|a, b| a.union(b)) else {
+            return;
+        };
+        let editor_size = ui.max_rect().size();
+        self.state_mut().pan_zoom.pan = editor_size / 2.0 - bounds.center().to_vec2();
+        let margin = Vec2::splat(80.0);
+        let fit = (editor_size / (bounds.size() + margin)).min_elem();
+        if fit < 1.0 {
+            self.state_mut().zoom(ui, fit);
+        }
+    }
+
+    // Undoes or redoes until the given number of steps is applied, used by the history window.
+    fn go_to_history_position(&mut self, position: usize) {
+        while self.history.position() > position && self.history.can_undo() {
+            self.do_undo();
+        }
+        while self.history.position() < position && self.history.can_redo() {
+            self.do_redo();
+        }
+    }
+
+    fn write_console_line(&mut self, line: String, message_type: ConsoleMessageType) {
+        let time = chrono::Local::now().format("[%H:%M:%S]");
+        self.console_lines.push_back(ConsoleLine {
+            text: format!("{} {}", time, line),
+            message_type,
+        });
+        if self.console_lines.len() > 100 {
+            self.console_lines.pop_front();
+        }
+    }
+
+    fn clear_console(&mut self) {
+        self.console_lines.clear();
+    }
+}
+
+impl PulseGraphEditor{
+    /// If the persistence feature is enabled, Called once before the first frame.
+    /// Load previous app state (if any).
+    pub fn new(cc: &eframe::CreationContext<'_>) -> anyhow::Result<Self> {
+        #[cfg(feature = "persistence")]
+        let (tab_states, active_tab) = match cc.storage
+            .and_then(|storage| eframe::get_value::<Vec<FullGraphState>>(storage, OPEN_TABS_KEY))
+            .filter(|tabs| !tabs.is_empty())
+        {
+            Some(tabs) => {
+                let active_tab = cc.storage
+                    .and_then(|storage| eframe::get_value(storage, ACTIVE_TAB_KEY))
+                    .unwrap_or_default();
+                (tabs, active_tab)
+            }
+            // state stored before tabs existed
+            None => (
+                vec![cc.storage
+                    .and_then(|storage| eframe::get_value(storage, PERSISTENCE_KEY))
+                    .unwrap_or_default()],
+                0,
+            ),
+        };
+        #[cfg(not(feature = "persistence"))]
+        let (tab_states, active_tab) = (vec![FullGraphState::default()], 0);
+        #[cfg(feature = "persistence")]
+        let modified_tabs: Vec<bool> = cc.storage
+            .and_then(|storage| eframe::get_value(storage, MODIFIED_TABS_KEY))
+            .unwrap_or_default();
+        #[cfg(not(feature = "persistence"))]
+        let modified_tabs: Vec<bool> = Vec::new();
+        #[cfg(feature = "persistence")]
+        let show_minimap = cc.storage
+            .and_then(|storage| eframe::get_value(storage, MINIMAP_KEY))
+            .unwrap_or_default();
+        #[cfg(not(feature = "persistence"))]
+        let show_minimap = false;
+        #[cfg(feature = "persistence")]
+        let recent_files: Vec<PathBuf> = cc.storage
+            .and_then(|storage| eframe::get_value(storage, RECENT_FILES_KEY))
+            .unwrap_or_default();
+        #[cfg(not(feature = "persistence"))]
+        let recent_files: Vec<PathBuf> = Vec::new();
+        #[cfg(feature = "persistence")]
+        let finder_memory = cc.storage
+            .and_then(|storage| eframe::get_value(storage, NODE_FINDER_KEY))
+            .unwrap_or_default();
+        #[cfg(not(feature = "persistence"))]
+        let finder_memory = NodeFinderMemory::default();
+        #[cfg(feature = "persistence")]
+        let grid = cc.storage
+            .and_then(|storage| eframe::get_value(storage, GRID_KEY))
+            .unwrap_or_default();
+        #[cfg(not(feature = "persistence"))]
+        let grid = Grid::default();
+        #[cfg(feature = "persistence")]
+        let flow_overlay = cc.storage
+            .and_then(|storage| eframe::get_value(storage, FLOW_OVERLAY_KEY))
+            .unwrap_or_default();
+        #[cfg(not(feature = "persistence"))]
+        let flow_overlay = FlowOverlay::default();
+        let (keymap, keymap_problems) = Keymap::load();
+        let mut grph = Self {
+            show_minimap,
+            flow_overlay,
+            grid,
+            keymap,
+            recent_files: recent_files.into_iter().filter(|path| path.exists()).collect(),
+            finder_memory,
+            current_modal_dialog: ModalWindow::default(),
+            version: FileVersion::default(),
+            console_lines: VecDeque::new(),
+            active_tab: active_tab.min(tab_states.len() - 1),
+            tabs: tab_states.into_iter().map(GraphTab::new).collect(),
+            ..Default::default()
+        };
+        for (tab, modified) in grph.tabs.iter_mut().zip(modified_tabs) {
+            if modified {
+                tab.history.mark_modified();
+            }
+        }
+        grph.swap_active_tab();
+        for problem in keymap_problems {
+            grph.write_console_line(problem, ConsoleMessageType::Warning);
+        }
+
+        #[cfg(feature = "nongame_asset_build")] {
+            let cfg_res = EditorConfig::load_from_file(Path::new("config.json"));
+            if let Err(e) = &cfg_res {
+                MessageDialog::new()
+                .set_level(rfd::MessageLevel::Error)
+                .set_title("Failed to load config file")
+                .set_buttons(rfd::MessageButtons::Ok)
+                .set_description(format!("Failed to load config.json, compiling will not work fully. Refer to the documentation on how to set up valid configuration.\n {e}"))
+                .show();
+            };
+            grph.editor_config = cfg_res.unwrap_or_default();
+        }
+
+        let bindings = load_bindings(Path::new(BINDINGS_MANIFEST_PATH));
+        match bindings {
+            Ok(bindings) => {
+                grph.bindings = Arc::new(bindings);
+            }
+            Err(e) => {
+                MessageDialog::new()
+                    .set_level(rfd::MessageLevel::Error)
+                    .set_title("Failed to load Pulse bindings")
+                    .set_buttons(rfd::MessageButtons::Ok)
+                    .set_description(e.to_string())
+                    .show();
+                return Err(e);
+            }
+        };
+        grph.user_state_mut().bindings = grph.bindings.clone();
+        migrations::verify_compat(&mut grph.full_state);
+        for tab in grph.tabs.iter_mut() {
+            tab.full_state.user_state.bindings = grph.bindings.clone();
+            migrations::verify_compat(&mut tab.full_state);
+        }
+        grph.recovered = recovery::find_sessions(grph.autosaver.dir());
+        if !grph.recovered.is_empty() {
+            grph.current_modal_dialog.is_open = true;
+            grph.current_modal_dialog.window_type = ModalWindowType::RestoreRecovered;
+        }
+        grph.update_titlebar(&cc.egui_ctx);
+        Ok(grph)
+    }
+
+    // Opens the graph in a new tab, or switches to the tab it's already open in.
+    // An empty unsaved graph in the active tab is replaced instead.
+    fn handle_open_file(&mut self, filepath: &PathBuf) -> anyhow::Result<()> {
+        if let Some(idx) = self.find_tab(filepath) {
+            self.switch_tab(idx);
+            self.add_recent_file(filepath);
+            return Ok(());
+        }
+        let res = if self.active_tab_is_blank() {
+            self.load_graph(filepath)
+        } else {
+            let mut full_state = FullGraphState::default();
+            full_state.user_state.bindings = self.bindings.clone();
+            full_state.load_state(filepath).map(|()| self.open_tab(full_state))
+        };
+        if let Err(e) = res {
+            MessageDialog::new()
+                .set_level(rfd::MessageLevel::Error)
+                .set_title("Load failed")
+                .set_buttons(rfd::MessageButtons::Ok)
+                .set_description(e.to_string())
+                .show();
+            return Err(e);
+        }
+        self.add_recent_file(filepath);
+        Ok(())
+    }
+
+    /// Opens the graphs given on the command line, e.g. by a file association.
+    pub fn open_files(&mut self, paths: &[PathBuf]) {
+        for path in paths {
+            // relative paths wouldn't match the tab of the same file, nor work from the recent files later.
+            let path = std::path::absolute(path).unwrap_or_else(|_| path.clone());
+            let _ = self.handle_open_file(&path);
+        }
+    }
+
+    fn add_recent_file(&mut self, filepath: &Path) {
+        self.recent_files.retain(|path| path != filepath);
+        self.recent_files.insert(0, filepath.to_path_buf());
+        self.recent_files.truncate(MAX_RECENT_FILES);
+    }
+
+    fn start_compile(&mut self) {
+        self.start_compile_snapshot(self.full_state.clone());
+    }
+
+    fn start_compile_snapshot(&mut self, snapshot: FullGraphState) {
+        self.write_console_line("Compiling graph...".into(), ConsoleMessageType::Info);
+        self.compile_task = Some(CompileTask::start(
+            snapshot,
+            self.graph_revision,
+            #[cfg(feature = "nongame_asset_build")] self.editor_config.clone(),
+        ));
+    }
+
+    // Reloads bindings from disk, and updates nodes that use bindings which have changed.
+    fn reload_bindings(&mut self) -> bool {
+        match load_bindings(Path::new(BINDINGS_MANIFEST_PATH)) {
+            Ok(bindings) => {
+                // the other tabs pick the new bindings up once they're switched to.
+                self.bindings = Arc::new(bindings);
+                let old_bindings = std::mem::replace(&mut self.full_state.user_state.bindings, self.bindings.clone());
+                let updated = self.revalidate_binding_nodes(&old_bindings);
+                self.graph_revision += 1;
+                self.write_console_line(
+                    format!("Bindings reloaded, {updated} node(s) updated"),
+                    ConsoleMessageType::Info,
+                );
+                true
+            }
+            Err(e) => {
+                self.write_console_line(format!("Failed to reload bindings: {e:#}"), ConsoleMessageType::Error);
+                false
+            }
+        }
+    }
+
+    // Rebuilds parameters of nodes whose library or event binding definition differs from the old one.
+    // Returns the number of updated nodes.
+    fn revalidate_binding_nodes(&mut self, old_bindings: &GraphBindings) -> usize {
+        let bindings = self.user_state().bindings.clone();
+        let mut library_nodes = vec![];
+        let mut event_nodes = vec![];
+        let graph = &self.state().graph;
+        for (node_id, node) in graph.nodes.iter() {
+            match node.user_data.template {
+                PulseNodeTemplate::InvokeLibraryBinding
+                | PulseNodeTemplate::LibraryBindingAssigned { .. } => {
+                    if let Ok(input) = node.get_input("binding") {
+                        if let Ok(idx) = graph.get_input(input).value.clone().try_library_binding() {
+                            library_nodes.push((node_id, node.label.clone(), idx));
+                        }
+                    }
+                }
+                PulseNodeTemplate::EventHandler => {
+                    if let Ok(input) = node.get_input("event") {
+                        if let Ok(idx) = graph.get_input(input).value.clone().try_event_binding_id() {
+                            event_nodes.push((node_id, node.label.clone(), idx));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        let mut updated = 0;
+        for (node_id, label, idx) in library_nodes {
+            match bindings.find_function_by_id(idx) {
+                Some(binding) => {
+                    if old_bindings.find_function_by_id(idx) != Some(binding) {
+                        self.update_library_binding_params(&node_id, binding);
+                        updated += 1;
+                    }
+                }
+                None => self.write_console_line(
+                    format!("Node '{label}' uses a library binding that no longer exists"),
+                    ConsoleMessageType::Warning,
+                ),
+            }
+        }
+        for (node_id, label, idx) in event_nodes {
+            match bindings.find_event_by_id(idx) {
+                Some(binding) => {
+                    if old_bindings.find_event_by_id(idx) != Some(binding) {
+                        self.update_event_binding_params(&node_id, binding);
+                        updated += 1;
+                    }
+                }
+                None => self.write_console_line(
+                    format!("Node '{label}' uses an event binding that no longer exists"),
+                    ConsoleMessageType::Warning,
+                ),
+            }
+        }
+        updated
+    }
+
+    // Watch mode: recompile when the file of an open graph or the bindings change on disk.
+    fn poll_watcher(&mut self) {
+        let bindings_dir = watch::bindings_dir();
+        let mut paths = vec![bindings_dir.clone()];
+        paths.extend((0..self.tabs.len()).filter_map(|idx| self.tab_state(idx).user_state.save_file_path.clone()));
+        self.file_watcher.set_paths(&paths);
+        let changed = self.file_watcher.poll_changes();
+        if changed.is_empty() {
+            return;
+        }
+        // what to compile for each tab
+        let mut snapshots: HashMap<usize, FullGraphState> = HashMap::new();
+        for path in changed {
+            if path == bindings_dir {
+                if self.reload_bindings() {
+                    snapshots.entry(self.active_tab).or_insert_with(|| self.full_state.clone());
+                }
+            } else if let Some(idx) = self.find_tab(&path) {
+                // compile what's on disk, the file could've been changed outside of the editor.
+                let mut state = FullGraphState::default();
+                state.user_state.bindings = self.bindings.clone();
+                match state.load_state(&path) {
+                    Ok(()) => {
+                        snapshots.insert(idx, state);
+                    }
+                    Err(e) => self.write_console_line(
+                        format!("Failed to load {}: {e}", path.display()),
+                        ConsoleMessageType::Error,
+                    ),
+                }
+            }
+        }
+        for (idx, snapshot) in snapshots {
+            self.with_tab(idx, |editor| editor.compile_watched(snapshot));
+        }
+    }
+
+    fn compile_watched(&mut self, snapshot: FullGraphState) {
+        if let Some(task) = &self.compile_task {
+            // compiled once the current compilation is done, replacing older changes waiting for it.
+            task.cancel();
+            self.pending_watch_snapshot = Some(snapshot);
+        } else {
+            self.write_console_line("[Watch] Change detected".into(), ConsoleMessageType::Info);
+            self.start_compile_snapshot(snapshot);
+        }
+    }
+
+    fn start_pending_watch_compile(&mut self) {
+        if self.compile_task.is_none() {
+            if let Some(snapshot) = self.pending_watch_snapshot.take() {
+                self.compile_watched(snapshot);
+            }
+        }
+    }
+
+    // Keeps the compilations watch mode started in background tabs going, their messages go to the tab's console.
+    fn poll_background_compiles(&mut self) {
+        for idx in 0..self.tabs.len() {
+            let tab = &self.tabs[idx];
+            if idx != self.active_tab && (tab.compile_task.is_some() || tab.pending_watch_snapshot.is_some()) {
+                self.with_tab(idx, |editor| {
+                    editor.poll_compile_task();
+                    editor.start_pending_watch_compile();
+                });
+            }
+        }
+    }
+
+    // Forwards messages from the running compilation to the console.
+    // Returns the node that caused a compile error, if it still exists in the graph.
+    fn poll_compile_task(&mut self) -> Option<NodeId> {
+        let task = self.compile_task.as_ref()?;
+        let stale = task.stale;
+        let mut error_node = None;
+        for message in task.poll() {
+            match message {
+                CompileMessage::Progress(text) => {
+                    self.write_console_line(text, ConsoleMessageType::Info);
+                }
+                CompileMessage::Finished(res) => {
+                    self.compile_task = None;
+                    match res {
+                        Err(CompileError::Cancelled) => {
+                            self.write_console_line("Compilation cancelled".into(), ConsoleMessageType::Warning);
+                        }
+                        Err(e) => {
+                            if let CompileError::Node(node_id, _) = e {
+                                if self.state().graph.nodes.contains_key(node_id) {
+                                    error_node = Some(node_id);
+                                }
+                            }
+                            self.write_console_line(format!("Compile error: {e}"), ConsoleMessageType::Error);
+                        }
+                        Ok(()) => {
+                            self.write_console_line("Graph compiled successfully".into(), ConsoleMessageType::Info);
+                        }
+                    }
+                    if stale {
+                        self.write_console_line(
+                            "The graph was modified during compilation, the result is stale. Compile again to include the latest changes.".into(),
+                            ConsoleMessageType::Warning,
+                        );
+                    }
+                    break;
+                }
+            }
+        }
+        error_node
+    }
+
+    fn open_compiled_inspector(&mut self, filepath: &Path) -> anyhow::Result<()> {
+        let value = read_compiled_resource(filepath)?;
+        self.compiled_inspector = Some(CompiledInspector {
+            source_path: filepath.to_path_buf(),
+            text: kv3::to_string(&value),
+        });
+        Ok(())
+    }
+
+    fn show_compiled_inspector(&mut self, ctx: &egui::Context) {
+        let Some(inspector) = &self.compiled_inspector else {
+            return;
+        };
+        let mut is_open = true;
+        let mut export_result: Option<anyhow::Result<PathBuf>> = None;
+        egui::Window::new("Compiled graph inspector")
+            .open(&mut is_open)
+            .default_size([600.0, 500.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(inspector.source_path.display().to_string());
+                    if ui.button("Export as text KV3").clicked() {
+                        let chosen_file = FileDialog::new()
+                            .add_filter("Text KV3", &["vpulse", "kv3"])
+                            .set_file_name(
+                                inspector.source_path.file_stem()
+                                    .map(|stem| format!("{}.vpulse", stem.to_string_lossy()))
+                                    .unwrap_or_default()
+                            )
+                            .save_file();
+                        if let Some(filepath) = chosen_file {
+                            export_result = Some(
+                                fs::write(&filepath, &inspector.text)
+                                    .map(|_| filepath)
+                                    .map_err(anyhow::Error::from)
+                            );
+                        }
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut inspector.text.as_str())
+                            .code_editor()
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+            });
+        match export_result {
+            Some(Ok(filepath)) => self.write_console_line(
+                format!("Exported decompiled graph to {}", filepath.display()),
+                ConsoleMessageType::Info,
+            ),
+            Some(Err(e)) => self.write_console_line(
+                format!("Export failed: {e}"),
+                ConsoleMessageType::Error,
+            ),
+            None => {}
+        }
+        if !is_open {
+            self.compiled_inspector = None;
+        }
+    }
+}
+
+#[cfg(feature = "persistence")]
+const PERSISTENCE_KEY: &str = "egui_node_graph";
+#[cfg(feature = "persistence")]
+const OPEN_TABS_KEY: &str = "open_tabs";
+#[cfg(feature = "persistence")]
+const ACTIVE_TAB_KEY: &str = "active_tab";
+// which of the open tabs differ from their files, their histories start over after a restart.
+#[cfg(feature = "persistence")]
+const MODIFIED_TABS_KEY: &str = "modified_tabs";
+// Stored next to the graph state, but it's a preference of the user rather than part of the graph.
+#[cfg(feature = "persistence")]
+const MINIMAP_KEY: &str = "show_minimap";
+#[cfg(feature = "persistence")]
+const RECENT_FILES_KEY: &str = "recent_files";
+#[cfg(feature = "persistence")]
+const NODE_FINDER_KEY: &str = "node_finder";
+#[cfg(feature = "persistence")]
+const GRID_KEY: &str = "grid";
+#[cfg(feature = "persistence")]
+const FLOW_OVERLAY_KEY: &str = "flow_overlay";
+const MAX_RECENT_FILES: usize = 10;
+
+pub fn has_polymorhpic_dependent_return(
+    template: &PulseNodeTemplate,
+    user_state: &PulseGraphState,
+) -> bool {
+    match template {
+        PulseNodeTemplate::GetArrayElement
+        | PulseNodeTemplate::NewArray
+        | PulseNodeTemplate::GetVar
+        | PulseNodeTemplate::ForEach => true,
+        PulseNodeTemplate::LibraryBindingAssigned { binding: idx } => {
+            let binding = match user_state.bindings.find_function_by_id(*idx) {
+                Some(binding) => binding,
+                None => return false,
+            };
+            binding.polymorphic_return.is_some()
+        }
+        _ => false
+    }
+}
+
+impl eframe::App for PulseGraphEditor {
+    #[cfg(feature = "persistence")]
+    /// If the persistence function is enabled,
+    /// Called by the frame work to save state before shutdown.
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        let tab_states: Vec<&FullGraphState> = (0..self.tabs.len()).map(|idx| self.tab_state(idx)).collect();
+        eframe::set_value(storage, OPEN_TABS_KEY, &tab_states);
+        eframe::set_value(storage, ACTIVE_TAB_KEY, &self.active_tab);
+        let modified: Vec<bool> = (0..self.tabs.len()).map(|idx| self.tab_history(idx).is_modified()).collect();
+        eframe::set_value(storage, MODIFIED_TABS_KEY, &modified);
+        eframe::set_value(storage, MINIMAP_KEY, &self.show_minimap);
+        eframe::set_value(storage, RECENT_FILES_KEY, &self.recent_files);
+        eframe::set_value(storage, NODE_FINDER_KEY, &self.finder_memory);
+        eframe::set_value(storage, GRID_KEY, &self.grid);
+        eframe::set_value(storage, FLOW_OVERLAY_KEY, &self.flow_overlay);
+    }
+    /// Called each time the UI needs repainting, which may be many times per second.
+    /// Put your widgets into a `SidePanel`, `TopPanel`, `CentralPanel`, `Window` or `Area`.
+    fn update(&mut self, ctx: &eframe::egui::Context, _frame: &mut eframe::Frame) {
+        if let Err(panic) = recovery::run_guarded(|| self.show(ctx)) {
+            // last chance to keep the open graphs before going down, the state may be broken
+            // halfway through an edit so this could panic as well.
+            let saved = recovery::run_guarded(|| self.autosave());
+            self.autosaver.release();
+            recovery::report_panic(saved);
+            resume_unwind(panic);
+        }
+    }
+
+    /// Exited normally, nothing to recover.
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.autosaver.clear();
+    }
+}
+
+impl PulseGraphEditor {
+    fn show(&mut self, ctx: &egui::Context) {
+        let mut prepended_responses: Vec<NodeResponse<PulseGraphResponse, PulseNodeData>> = vec![];
+        let mut center_on_node: Option<NodeId> = None;
+        ctx.set_visuals(egui::Visuals::dark());
+        ctx.style_mut(|s| s.interaction.selectable_labels = false);
+        self.record_history(ctx);
+        self.poll_autosave(ctx);
+        if ctx.input(|i| i.viewport().close_requested())
+            && (0..self.tabs.len()).any(|idx| self.tab_has_unsaved_changes(idx))
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.confirm_save(UnsavedAction::Exit);
+        }
+        if self.current_modal_dialog.is_open {
+            let mut delete_in_use: Option<(NamedItem, usize, DeleteInUseAction)> = None;
+            let mut unsaved_choice: Option<(UnsavedAction, bool)> = None;
+            let mut recovered_action: Option<bool> = None;
+            let modal = Modal::new(Id::new("MainModal")).show(ctx, |ui| {
+                match &mut self.current_modal_dialog.window_type {
+                    ModalWindowType::ConfirmSave(action) => {
+                        let action = *action;
+                        ui.set_width(400.0);
+                        let names: Vec<String> = self.tabs_losing_changes(action)
+                            .into_iter()
+                            .map(|idx| graph_title(&self.tab_state(idx).user_state))
+                            .collect();
+                        let (title, message) = match action {
+                            UnsavedAction::NewGraph => (
+                                "Create new graph",
+                                format!("'{}' has unsaved changes. Save them before replacing it with a new graph?", names[0]),
+                            ),
+                            UnsavedAction::CloseTab(_) => (
+                                "Close tab",
+                                format!("'{}' has unsaved changes. Save them before closing it?", names[0]),
+                            ),
+                            UnsavedAction::Exit => (
+                                "Quit",
+                                format!("These graphs have unsaved changes, save them before quitting?\n{}", names.join("\n")),
+                            ),
+                        };
+
+                        ui.label(RichText::new(title).size(24.0));
+                        ui.label(RichText::new(message).size(16.0));
+
+                        egui::Sides::new().show(
+                            ui,
+                |_ui| {},
+                |ui| {
+                            let btn_cancel = ui.add_sized([120., 30.], Button::new(RichText::new("Cancel").size(18.0)));
+                            let btn_discard = ui.add_sized([120., 30.], Button::new(RichText::new("Discard").size(18.0)));
+                            let btn_save = ui.add_sized([120., 30.], Button::new(RichText::new("Save").size(18.0)));
+                            if btn_cancel.clicked() {
+                                ui.close();
+                            }
+                            if btn_discard.clicked() {
+                                unsaved_choice = Some((action, false));
+                                ui.close();
+                            }
+                            if btn_save.clicked() {
+                                unsaved_choice = Some((action, true));
+                                ui.close();
+                            }
+                        });
+                    }
+                    ModalWindowType::RestoreRecovered => {
+                        ui.set_width(400.0);
+
+                        ui.label(RichText::new("Recover unsaved work").size(24.0));
+                        ui.label(RichText::new(
+                            "The editor didn't exit properly last time. These graphs were autosaved before that:"
+                        ).size(16.0));
+                        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                            for graph in self.recovered.iter().flat_map(|session| session.graphs.iter()) {
+                                let saved_at = chrono::DateTime::<chrono::Local>::from(graph.saved_at);
+                                ui.label(RichText::new(graph.name()).strong());
+                                ui.label(format!("Autosaved {}", saved_at.format("%Y-%m-%d %H:%M:%S")));
+                                ui.label(RichText::new(graph.summary()).weak());
+                                ui.add_space(4.0);
+                            }
+                        });
+
+                        egui::Sides::new().show(
+                            ui,
+                |_ui| {},
+                |ui| {
+                            let btn_discard = ui.add_sized([120., 30.], Button::new(RichText::new("Discard").size(18.0)));
+                            let btn_later = ui.add_sized([120., 30.], Button::new(RichText::new("Later").size(18.0)))
+                                .on_hover_text("Ask again the next time the editor starts");
+                            let btn_restore = ui.add_sized([120., 30.], Button::new(RichText::new("Restore").size(18.0)));
+                            if btn_discard.clicked() {
+                                recovered_action = Some(false);
+                                ui.close();
+                            }
+                            if btn_later.clicked() {
+                                ui.close();
+                            }
+                            if btn_restore.clicked() {
+                                recovered_action = Some(true);
+                                ui.close();
+                            }
+                        });
+                    }
+                    ModalWindowType::DeleteInUse { item, index, users, retarget } => {
+                        ui.set_width(400.0);
+                        let names = self.full_state.item_names(*item);
+                        let name = names.get(*index).cloned().unwrap_or_default();
+                        let graph = &self.full_state.state.graph;
+
+                        ui.label(RichText::new(format!("Delete {} '{name}'", item.describe())).size(24.0));
+                        ui.label(RichText::new(format!("It's still used by {} nodes:", users.len())).size(16.0));
+                        egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                            for node_id in users.iter() {
+                                if let Some(node) = graph.nodes.get(*node_id) {
+                                    ui.label(format!("• {}", node.label));
+                                }
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Retarget them to");
+                            egui::ComboBox::from_id_salt("retarget")
+                                .selected_text(retarget.and_then(|idx| names.get(idx).cloned()).unwrap_or_default())
+                                .show_ui(ui, |ui| {
+                                    for (idx, other) in names.iter().enumerate() {
+                                        if idx != *index {
+                                            ui.selectable_value(retarget, Some(idx), other);
+                                        }
+                                    }
+                                });
+                        });
+
+                        egui::Sides::new().show(
+                            ui,
+                |_ui| {},
+                |ui| {
+                            let btn_cancel = ui.add_sized([120., 30.], Button::new(RichText::new("Cancel").size(18.0)));
+                            let btn_retarget = ui.add_enabled_ui(retarget.is_some(), |ui| {
+                                ui.add_sized([120., 30.], Button::new(RichText::new("Retarget").size(18.0)))
+                            }).inner;
+                            let btn_remove = ui.add_sized([120., 30.], Button::new(RichText::new("Remove nodes").size(18.0)));
+                            if btn_cancel.clicked() {
+                                ui.close();
+                            }
+                            if let (true, Some(target)) = (btn_retarget.clicked(), *retarget) {
+                                delete_in_use = Some((*item, *index, DeleteInUseAction::Retarget(target)));
+                                ui.close();
+                            }
+                            if btn_remove.clicked() {
+                                delete_in_use = Some((*item, *index, DeleteInUseAction::RemoveUsers));
+                                ui.close();
+                            }
+                        });
+                    }
+                    ModalWindowType::None => {}
+                }
+            });
+            if modal.should_close() {
+                self.current_modal_dialog.is_open = false;
+            }
+            if let Some((item, index, action)) = delete_in_use {
+                self.delete_item(item, index, action, &mut prepended_responses);
+            }
+            if let Some((action, save)) = unsaved_choice {
+                self.resolve_unsaved(ctx, action, save);
+            }
+            match recovered_action {
+                Some(true) => {
+                    self.restore_recovered();
+                    self.update_titlebar(ctx);
+                }
+                Some(false) => self.discard_recovered(),
+                None => {}
+            }
+        }
+        egui::TopBottomPanel::top("top").show(ctx, |ui| {
+            egui::MenuBar::new().ui(ui, |ui: &mut egui::Ui| {
+                if let Some(task) = &self.compile_task {
+                    ui.spinner();
+                    ui.label(format!("Compiling... {:.1}s", task.elapsed().as_secs_f32()));
+                    if ui.add_enabled(!task.is_cancelling(), Button::new("Cancel")).clicked() {
+                        task.cancel();
+                    }
+                    // keep the elapsed time ticking even without input
+                    ctx.request_repaint_after(std::time::Duration::from_millis(100));
+                } else if ui.button("Compile")
+                    .on_hover_text(self.keymap.hint(ctx, "Compile the graph", Action::Compile))
+                    .clicked()
+                    || self.keymap.pressed(ctx, Action::Compile) {
+                    self.start_compile();
+                }
+                if ui.checkbox(&mut self.watch_mode, "Watch")
+                    .on_hover_text("Recompile automatically when the graph file is saved or the bindings change")
+                    .changed() && !self.watch_mode {
+                    self.file_watcher.clear();
+                    self.pending_watch_snapshot = None;
+                    for tab in self.tabs.iter_mut() {
+                        tab.pending_watch_snapshot = None;
+                    }
+                }
+                if self.watch_mode {
+                    self.poll_watcher();
+                    self.poll_background_compiles();
+                    ctx.request_repaint_after(watch::POLL_INTERVAL);
+                }
+                // only the shown graph can be edited, `graph_revision` is its revision
+                if let Some(task) = self.compile_task.as_mut() {
+                    task.check_stale(self.graph_revision);
+                }
+                if let Some(node_id) = self.poll_compile_task() {
+                    prepended_responses.push(NodeResponse::ChangeSelectionColor(node_id, Some(egui::Color32::RED)));
+                    self.state_mut().reset_zoom(ui);
+                    center_on_node = Some(node_id);
+                }
+                self.start_pending_watch_compile();
+                // User pressed the "Save" button or
+                if ui.button("Save").on_hover_text(self.keymap.hint(ctx, "Save", Action::Save)).clicked()
+                    || self.keymap.pressed(ctx, Action::Save)
+                {
+                    self.save_active();
+                    self.update_titlebar(ctx);
+                }
+                if (ui.button("Save as...").on_hover_text(self.keymap.hint(ctx, "Save as", Action::SaveAs)).clicked()
+                    || self.keymap.pressed(ctx, Action::SaveAs))
+                    && self.dialog_change_save_file()
+                {
+                    // TODO: DRY
+                    if let Err(e) = self.perform_save(None) {
+                        MessageDialog::new()
+                            .set_level(rfd::MessageLevel::Error)
+                            .set_title("Save failed")
+                            .set_buttons(rfd::MessageButtons::Ok)
+                            .set_description(e.to_string())
+                            .show();
+                    }
+                    self.update_titlebar(ctx);
+                }
+                if ui.button("Open").clicked() {
+                    let chosen_file = FileDialog::new()
+                        .add_filter("Pulse Graph Editor State", &["ron"])
+                        .pick_file();
+                    if let Some(filepath) = &chosen_file {
+                        if self.handle_open_file(filepath).is_ok() {
+                            self.update_titlebar(ctx);
+                        }
+                    }
+                }
+                let mut open_recent: Option<PathBuf> = None;
+                ui.menu_button("Open recent", |ui| {
+                    // files moved or deleted since don't stay in the list
+                    self.recent_files.retain(|path| path.exists());
+                    if self.recent_files.is_empty() {
+                        ui.label(RichText::new("No recent files").weak());
+                        return;
+                    }
+                    for path in self.recent_files.iter() {
+                        let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+                        if ui.button(name).on_hover_text(path.display().to_string()).clicked() {
+                            open_recent = Some(path.clone());
+                        }
+                    }
+                    ui.separator();
+                    if ui.button("Clear list").clicked() {
+                        self.recent_files.clear();
+                    }
+                });
+                if let Some(filepath) = &open_recent {
+                    if self.handle_open_file(filepath).is_ok() {
+                        self.update_titlebar(ctx);
+                    }
+                }
+                if ui.button("Inspect compiled").clicked() {
+                    let chosen_file = FileDialog::new()
+                        .add_filter("Compiled Pulse Graph", &["vpulse_c"])
+                        .pick_file();
+                    if let Some(filepath) = &chosen_file {
+                        if let Err(e) = self.open_compiled_inspector(filepath) {
+                            self.write_console_line(format!("Failed to read compiled graph: {e:#}"), ConsoleMessageType::Error);
+                        }
+                    }
+                }
+                let mut should_update_title = false;
+                // every dropped file gets its own tab
+                let dropped_paths: Vec<PathBuf> = ctx.input(|i| {
+                    i.raw.dropped_files.iter().filter_map(|file| file.path.clone()).collect()
+                });
+                for path in dropped_paths.iter() {
+                    if self.handle_open_file(path).is_ok() {
+                        // defer title update after handling the DND event, otherwise we freeze due to Windows OLE bug.
+                        should_update_title = true;
+                    }
+                }
+                if should_update_title {
+                    self.update_titlebar(ctx);
+                }
+                if ui.button("New").clicked() {
+                    if self.tab_has_unsaved_changes(self.active_tab) {
+                        self.confirm_save(UnsavedAction::NewGraph);
+                    } else {
+                        self.new_graph(ctx);
+                    }
+                }
+
+                if ui.add_enabled(
+                    self.history.can_undo(), egui::Button::new("⟲")
+                    ).on_hover_text(self.keymap.hint(
+                        ctx, format!("Undo {}", self.history.undo_label().unwrap_or_default()).trim_end(), Action::Undo
+                    ))
+                    .clicked() ||
+                    self.keymap.pressed(ctx, Action::Undo)
+                {
+                    self.do_undo();
+                }
+                else if ui.add_enabled(
+                    self.history.can_redo(), egui::Button::new("⟳")
+                    ).on_hover_text(self.keymap.hint(
+                        ctx, format!("Redo {}", self.history.redo_label().unwrap_or_default()).trim_end(), Action::Redo
+                    ))
+                    .clicked() ||
+                    self.keymap.pressed(ctx, Action::Redo)
+                {
+                    self.do_redo();
+                }
+                if self.keymap.pressed(ctx, Action::Duplicate) {
+                    let selected_nodes: Vec<_> = self.state().selected_nodes.to_vec();
+                    let mut new_nodes: Vec<_> = vec![];
+                    for node_id in selected_nodes {
+                        new_nodes.push(
+                            self.clone_node(node_id, Vec2::new(20.0, 20.0))
+                        );
+                    }
+                    self.state_mut().selected_nodes = new_nodes;
+                }
+                if ui.button("Auto layout")
+                    .on_hover_text(self.keymap.hint(
+                        ctx,
+                        "Arrange the selected nodes along the action flow, or the whole graph if nothing is selected",
+                        Action::AutoLayout,
+                    ))
+                    .clicked()
+                    || self.keymap.pressed(ctx, Action::AutoLayout)
+                {
+                    self.auto_layout();
+                }
+                // stays open when changing the grid settings
+                let arrange_menu = egui::containers::menu::MenuConfig::new()
+                    .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside);
+                egui::containers::menu::MenuButton::new("Arrange").config(arrange_menu).ui(ui, |ui| {
+                    let selected = self.state().selected_nodes.len();
+                    let alignments = [
+                        (Alignment::Left, "Align left"),
+                        (Alignment::HorizontalCenter, "Align horizontal centers"),
+                        (Alignment::Right, "Align right"),
+                        (Alignment::Top, "Align top"),
+                        (Alignment::VerticalCenter, "Align vertical centers"),
+                        (Alignment::Bottom, "Align bottom"),
+                    ];
+                    for (alignment, label) in alignments {
+                        if ui.add_enabled(selected >= 2, Button::new(label))
+                            .on_disabled_hover_text("Select at least two nodes")
+                            .clicked()
+                        {
+                            self.align_selected(alignment, label);
+                            ui.close();
+                        }
+                    }
+                    ui.separator();
+                    let distributions = [
+                        (Distribution::Horizontal, "Distribute horizontally"),
+                        (Distribution::Vertical, "Distribute vertically"),
+                    ];
+                    for (distribution, label) in distributions {
+                        if ui.add_enabled(selected >= 3, Button::new(label))
+                            .on_disabled_hover_text("Select at least three nodes")
+                            .clicked()
+                        {
+                            self.distribute_selected(distribution, label);
+                            ui.close();
+                        }
+                    }
+                    ui.separator();
+                    ui.checkbox(&mut self.grid.visible, "Show grid");
+                    ui.checkbox(&mut self.grid.snap, "Snap to grid")
+                        .on_hover_text("Dragged nodes snap to the grid, whether it's shown or not");
+                    ui.horizontal(|ui| {
+                        ui.label("Grid size");
+                        ui.add(egui::DragValue::new(&mut self.grid.size).range(5.0..=200.0));
+                    });
+                });
+                if ui.button("Group")
+                    .on_hover_text(self.keymap.hint(
+                        ctx, "Put the selected nodes in a frame, right-click its title to edit it", Action::Group
+                    ))
+                    .clicked()
+                    || self.keymap.pressed(ctx, Action::Group)
+                {
+                    let selected_nodes = self.state().selected_nodes.clone();
+                    self.history.touch_frames(&self.full_state);
+                    self.state_mut().add_frame("Group", &selected_nodes);
+                }
+                if ui.button("Search")
+                    .on_hover_text(self.keymap.hint(
+                        ctx, "Find nodes by label, binding, variable, entity name or constant", Action::Search
+                    ))
+                    .clicked()
+                    || self.keymap.pressed(ctx, Action::Search)
+                {
+                    self.search.open();
+                }
+                ui.toggle_value(&mut self.show_history, "History")
+                    .on_hover_text("List of the changes that can be undone, click one to go back to it");
+                ui.checkbox(&mut self.show_minimap, "Minimap")
+                    .on_hover_text(self.keymap.hint(
+                        ctx, "Overview of the whole graph, click or drag in it to move around", Action::ToggleMinimap
+                    ));
+                if self.keymap.pressed(ctx, Action::ToggleMinimap) {
+                    self.show_minimap = !self.show_minimap;
+                }
+                ui.checkbox(&mut self.flow_overlay.show, "Execution order")
+                    .on_hover_text(self.keymap.hint(
+                        ctx,
+                        "Number the actions in the order they run from each entry point. \
+                            Selecting an entry point highlights everything it runs",
+                        Action::ToggleExecutionOrder,
+                    ));
+                if self.keymap.pressed(ctx, Action::ToggleExecutionOrder) {
+                    self.flow_overlay.show = !self.flow_overlay.show;
+                }
+                if self.flow_overlay.show {
+                    ui.checkbox(&mut self.flow_overlay.animate, "Animate")
+                        .on_hover_text("Show the flow along the action wires");
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                    if ui.button("Check for updates").clicked() {
+                        thread::spawn(move || {
+                            if let Err(e) = smol::block_on(PulseGraphEditor::check_for_updates()) {
+                                MessageDialog::new()
+                                    .set_level(rfd::MessageLevel::Error)
+                                    .set_title("Update check failed")
+                                    .set_buttons(rfd::MessageButtons::Ok)
+                                    .set_description(e.to_string())
+                                    .show();
+                            }
+                        });
+                    }
+                    ui.label(env!("CARGO_PKG_VERSION"));
+                    ui.toggle_value(&mut self.keymap.show_settings, "Shortcuts")
+                        .on_hover_text("Change the keyboard shortcuts");
+                });
+            });
+        });
+        egui::TopBottomPanel::top("tabs").show(ctx, |ui| {
+            let mut switch_to: Option<usize> = None;
+            let mut close: Option<usize> = None;
+            let mut new_tab = false;
+            egui::ScrollArea::horizontal().show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    for idx in 0..self.tabs.len() {
+                        let state = self.tab_state(idx);
+                        let mut title = graph_title(&state.user_state);
+                        if self.tab_history(idx).is_modified() {
+                            title.push_str(" *");
+                        }
+                        let hover_text = match &state.user_state.save_file_path {
+                            Some(path) => path.display().to_string(),
+                            None => "Not saved yet".to_string(),
+                        };
+                        let response = ui.selectable_label(idx == self.active_tab, title)
+                            .on_hover_text(hover_text);
+                        if response.clicked() {
+                            switch_to = Some(idx);
+                        }
+                        if response.middle_clicked()
+                            || ui.small_button("x").on_hover_text(self.keymap.hint(ctx, "Close tab", Action::CloseTab)).clicked() {
+                            close = Some(idx);
+                        }
+                        ui.separator();
+                    }
+                    new_tab = ui.button("+").on_hover_text(self.keymap.hint(ctx, "New tab", Action::NewTab)).clicked();
+                });
+            });
+            if !self.current_modal_dialog.is_open {
+                let count = self.tabs.len();
+                if self.keymap.pressed(ctx, Action::NewTab) {
+                    new_tab = true;
+                } else if self.keymap.pressed(ctx, Action::CloseTab) {
+                    close = Some(self.active_tab);
+                } else if self.keymap.pressed(ctx, Action::NextTab) {
+                    switch_to = Some((self.active_tab + 1) % count);
+                } else if self.keymap.pressed(ctx, Action::PreviousTab) {
+                    switch_to = Some((self.active_tab + count - 1) % count);
+                }
+            }
+            if new_tab {
+                self.open_tab(FullGraphState::default());
+            }
+            if let Some(idx) = switch_to {
+                self.switch_tab(idx);
+            }
+            if let Some(idx) = close {
+                self.request_close_tab(idx);
+            }
+            if new_tab || switch_to.is_some() || close.is_some() {
+                self.update_titlebar(ctx);
+            }
+        });
+        let mut output_scheduled_for_deletion: Option<usize> = None; // we can get away with just one reference (it's not like the user can click more than one at once)
+        let mut variable_scheduled_for_deletion: Option<usize> = None;
+        // the panel edits the variables and outputs in place
+        self.history.touch_data(&self.full_state);
+        egui::SidePanel::left("left_panel").show(ctx, |ui| {
+            egui::CollapsingHeader::new("Advanced")
+                .default_open(false)
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Graph domain").on_hover_text("Suggests which context the graph can be used in, and what features will be available.");
+                        ui.text_edit_singleline(&mut self.user_state_mut().graph_domain);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Graph sub-type").on_hover_text("The type on which the graph will be ran on eg. point entity/model entity/panel.");
+                        ui.text_edit_singleline(&mut self.user_state_mut().graph_subtype);
+                    });
+                });
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.label("Public outputs:");
+                if ui.button("Add output").clicked() {
+                    let name = self.full_state.unique_item_name(NamedItem::PublicOutput, "output");
+                    self.user_state_mut().public_outputs.push(OutputDefinition {
+                        name,
+                        data_type: PulseDataType::default(),
+                        value_type: PulseGraphValueType::default(),
+                        typ: PulseValueType::PVAL_INT(None),
+                        typ_old: PulseValueType::PVAL_INT(None),
+                    });
+                }
+                let (out_scheduled_for_deletion, updated_output_idx, output_rename) = appwidgets::public_output_list_widget(
+                    ui, &mut self.full_state.user_state.public_outputs, &self.full_state.user_state.bindings
+                );
+                output_scheduled_for_deletion = out_scheduled_for_deletion;
+                if let Some((output_idx, new_name)) = output_rename {
+                    self.rename_item(NamedItem::PublicOutput, output_idx, &new_name);
+                }
+                if let Some(output_idx) = updated_output_idx {
+                    for node_id in self.full_state.state.graph.nodes.keys().collect::<Vec<_>>() {
+                        self.update_node_public_output_types(node_id, output_idx);
+                    }
+                }
+
+                ui.separator();
+
+                ui.label("Variables:");
+                if ui.button("Add variable").clicked() {
+                    let name = self.full_state.unique_item_name(NamedItem::Variable, "variable");
+                    self.full_state.user_state.variables.push(PulseVariable {
+                        name,
+                        data_type: PulseDataType::default(),
+                        stored_value: PulseGraphValueType::default(),
+                        typ_and_default_value: PulseValueType::PVAL_INVALID,
+                        default_value_buffer: String::default(),
+                    });
+                }
+                let variable_type_list = PulseDataType::get_variable_supported_types();
+                let (var_scheduled_for_deletion, updated_var_idx, var_rename) = appwidgets::variable_list_widget(
+                    ui,
+                    &mut self.full_state.user_state.variables,
+                    variable_type_list,
+                    &self.full_state.user_state.bindings
+                );
+                variable_scheduled_for_deletion = var_scheduled_for_deletion;
+                if let Some((var_idx, new_name)) = var_rename {
+                    self.rename_item(NamedItem::Variable, var_idx, &new_name);
+                }
+                if let Some(var_idx) = updated_var_idx {
+                    for node_id in self.full_state.state.graph.nodes.keys().collect::<Vec<_>>() {
+                        self.update_node_variable_types(node_id, var_idx);
+                    }
+                }
+            });
+        });
+        if let Some(output_scheduled_for_deletion) = output_scheduled_for_deletion {
+            self.request_item_deletion(NamedItem::PublicOutput, output_scheduled_for_deletion);
+        }
+        if let Some(variable_scheduled_for_deletion) = variable_scheduled_for_deletion {
+            self.request_item_deletion(NamedItem::Variable, variable_scheduled_for_deletion);
+        }
+
+        // not while typing, the frame menu has text fields while its contents are selected.
+        if self.keymap.pressed(ctx, Action::Delete) {
+            // delete selected nodes
+            for node_id in self.state().selected_nodes.iter() {
+                prepended_responses.push(NodeResponse::DeleteNodeUi(*node_id));
+            }
+            if !self.state().selected_frames.is_empty() {
+                self.history.touch_frames(&self.full_state);
+            }
+            for frame_id in self.state().selected_frames.clone() {
+                self.state_mut().remove_frame(frame_id);
+            }
+        }
+
+        self.show_compiled_inspector(ctx);
+        if self.show_history {
+            if let Some(position) = self.history.show(ctx, &mut self.show_history) {
+                self.go_to_history_position(position);
+            }
+        }
+        let found = self.search.show(
+            ctx,
+            &self.full_state.state.graph,
+            &self.full_state.user_state.bindings,
+            &self.full_state.state.node_order,
+            self.graph_revision,
+        );
+        if let Some(node_id) = found {
+            self.state_mut().selected_nodes = vec![node_id];
+            center_on_node = Some(node_id);
+        }
+        egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
+            egui::ScrollArea::vertical()
+            .auto_shrink(false)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in &self.console_lines {
+                    let color = match line.message_type {
+                        ConsoleMessageType::Info => egui::Color32::WHITE,
+                        ConsoleMessageType::Warning => egui::Color32::YELLOW,
+                        ConsoleMessageType::Error => egui::Color32::RED,
+                    };
+                    ui.label(RichText::new(&line.text).color(color));
+                }
+            });
+        });
+
+        if self.keymap.pressed(ctx, Action::ShowShortcuts) {
+            self.keymap.show_cheat_sheet = !self.keymap.show_cheat_sheet;
+        }
+        let open_node_finder = self.keymap.pressed(ctx, Action::OpenNodeFinder);
+        let focus_selection = self.keymap.pressed(ctx, Action::FocusSelection);
+        self.full_state.state.grid = self.grid;
+        let execution_order = self.flow_overlay.show.then(|| ExecutionOrder::of(&self.state().graph));
+        self.flow_overlay.apply(&mut self.full_state.state, execution_order.as_ref());
+        let graph_response = egui::CentralPanel::default()
+            .show(ctx, |ui| {
+                let graph_response = self.full_state.state.draw_graph_editor(
+                    ui,
+                    AllMyNodeTemplates {
+                        game_function_count: self.user_state().bindings.gamefunctions.len()
+                    },
+                    &mut self.full_state.user_state,
+                    &mut self.finder_memory,
+                    prepended_responses,
+                );
+                self.history.take_graph_edits(&mut self.full_state);
+
+                if let Some(node_id) = center_on_node.take() {
+                    if let Some(node_pos) = self.state().node_positions.get(node_id) {
+                        let node_size = self
+                            .state()
+                            .node_sizes
+                            .get(node_id)
+                            .copied()
+                            .unwrap_or(Vec2::new(200.0, 200.0));
+                        let editor_size = ui.max_rect().size();
+                        let node_center = node_pos.to_vec2() + node_size / 2.0;
+                        self.state_mut().pan_zoom.pan = editor_size / 2.0 - node_center;
+                    }
+                }
+                if focus_selection {
+                    self.focus_selection(ui);
+                }
+                // opened after drawing, so the key doesn't get typed into its search field
+                if open_node_finder {
+                    if let Some(pos) = ui.ctx().pointer_hover_pos().filter(|pos| ui.max_rect().contains(*pos)) {
+                        self.state_mut().node_finder = Some(NodeFinder::new_at(pos));
+                    }
+                }
+                if let Some(order) = &execution_order {
+                    self.flow_overlay.draw(ui, self.state(), order);
+                }
+                if self.show_minimap {
+                    self.full_state.state.draw_minimap(ui, &mut self.full_state.user_state);
+                }
+
+                graph_response
+            })
+            .inner;
+        self.keymap.show_cheat_sheet(ctx);
+        if let Err(e) = self.keymap.show_settings(ctx) {
+            self.write_console_line(format!("Failed to save the shortcuts: {e:#}"), ConsoleMessageType::Error);
+        }
+
+        for node_response in graph_response.node_responses {
+            // handle all responses generated by the graph ui...
+            match node_response {
+                NodeResponse::User(user_event) => {
+                    if let Some(node_id) = user_event.node_id() {
+                        self.history.touch_node(&self.full_state, node_id);
+                    }
+                    match user_event {
+                        // node that supports adding parameters is trying to add one
+                        PulseGraphResponse::AddOutputParam(node_id, name, datatype) => {
+                            self.state_mut().graph.add_output_param(
+                                node_id,
+                                name,
+                                datatype,
+                            );
+                        }
+                        PulseGraphResponse::AddCustomInputParam(
+                            node_id,
+                            name,
+                            datatype,
+                            valuetype,
+                            paramkind,
+                            autoindex
+                        ) => {
+                            let param_list = &mut self.state_mut().graph.nodes.get_mut(node_id).unwrap().user_data.added_inputs;
+                            let idx = param_list.len();
+                            let name = if autoindex {
+                                format!("{name}{}", idx)
+                            } else {
+                                name
+                            };
+                            let input_id = self.state_mut().graph.add_input_param(
+                                node_id,
+                                name,
+                                datatype,
+                                valuetype,
+                                paramkind,
+                                true
+                            );
+                            self.state_mut().graph.nodes.get_mut(node_id).unwrap().user_data.added_inputs.push(input_id);
+                        }
+                        PulseGraphResponse::RemoveCustomInputParam(node_id, input_id) => {
+                            let param_list = &mut self.state_mut().graph.nodes.get_mut(node_id).unwrap().user_data.added_inputs;
+                            if let Some(pos) = param_list.iter().position(|x| *x == input_id) {
+                                param_list.remove(pos);
+                            }
+                            self.state_mut().graph.remove_input_param(input_id);
+                        }
+                        PulseGraphResponse::RemoveOutputParam(node_id, name) => {
+                            // node that supports adding parameters is removing one
+                            let param = self
+                                .state()
+                                .graph
+                                .nodes
+                                .get(node_id)
+                                .unwrap()
+                                .get_output(&name)
+                                .unwrap();
+                            self.state_mut().graph.remove_output_param(param);
+                        }
+                        PulseGraphResponse::ChangeOutputParamType(node_id, out_idx) => {
+                            match node_id {
+                                Some(node_id) => { 
+                                    self.update_node_public_output_types(node_id, out_idx); 
+                                }
+                                None => {
+                                    // send update to all nodes
+                                    for node_id in self.full_state.state.graph.nodes.keys().collect::<Vec<_>>() {
+                                        self.update_node_public_output_types(node_id, out_idx);
+                                    }
+                                }
+                            }
+                        }
+                        PulseGraphResponse::ChangeVariableParamType(node_id, var_idx) => {
+                            match node_id {
+                                Some(node_id) => { 
+                                    self.update_node_variable_types(node_id, var_idx); 
+                                }
+                                None => {
+                                    // send update to all nodes
+                                    for node_id in self.full_state.state.graph.nodes.keys().collect::<Vec<_>>() {
+                                        self.update_node_variable_types(node_id, var_idx);
+                                    }
+                                }
+                            }
+                        }
+                        PulseGraphResponse::ChangeParamType(node_id, name, typ) => {
+                            self.update_node_inputs_outputs_types(node_id, name.into(), Some(typ));
+                        }
+                        PulseGraphResponse::ChangeEventBinding(node_id, bindings) => {
+                            //let node = self.state.graph.nodes.get_mut(node_id).unwrap();
+                            self.update_event_binding_params(&node_id, &bindings);
+                        }
+                        PulseGraphResponse::ChangeFunctionBinding(node_id, bindings) => {
+                            //let node = self.state.graph.nodes.get_mut(node_id).unwrap();
+                            self.update_library_binding_params(&node_id, &bindings);
+                        }
+                        PulseGraphResponse::ChangeRemoteNodeId(node_id, node_id_refrence) => {
+                            self.update_remote_node_params(&node_id, &node_id_refrence);
+                        }
+                        PulseGraphResponse::UpdatePolymorphicTypes(node_id) => {
+                            if let Err(e) = self.update_polymorphic_output_types(node_id, None, None) {
+                                self.write_console_line(
+                                    format!("[UI] Failed to update polymorphic output types: {e}"), 
+                                    ConsoleMessageType::Warning
+                                );
+                            }
+                        }
+                        PulseGraphResponse::RenameExposedNode(node_id, name) => {
+                            self.user_state_mut().exposed_nodes.insert(node_id, name);
+                        }
+                    }
+                }
+                NodeResponse::DeleteNodeFull { node_id, .. } => {
+                    self.user_state_mut().exposed_nodes.remove(node_id);
+                }
+                NodeResponse::ConnectEventRejected { output, input } => {
+                    self.history.begin_step("Insert conversion");
+                    self.insert_conversion_node(output, input);
+                }
+                NodeResponse::ConnectEventEnded { output, input, input_hook: _} => {
+                    let graph = &self.state().graph;
+                    let node_id = graph.get_output(output).node;
+                    let input_node_id = graph.get_input(input).node;
+                    if let Err(e) = self.update_polymorphic_output_types(node_id, None, None) {
+                        self.write_console_line(
+                            format!("[UI] Warning: Failed to update polymorphic output types: {e}"),
+                            ConsoleMessageType::Warning
+                        );
+                    }
+                    self.update_reroute_types(input_node_id);
+                }
+                NodeResponse::DisconnectEvent { output: _, input } => {
+                    // the input is already gone if its node was deleted.
+                    if let Some(param) = self.state().graph.inputs.get(input) {
+                        self.update_reroute_types(param.node);
+                    }
+                }
+                NodeResponse::WireDoubleClicked { output, input, position } => {
+                    self.history.begin_step("Insert reroute");
+                    self.insert_reroute(output, input, position);
+                }
+                NodeResponse::SplitWire { node, .. } => {
+                    let label = self.state().graph[node].label.clone();
+                    self.history.begin_step(format!("Insert '{label}'"));
+                }
+                NodeResponse::NodeReplaced { old: _, new, dropped } => {
+                    // the old node is already gone, its connections were moved in the same frame.
+                    let label = self.state().graph[new].label.clone();
+                    self.history.begin_step(format!("Replace with '{label}'"));
+                    if !dropped.is_empty() {
+                        self.write_console_line(
+                            format!("[UI] Replacing with '{label}' dropped the connections of: {}", dropped.join(", ")),
+                            ConsoleMessageType::Warning
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+        // for (nodeid, name) in output_node_updates {
+        //     self.update_output_node_param(nodeid, &name, "param");
+        // }
+        // any edit, undo or save can change the modified marker
+        self.update_titlebar(ctx);
+    }
+}
+
@@ -0,0 +1,166 @@
+// Execution order overlay. Follows the action wires from every entry point by the same rules as the
+// compiler does in `traverse_inflow_nodes`, and numbers the actions in the order they run. Calls are
+// followed into the function they run.
+use std::collections::{HashMap, HashSet};
+use eframe::egui::{self, Color32, FontId, Stroke, StrokeKind, Vec2};
+use egui_node_graph2::*;
+use serde::{Deserialize, Serialize};
+use crate::utils::{get_action_outputs, runs_every_connected_action};
+use super::layout::is_inflow;
+use super::types::*;
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub(super) struct FlowOverlay {
+    pub show: bool,
+    /// Dots moving along the action wires.
+    pub animate: bool,
+}
+
+// The actions run from one entry point, in order.
+struct Chain {
+    entry: NodeId,
+    steps: Vec<NodeId>,
+    wires: Vec<(OutputId, InputId)>,
+}
+
+pub(super) struct ExecutionOrder {
+    chains: Vec<Chain>,
+}
+
+impl ExecutionOrder {
+    pub fn of(graph: &PulseGraph) -> Self {
+        // in the order the compiler finds them
+        let mut targets: HashMap<OutputId, Vec<InputId>> = HashMap::new();
+        for (input, output) in graph.iter_connections() {
+            targets.entry(output).or_default().push(input);
+        }
+        let chains = graph
+            .iter_nodes()
+            .filter(|node_id| is_inflow(graph[*node_id].user_data.template))
+            .map(|entry| {
+                let mut chain = Chain { entry, steps: Vec::new(), wires: Vec::new() };
+                let mut path = HashSet::from([entry]);
+                follow_actions(graph, &targets, entry, &mut chain, &mut path);
+                chain
+            })
+            .collect();
+        Self { chains }
+    }
+}
+
+// The function, or entity output listener, a call node runs before carrying on.
+fn called_node(graph: &PulseGraph, node_id: NodeId) -> Option<NodeId> {
+    let input = graph[node_id].get_input("nodeId").ok()?;
+    let called = graph.get_input(input).value.clone().try_node_id().ok()?;
+    graph.nodes.contains_key(called).then_some(called)
+}
+
+fn follow_actions(
+    graph: &PulseGraph,
+    targets: &HashMap<OutputId, Vec<InputId>>,
+    node_id: NodeId,
+    chain: &mut Chain,
+    path: &mut HashSet<NodeId>,
+) {
+    // `path` holds the nodes leading up to this one, only to stop at cycles. A node reached
+    // again some other way runs again, like the compiler emits it again.
+
+    // the actions of the called function run first, as part of this chain.
+    if graph[node_id].user_data.template == PulseNodeTemplate::CallNode {
+        if let Some(called) = called_node(graph, node_id).filter(|called| path.insert(*called)) {
+            follow_actions(graph, targets, called, chain, path);
+            path.remove(&called);
+        }
+    }
+    for (name, output) in get_action_outputs(&graph[node_id], graph) {
+        let Some(inputs) = targets.get(&output) else {
+            continue;
+        };
+        let count = if runs_every_connected_action(graph, &graph[node_id], name) { inputs.len() } else { 1 };
+        for input in inputs.iter().take(count) {
+            chain.wires.push((output, *input));
+            let next = graph.get_input(*input).node;
+            if !path.insert(next) {
+                continue;
+            }
+            // reroutes pass the flow through without running anything
+            if graph[next].user_data.template != PulseNodeTemplate::Reroute {
+                chain.steps.push(next);
+            }
+            follow_actions(graph, targets, next, chain, path);
+            path.remove(&next);
+        }
+    }
+}
+
+fn entry_color(idx: usize) -> Color32 {
+    egui::ecolor::Hsva::new((idx as f32 * 0.618_034).fract(), 0.6, 0.9, 1.0).into()
+}
+
+impl FlowOverlay {
+    /// Marks the action wires for the next time the graph is drawn. The wires run by the
+    /// selected entry points glow in their color.
+    pub fn apply(&self, state: &mut MyEditorState, order: Option<&ExecutionOrder>) {
+        state.wire_highlights.clear();
+        let Some(order) = order else {
+            return;
+        };
+        for (idx, chain) in order.chains.iter().enumerate() {
+            let selected = state.selected_nodes.contains(&chain.entry);
+            for wire in chain.wires.iter() {
+                let highlight = state.wire_highlights.entry(*wire).or_default();
+                highlight.animated |= self.animate;
+                if selected {
+                    highlight.glow = Some(entry_color(idx).gamma_multiply(0.5));
+                }
+            }
+        }
+    }
+
+    /// Numbers the actions and outlines the ones run by the selected entry points.
+    /// Call this after drawing the graph, with the same `ui`.
+    pub fn draw(&self, ui: &egui::Ui, state: &MyEditorState, order: &ExecutionOrder) {
+        let zoom = state.pan_zoom.zoom;
+        let pan = state.pan_zoom.pan + ui.max_rect().min.to_vec2();
+        let hidden = state.hidden_nodes();
+        let painter = ui.painter();
+
+        let mut badges: HashMap<NodeId, Vec<(Color32, usize)>> = HashMap::new();
+        for (idx, chain) in order.chains.iter().enumerate() {
+            let color = entry_color(idx);
+            for (step, node_id) in chain.steps.iter().enumerate() {
+                badges.entry(*node_id).or_default().push((color, step + 1));
+            }
+            if !state.selected_nodes.contains(&chain.entry) {
+                continue;
+            }
+            for node_id in std::iter::once(&chain.entry).chain(chain.steps.iter()) {
+                if let Some(rect) = state.node_rect(*node_id).filter(|_| !hidden.contains(node_id)) {
+                    painter.rect_stroke(
+                        rect.translate(pan).expand(3.0 * zoom),
+                        4.0 * zoom,
+                        Stroke::new(2.0 * zoom, color),
+                        StrokeKind::Outside,
+                    );
+                }
+            }
+        }
+
+        // one badge per time an entry point runs the node, above its top left corner
+        let padding = Vec2::new(4.0, 1.0) * zoom;
+        for (node_id, steps) in badges {
+            let Some(rect) = state.node_rect(node_id).filter(|_| !hidden.contains(&node_id)) else {
+                continue;
+            };
+            let mut corner = rect.translate(pan).left_top();
+            for (color, step) in steps {
+                let galley = painter.layout_no_wrap(step.to_string(), FontId::proportional(12.0 * zoom), Color32::BLACK);
+                let size = galley.size() + padding * 2.0;
+                let badge = egui::Rect::from_min_size(corner - Vec2::new(0.0, size.y + 2.0 * zoom), size);
+                painter.rect_filled(badge, 3.0 * zoom, color);
+                painter.galley(badge.min + padding, galley, Color32::BLACK);
+                corner.x += size.x + 2.0 * zoom;
+            }
+        }
+    }
+}
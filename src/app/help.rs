@@ -46,6 +46,7 @@ In the future this may be automated by this node.".into(),
         PulseNodeTemplate::ListenForEntityOutput => "Listens to an output from the provided entity in the current map, causing an action if it gets triggered. Also provides the activator entity handle.".into(),
         PulseNodeTemplate::Timeline => "Runs actions in a sequential order with a delay between each action.".into(),
         PulseNodeTemplate::NewArray => "Creates a new array of the provided type. You can also add initial values if applicable to the type, otherwise they may be added later at runtime.".into(),
+        PulseNodeTemplate::Reroute => "Routes a wire of any type, including actions, to keep the graph readable. It has no effect on the compiled graph. Double-click a wire to insert one.".into(),
         PulseNodeTemplate::LibraryBindingAssigned { binding } => {
             user_state
                 .get_library_binding_from_index(binding)
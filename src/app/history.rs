@@ -0,0 +1,1177 @@
+// Undo history made of edit commands. The places editing the graph note what they're about to
+// change, along with the changes the graph editor lists while it's drawn, and only those are
+// compared with how they turned out into reversible commands. An undo step only keeps what changed,
+// instead of a snapshot of the whole graph.
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use eframe::egui::{self, Pos2, RichText, Vec2};
+use egui_node_graph2::*;
+use slotmap::SlotMap;
+use crate::pulsetypes::{OutputDefinition, PulseVariable};
+use crate::typing::PulseValueType;
+use super::types::*;
+use super::FullGraphState;
+
+const MAX_STEPS: usize = 100;
+
+type PulseInputParam = InputParam<PulseDataType, PulseGraphValueType>;
+type PulseOutputParam = OutputParam<PulseDataType>;
+type PulseGraphEdit = GraphEdit<PulseNodeData, PulseDataType, PulseGraphValueType>;
+
+// Removed nodes come back with new ids, which replace the old ones everywhere they're referenced.
+#[derive(Default)]
+struct IdMap {
+    nodes: HashMap<NodeId, NodeId>,
+    inputs: HashMap<InputId, InputId>,
+    outputs: HashMap<OutputId, OutputId>,
+}
+
+impl IdMap {
+    fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.inputs.is_empty() && self.outputs.is_empty()
+    }
+    fn node(&self, id: NodeId) -> NodeId {
+        self.nodes.get(&id).copied().unwrap_or(id)
+    }
+    fn input(&self, id: InputId) -> InputId {
+        self.inputs.get(&id).copied().unwrap_or(id)
+    }
+    fn output(&self, id: OutputId) -> OutputId {
+        self.outputs.get(&id).copied().unwrap_or(id)
+    }
+    fn value(&self, value: &mut PulseGraphValueType) {
+        if let PulseGraphValueType::NodeChoice { node: Some(node_id) } = value {
+            *node_id = self.node(*node_id);
+        }
+    }
+    fn input_param(&self, param: &mut PulseInputParam) {
+        param.id = self.input(param.id);
+        param.node = self.node(param.node);
+        self.value(&mut param.value);
+    }
+}
+
+/// Parts of the user state that are edited along with the graph.
+#[derive(Clone)]
+pub(super) struct GraphData {
+    public_outputs: Vec<OutputDefinition>,
+    variables: Vec<PulseVariable>,
+    outputs_dropdown_choices: Vec<PulseValueType>,
+    graph_domain: String,
+    graph_subtype: String,
+}
+
+impl GraphData {
+    fn of(user_state: &PulseGraphState) -> Self {
+        Self {
+            public_outputs: user_state.public_outputs.clone(),
+            variables: user_state.variables.clone(),
+            outputs_dropdown_choices: user_state.outputs_dropdown_choices.clone(),
+            graph_domain: user_state.graph_domain.clone(),
+            graph_subtype: user_state.graph_subtype.clone(),
+        }
+    }
+
+    fn matches(&self, user_state: &PulseGraphState) -> bool {
+        self.public_outputs == user_state.public_outputs
+            && self.variables == user_state.variables
+            && self.outputs_dropdown_choices == user_state.outputs_dropdown_choices
+            && self.graph_domain == user_state.graph_domain
+            && self.graph_subtype == user_state.graph_subtype
+    }
+
+    fn write_to(&self, user_state: &mut PulseGraphState) {
+        user_state.public_outputs = self.public_outputs.clone();
+        user_state.variables = self.variables.clone();
+        user_state.outputs_dropdown_choices = self.outputs_dropdown_choices.clone();
+        user_state.graph_domain = self.graph_domain.clone();
+        user_state.graph_subtype = self.graph_subtype.clone();
+    }
+}
+
+/// What a node is besides its ports.
+#[derive(Clone, PartialEq)]
+pub(super) struct NodeProps {
+    label: String,
+    user_data: PulseNodeData,
+    // name under which other nodes can refer to it
+    exposed_name: Option<String>,
+}
+
+impl NodeProps {
+    fn of(state: &FullGraphState, node_id: NodeId) -> Self {
+        let node = &state.state.graph[node_id];
+        Self {
+            label: node.label.clone(),
+            user_data: node.user_data.clone(),
+            exposed_name: state.user_state.exposed_nodes.get(node_id).cloned(),
+        }
+    }
+
+    fn write_to(&self, state: &mut FullGraphState, node_id: NodeId) {
+        let node = &mut state.state.graph[node_id];
+        node.label = self.label.clone();
+        node.user_data = self.user_data.clone();
+        match &self.exposed_name {
+            Some(name) => state.user_state.exposed_nodes.insert(node_id, name.clone()),
+            None => state.user_state.exposed_nodes.remove(node_id),
+        };
+    }
+
+    fn remap(&mut self, map: &IdMap) {
+        for input_id in self.user_data.added_inputs.iter_mut() {
+            *input_id = map.input(*input_id);
+        }
+    }
+}
+
+/// Everything needed to bring a removed node back.
+#[derive(Clone)]
+pub(super) struct NodeSnapshot {
+    node_id: NodeId,
+    props: NodeProps,
+    inputs: Vec<(String, PulseInputParam)>,
+    outputs: Vec<(String, PulseOutputParam)>,
+    position: Pos2,
+    size: Option<Vec2>,
+    order: usize,
+}
+
+impl NodeSnapshot {
+    fn capture(state: &FullGraphState, node_id: NodeId) -> Self {
+        let editor = &state.state;
+        let (graph, node_order) = (&editor.graph, &editor.node_order);
+        let node = &graph[node_id];
+        Self {
+            node_id,
+            props: NodeProps::of(state, node_id),
+            inputs: node.inputs.iter().map(|(name, id)| (name.clone(), graph.get_input(*id).clone())).collect(),
+            outputs: node.outputs.iter().map(|(name, id)| (name.clone(), graph.get_output(*id).clone())).collect(),
+            position: editor.node_positions.get(node_id).copied().unwrap_or_default(),
+            size: editor.node_sizes.get(node_id).copied(),
+            order: node_order.iter().position(|id| *id == node_id).unwrap_or(node_order.len()),
+        }
+    }
+
+    // A node the graph editor deleted, its exposed name is still around until the deletion is handled.
+    fn removed(removed: &RemovedNode<PulseNodeData, PulseDataType, PulseGraphValueType>, state: &FullGraphState) -> Self {
+        let node = &removed.node;
+        Self {
+            node_id: node.id,
+            props: NodeProps {
+                label: node.label.clone(),
+                user_data: node.user_data.clone(),
+                exposed_name: state.user_state.exposed_nodes.get(node.id).cloned(),
+            },
+            inputs: node.inputs.iter().map(|(name, _)| name.clone()).zip(removed.inputs.iter().cloned()).collect(),
+            outputs: node.outputs.iter().map(|(name, _)| name.clone()).zip(removed.outputs.iter().cloned()).collect(),
+            position: removed.position,
+            size: removed.size,
+            order: removed.order,
+        }
+    }
+
+    fn restore(&self, state: &mut FullGraphState, map: &mut IdMap) {
+        let editor = &mut state.state;
+        let node_id = editor.graph.add_node(self.props.label.clone(), self.props.user_data.clone(), |graph, node_id| {
+            for (name, param) in self.inputs.iter() {
+                let input_id = graph.add_wide_input_param(
+                    node_id,
+                    name.clone(),
+                    param.typ.clone(),
+                    param.value.clone(),
+                    param.kind,
+                    param.max_connections,
+                    param.shown_inline,
+                );
+                map.inputs.insert(param.id, input_id);
+            }
+            for (name, param) in self.outputs.iter() {
+                let output_id = graph.add_output_param(node_id, name.clone(), param.typ.clone());
+                map.outputs.insert(param.id, output_id);
+            }
+        });
+        map.nodes.insert(self.node_id, node_id);
+        let node = &mut editor.graph.nodes[node_id];
+        for input_id in node.user_data.added_inputs.iter_mut() {
+            *input_id = map.input(*input_id);
+        }
+        editor.node_positions.insert(node_id, self.position);
+        if let Some(size) = self.size {
+            editor.node_sizes.insert(node_id, size);
+        }
+        editor.node_order.insert(self.order.min(editor.node_order.len()), node_id);
+        if let Some(name) = &self.props.exposed_name {
+            state.user_state.exposed_nodes.insert(node_id, name.clone());
+        }
+    }
+
+    fn remove(&self, state: &mut FullGraphState) {
+        let editor = &mut state.state;
+        if editor.graph.nodes.contains_key(self.node_id) {
+            editor.graph.remove_node(self.node_id);
+            editor.node_positions.remove(self.node_id);
+            editor.node_sizes.remove(self.node_id);
+            editor.node_order.retain(|id| *id != self.node_id);
+            editor.selected_nodes.retain(|id| *id != self.node_id);
+            state.user_state.exposed_nodes.remove(self.node_id);
+        }
+    }
+
+    fn same_ports(&self, node: &Node<PulseNodeData>) -> bool {
+        self.inputs.iter().map(|(name, param)| (name, param.id)).eq(node.inputs.iter().map(|(name, id)| (name, *id)))
+            && self.outputs.iter().map(|(name, param)| (name, param.id)).eq(node.outputs.iter().map(|(name, id)| (name, *id)))
+    }
+
+    fn remap(&mut self, map: &IdMap) {
+        self.node_id = map.node(self.node_id);
+        self.props.remap(map);
+        for (_, param) in self.inputs.iter_mut() {
+            map.input_param(param);
+        }
+        for (_, param) in self.outputs.iter_mut() {
+            param.id = map.output(param.id);
+            param.node = map.node(param.node);
+        }
+    }
+}
+
+/// A single reversible change of the graph.
+#[derive(Clone)]
+pub(super) enum EditCommand {
+    AddNode(Box<NodeSnapshot>),
+    RemoveNode(Box<NodeSnapshot>),
+    SetConnections { input_id: InputId, before: Vec<OutputId>, after: Vec<OutputId> },
+    MoveNode { node_id: NodeId, from: Pos2, to: Pos2 },
+    SetInput { input_id: InputId, before: Box<PulseInputParam>, after: Box<PulseInputParam> },
+    SetOutput { output_id: OutputId, before: PulseOutputParam, after: PulseOutputParam },
+    SetNode { node_id: NodeId, before: Box<NodeProps>, after: Box<NodeProps> },
+    SetGraphData { before: Box<GraphData>, after: Box<GraphData> },
+    SetFrames { before: SlotMap<FrameId, Frame>, after: SlotMap<FrameId, Frame> },
+}
+
+impl EditCommand {
+    // Applies the command, or reverts it when going backwards.
+    fn apply(&self, state: &mut FullGraphState, forward: bool, map: &mut IdMap) {
+        fn pick<T>(forward: bool, before: T, after: T) -> T {
+            if forward { after } else { before }
+        }
+        let editor = &mut state.state;
+        match self {
+            EditCommand::AddNode(snapshot) | EditCommand::RemoveNode(snapshot) => {
+                if matches!(self, EditCommand::AddNode(_)) == forward {
+                    snapshot.restore(state, map);
+                } else {
+                    snapshot.remove(state);
+                }
+            }
+            EditCommand::SetConnections { input_id, before, after } => {
+                if editor.graph.inputs.contains_key(*input_id) {
+                    let outputs: &Vec<OutputId> = pick(forward, before, after);
+                    if outputs.is_empty() {
+                        editor.graph.connections.remove(*input_id);
+                    } else {
+                        editor.graph.connections.insert(*input_id, outputs.clone());
+                    }
+                }
+            }
+            EditCommand::MoveNode { node_id, from, to } => {
+                if let Some(position) = editor.node_positions.get_mut(*node_id) {
+                    *position = *pick(forward, from, to);
+                }
+            }
+            EditCommand::SetInput { input_id, before, after } => {
+                if let Some(param) = editor.graph.inputs.get_mut(*input_id) {
+                    *param = (**pick(forward, before, after)).clone();
+                }
+            }
+            EditCommand::SetOutput { output_id, before, after } => {
+                if let Some(param) = editor.graph.outputs.get_mut(*output_id) {
+                    *param = pick(forward, before, after).clone();
+                }
+            }
+            EditCommand::SetNode { node_id, before, after } => {
+                if editor.graph.nodes.contains_key(*node_id) {
+                    pick(forward, before, after).write_to(state, *node_id);
+                }
+            }
+            EditCommand::SetGraphData { before, after } => {
+                pick(forward, before, after).write_to(&mut state.user_state);
+            }
+            EditCommand::SetFrames { before, after } => {
+                editor.frames = pick(forward, before, after).clone();
+                let frames = &editor.frames;
+                editor.selected_frames.retain(|frame_id| frames.contains_key(*frame_id));
+            }
+        }
+    }
+
+    fn remap(&mut self, map: &IdMap) {
+        match self {
+            EditCommand::AddNode(snapshot) | EditCommand::RemoveNode(snapshot) => snapshot.remap(map),
+            EditCommand::SetConnections { input_id, before, after } => {
+                *input_id = map.input(*input_id);
+                for output_id in before.iter_mut().chain(after.iter_mut()) {
+                    *output_id = map.output(*output_id);
+                }
+            }
+            EditCommand::MoveNode { node_id, .. } => *node_id = map.node(*node_id),
+            EditCommand::SetInput { input_id, before, after } => {
+                *input_id = map.input(*input_id);
+                map.input_param(before);
+                map.input_param(after);
+            }
+            EditCommand::SetOutput { output_id, before, after } => {
+                *output_id = map.output(*output_id);
+                for param in [before, after] {
+                    param.id = map.output(param.id);
+                    param.node = map.node(param.node);
+                }
+            }
+            EditCommand::SetNode { node_id, before, after } => {
+                *node_id = map.node(*node_id);
+                before.remap(map);
+                after.remap(map);
+            }
+            EditCommand::SetGraphData { .. } | EditCommand::SetFrames { .. } => {}
+        }
+    }
+
+    fn transform_positions(&mut self, transform: &impl Fn(Pos2) -> Pos2) {
+        match self {
+            EditCommand::AddNode(snapshot) | EditCommand::RemoveNode(snapshot) => {
+                snapshot.position = transform(snapshot.position);
+            }
+            EditCommand::MoveNode { from, to, .. } => {
+                *from = transform(*from);
+                *to = transform(*to);
+            }
+            EditCommand::SetFrames { before, after } => {
+                for (_, frame) in before.iter_mut().chain(after.iter_mut()) {
+                    frame.position = transform(frame.position);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Commands setting something to a value, that a later one setting the same thing can be folded into.
+    fn same_target(&self, other: &EditCommand) -> bool {
+        match (self, other) {
+            (EditCommand::MoveNode { node_id, .. }, EditCommand::MoveNode { node_id: other_id, .. })
+            | (EditCommand::SetNode { node_id, .. }, EditCommand::SetNode { node_id: other_id, .. }) => node_id == other_id,
+            (EditCommand::SetInput { input_id, .. }, EditCommand::SetInput { input_id: other_id, .. }) => input_id == other_id,
+            (EditCommand::SetOutput { output_id, .. }, EditCommand::SetOutput { output_id: other_id, .. }) => output_id == other_id,
+            (EditCommand::SetGraphData { .. }, EditCommand::SetGraphData { .. })
+            | (EditCommand::SetFrames { .. }, EditCommand::SetFrames { .. }) => true,
+            _ => false,
+        }
+    }
+
+    fn absorb(&mut self, other: &EditCommand) -> bool {
+        if !self.same_target(other) {
+            return false;
+        }
+        match (self, other) {
+            (EditCommand::MoveNode { to, .. }, EditCommand::MoveNode { to: other_to, .. }) => *to = *other_to,
+            (EditCommand::SetInput { after, .. }, EditCommand::SetInput { after: other_after, .. }) => {
+                *after = other_after.clone()
+            }
+            (EditCommand::SetOutput { after, .. }, EditCommand::SetOutput { after: other_after, .. }) => {
+                *after = other_after.clone()
+            }
+            (EditCommand::SetNode { after, .. }, EditCommand::SetNode { after: other_after, .. }) => {
+                *after = other_after.clone()
+            }
+            (EditCommand::SetGraphData { after, .. }, EditCommand::SetGraphData { after: other_after, .. }) => {
+                *after = other_after.clone()
+            }
+            (EditCommand::SetFrames { after, .. }, EditCommand::SetFrames { after: other_after, .. }) => {
+                *after = other_after.clone()
+            }
+            _ => {}
+        }
+        true
+    }
+}
+
+// Things edited since the last step was recorded, along with how they were before their first edit.
+struct Touched<K, V> {
+    order: Vec<K>,
+    before: HashMap<K, V>,
+}
+
+impl<K, V> Default for Touched<K, V> {
+    fn default() -> Self {
+        Self { order: Vec::new(), before: HashMap::new() }
+    }
+}
+
+impl<K: Copy + Eq + Hash, V> Touched<K, V> {
+    fn touch(&mut self, key: K, before: impl FnOnce() -> V) {
+        if let Entry::Vacant(entry) = self.before.entry(key) {
+            entry.insert(before());
+            self.order.push(key);
+        }
+    }
+
+    fn get(&self, key: K) -> Option<&V> {
+        self.before.get(&key)
+    }
+
+    fn contains(&self, key: K) -> bool {
+        self.before.contains_key(&key)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    // in the order they were first edited
+    fn iter(&self) -> impl Iterator<Item = (K, &V)> {
+        self.order.iter().map(|key| (*key, &self.before[key]))
+    }
+
+    fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        self.before.values_mut()
+    }
+}
+
+#[derive(Default)]
+struct Changes {
+    // nodes added since have nothing to go back to
+    nodes: Touched<NodeId, Option<Box<NodeSnapshot>>>,
+    connections: Touched<InputId, Vec<OutputId>>,
+    // positions and values of nodes that aren't touched as a whole
+    moves: Touched<NodeId, Pos2>,
+    inputs: Touched<InputId, PulseInputParam>,
+    data: Option<GraphData>,
+    frames: Option<SlotMap<FrameId, Frame>>,
+}
+
+impl Changes {
+    fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+            && self.connections.is_empty()
+            && self.moves.is_empty()
+            && self.inputs.is_empty()
+            && self.data.is_none()
+            && self.frames.is_none()
+    }
+
+    // A node about to be changed as a whole, along with the connections it takes part in.
+    // Its position and values may already have been edited, those are put back as they were.
+    fn touch_node(&mut self, state: &FullGraphState, mut snapshot: NodeSnapshot) {
+        let node_id = snapshot.node_id;
+        if self.nodes.contains(node_id) {
+            return;
+        }
+        if let Some(from) = self.moves.get(node_id) {
+            snapshot.position = *from;
+        }
+        for (_, param) in snapshot.inputs.iter_mut() {
+            if let Some(before) = self.inputs.get(param.id) {
+                *param = before.clone();
+            }
+        }
+        let graph = &state.state.graph;
+        let outputs: Vec<OutputId> = snapshot.outputs.iter().map(|(_, param)| param.id).collect();
+        for (input_id, sources) in graph.connections.iter() {
+            if graph.get_input(input_id).node == node_id || sources.iter().any(|output_id| outputs.contains(output_id)) {
+                self.connections.touch(input_id, || sources.clone());
+            }
+        }
+        for (_, param) in snapshot.inputs.iter() {
+            self.connections.touch(param.id, Vec::new);
+        }
+        self.nodes.touch(node_id, || Some(Box::new(snapshot)));
+    }
+
+    fn transform_positions(&mut self, transform: &impl Fn(Pos2) -> Pos2) {
+        for snapshot in self.nodes.values_mut().flatten() {
+            snapshot.position = transform(snapshot.position);
+        }
+        for position in self.moves.values_mut() {
+            *position = transform(*position);
+        }
+        for (_, frame) in self.frames.iter_mut().flat_map(|frames| frames.iter_mut()) {
+            frame.position = transform(frame.position);
+        }
+    }
+
+    // Commands turning what was touched into the current state, in the order they have to be applied.
+    // Undoing runs their inverses backwards, so connections are removed before the nodes they lead to,
+    // and made after the nodes they lead to exist.
+    fn into_commands(mut self, state: &FullGraphState) -> Vec<EditCommand> {
+        let editor = &state.state;
+        let graph = &editor.graph;
+        let mut removed = vec![];
+        let mut added = vec![];
+        let mut edits = vec![];
+        // nodes that gained or lost ports are replaced as a whole, the ids of removed ports can't be brought back.
+        let mut replaced_inputs = HashSet::new();
+        let mut replaced_outputs = HashSet::new();
+        for (node_id, before) in self.nodes.iter() {
+            let (before, node) = match (before, graph.nodes.get(node_id)) {
+                (Some(before), Some(node)) => (before, node),
+                (Some(before), None) => {
+                    removed.push(EditCommand::RemoveNode(before.clone()));
+                    continue;
+                }
+                (None, Some(_)) => {
+                    added.push(EditCommand::AddNode(Box::new(NodeSnapshot::capture(state, node_id))));
+                    continue;
+                }
+                // added and removed again
+                (None, None) => continue,
+            };
+            if !before.same_ports(node) {
+                replaced_inputs.extend(before.inputs.iter().map(|(_, param)| param.id).chain(node.input_ids()));
+                replaced_outputs.extend(before.outputs.iter().map(|(_, param)| param.id).chain(node.output_ids()));
+                removed.push(EditCommand::RemoveNode(before.clone()));
+                added.push(EditCommand::AddNode(Box::new(NodeSnapshot::capture(state, node_id))));
+                continue;
+            }
+            let props = NodeProps::of(state, node_id);
+            if before.props != props {
+                edits.push(EditCommand::SetNode { node_id, before: Box::new(before.props.clone()), after: Box::new(props) });
+            }
+            for (_, param) in before.inputs.iter() {
+                let current = graph.get_input(param.id);
+                if current != param {
+                    edits.push(EditCommand::SetInput {
+                        input_id: param.id,
+                        before: Box::new(param.clone()),
+                        after: Box::new(current.clone()),
+                    });
+                }
+            }
+            for (_, param) in before.outputs.iter() {
+                let current = graph.get_output(param.id);
+                if current != param {
+                    edits.push(EditCommand::SetOutput { output_id: param.id, before: param.clone(), after: current.clone() });
+                }
+            }
+            if let Some(to) = editor.node_positions.get(node_id) {
+                if *to != before.position {
+                    edits.push(EditCommand::MoveNode { node_id, from: before.position, to: *to });
+                }
+            }
+        }
+        // inputs of new nodes had nothing connected before
+        for command in added.iter() {
+            if let EditCommand::AddNode(snapshot) = command {
+                for (_, param) in snapshot.inputs.iter() {
+                    self.connections.touch(param.id, Vec::new);
+                }
+            }
+        }
+        for (node_id, from) in self.moves.iter() {
+            if let Some(to) = editor.node_positions.get(node_id).filter(|_| !self.nodes.contains(node_id)) {
+                if to != from {
+                    edits.push(EditCommand::MoveNode { node_id, from: *from, to: *to });
+                }
+            }
+        }
+        for (input_id, before) in self.inputs.iter() {
+            if let Some(current) = graph.inputs.get(input_id).filter(|_| !self.nodes.contains(before.node)) {
+                if current != before {
+                    edits.push(EditCommand::SetInput {
+                        input_id,
+                        before: Box::new(before.clone()),
+                        after: Box::new(current.clone()),
+                    });
+                }
+            }
+        }
+
+        // ports of replaced nodes get new ids when they come back, so all of their connections are remade.
+        let mut disconnects = vec![];
+        let mut connects = vec![];
+        for (input_id, before) in self.connections.iter() {
+            let before = before.clone();
+            let after = graph.connections.get(input_id).cloned().unwrap_or_default();
+            let remade = replaced_inputs.contains(&input_id);
+            if before == after && !remade && !before.iter().any(|output_id| replaced_outputs.contains(output_id)) {
+                continue;
+            }
+            let kept: Vec<OutputId> = before.iter()
+                .copied()
+                .filter(|output_id| !remade && after.contains(output_id) && !replaced_outputs.contains(output_id))
+                .collect();
+            if before != kept {
+                disconnects.push(EditCommand::SetConnections { input_id, before, after: kept.clone() });
+            }
+            if kept != after {
+                connects.push(EditCommand::SetConnections { input_id, before: kept, after });
+            }
+        }
+
+        if let Some(data) = self.data.filter(|data| !data.matches(&state.user_state)) {
+            edits.push(EditCommand::SetGraphData { before: Box::new(data), after: Box::new(GraphData::of(&state.user_state)) });
+        }
+        if let Some(frames) = self.frames {
+            if frames.len() != editor.frames.len()
+                || frames.iter().any(|(frame_id, frame)| editor.frames.get(frame_id) != Some(frame))
+            {
+                edits.push(EditCommand::SetFrames { before: frames, after: editor.frames.clone() });
+            }
+        }
+
+        let mut commands = disconnects;
+        commands.extend(removed);
+        commands.extend(added);
+        commands.extend(connects);
+        commands.extend(edits);
+        commands
+    }
+}
+
+fn node_label(graph: &PulseGraph, node_id: NodeId) -> String {
+    graph.nodes.get(node_id).map(|node| node.label.clone()).unwrap_or_default()
+}
+
+// Names the step after its most significant change.
+fn describe(commands: &[EditCommand], graph: &PulseGraph) -> String {
+    let added: Vec<&NodeSnapshot> = commands.iter()
+        .filter_map(|command| match command { EditCommand::AddNode(snapshot) => Some(&**snapshot), _ => None })
+        .collect();
+    let removed: Vec<&NodeSnapshot> = commands.iter()
+        .filter_map(|command| match command { EditCommand::RemoveNode(snapshot) => Some(&**snapshot), _ => None })
+        .collect();
+    let count_nodes = |snapshots: &[&NodeSnapshot], verb: &str| match snapshots {
+        [snapshot] => format!("{verb} '{}'", snapshot.props.label),
+        _ => format!("{verb} {} nodes", snapshots.len()),
+    };
+    let only_added: Vec<&NodeSnapshot> = added.iter().copied()
+        .filter(|snapshot| !removed.iter().any(|other| other.node_id == snapshot.node_id))
+        .collect();
+    let only_removed: Vec<&NodeSnapshot> = removed.iter().copied()
+        .filter(|snapshot| !added.iter().any(|other| other.node_id == snapshot.node_id))
+        .collect();
+    if !only_removed.is_empty() {
+        return count_nodes(&only_removed, "Delete");
+    }
+    if !only_added.is_empty() {
+        return count_nodes(&only_added, "Add");
+    }
+    if !added.is_empty() {
+        return count_nodes(&added, "Change");
+    }
+    for command in commands {
+        if let EditCommand::SetConnections { input_id, before, after } = command {
+            let output_id = after.iter().find(|id| !before.contains(id))
+                .or_else(|| before.iter().find(|id| !after.contains(id)));
+            let verb = if after.len() >= before.len() { "Connect" } else { "Disconnect" };
+            let input_node = graph.inputs.get(*input_id).map(|input| node_label(graph, input.node));
+            let output_node = output_id
+                .and_then(|id| graph.outputs.get(*id))
+                .map(|output| node_label(graph, output.node));
+            return match (output_node, input_node) {
+                (Some(output_node), Some(input_node)) => format!("{verb} '{output_node}' and '{input_node}'"),
+                _ => format!("{verb} nodes"),
+            };
+        }
+    }
+    let set_inputs: Vec<InputId> = commands.iter()
+        .filter_map(|command| match command { EditCommand::SetInput { input_id, .. } => Some(*input_id), _ => None })
+        .collect();
+    match set_inputs.as_slice() {
+        [] => {}
+        [input_id] => {
+            if let Some(input) = graph.inputs.get(*input_id) {
+                let name = graph[input.node].inputs.iter()
+                    .find(|(_, id)| id == input_id)
+                    .map(|(name, _)| name.clone())
+                    .unwrap_or_default();
+                return format!("Change '{name}' of '{}'", node_label(graph, input.node));
+            }
+            return "Change a value".to_string();
+        }
+        _ => return format!("Change {} values", set_inputs.len()),
+    }
+    let moved: Vec<NodeId> = commands.iter()
+        .filter_map(|command| match command { EditCommand::MoveNode { node_id, .. } => Some(*node_id), _ => None })
+        .collect();
+    for command in commands {
+        match command {
+            EditCommand::SetNode { node_id, .. } => return format!("Change '{}'", node_label(graph, *node_id)),
+            EditCommand::SetOutput { output_id, .. } => {
+                let node_id = graph.outputs.get(*output_id).map(|output| output.node);
+                return format!("Change '{}'", node_id.map(|id| node_label(graph, id)).unwrap_or_default());
+            }
+            _ => {}
+        }
+    }
+    match moved.as_slice() {
+        [] => {}
+        [node_id] => return format!("Move '{}'", node_label(graph, *node_id)),
+        _ => return format!("Move {} nodes", moved.len()),
+    }
+    for command in commands {
+        match command {
+            EditCommand::SetGraphData { before, after } => {
+                return if before.variables != after.variables {
+                    "Edit variables"
+                } else if before.public_outputs != after.public_outputs {
+                    "Edit public outputs"
+                } else {
+                    "Edit graph settings"
+                }.to_string();
+            }
+            EditCommand::SetFrames { .. } => return "Edit frames".to_string(),
+            _ => {}
+        }
+    }
+    "Edit graph".to_string()
+}
+
+pub(super) struct UndoStep {
+    label: String,
+    // named by the command that made it, rather than after its changes
+    named: bool,
+    commands: Vec<EditCommand>,
+}
+
+/// Undo and redo stacks of one graph.
+#[derive(Default)]
+pub(super) struct History {
+    undo: VecDeque<UndoStep>,
+    redo: Vec<UndoStep>,
+    changes: Changes,
+    // zoom the stored positions are at
+    zoom: Option<f32>,
+    // the last step still takes in changes, e.g. while a node is being dragged.
+    open: bool,
+    frame: u64,
+    last_change_frame: u64,
+    next_label: Option<String>,
+    // position of the saved state, unless the step it was at got changed or dropped
+    saved_position: usize,
+    saved_unreachable: bool,
+}
+
+impl History {
+    /// Starts a new step for the changes made by a command, under its name.
+    pub fn begin_step(&mut self, label: impl Into<String>) {
+        self.open = false;
+        self.next_label = Some(label.into());
+    }
+
+    /// Notes a node about to be changed, removed or given other ports, along with its connections.
+    pub fn touch_node(&mut self, state: &FullGraphState, node_id: NodeId) {
+        self.touch_nodes(state, &[node_id]);
+    }
+
+    pub fn touch_nodes(&mut self, state: &FullGraphState, nodes: &[NodeId]) {
+        for node_id in nodes {
+            if state.state.graph.nodes.contains_key(*node_id) && !self.changes.nodes.contains(*node_id) {
+                self.changes.touch_node(state, NodeSnapshot::capture(state, *node_id));
+            }
+        }
+    }
+
+    /// Notes a node that was just added.
+    pub fn node_added(&mut self, node_id: NodeId) {
+        self.changes.nodes.touch(node_id, || None);
+    }
+
+    /// Notes the connections of an input, before changing them.
+    pub fn touch_connections(&mut self, state: &FullGraphState, input_id: InputId) {
+        self.changes.connections.touch(input_id, || state.state.graph.connections(input_id));
+    }
+
+    /// Notes the positions of nodes, before moving them.
+    pub fn touch_positions(&mut self, state: &FullGraphState, nodes: &[NodeId]) {
+        for node_id in nodes {
+            if let Some(position) = state.state.node_positions.get(*node_id) {
+                self.changes.moves.touch(*node_id, || *position);
+            }
+        }
+    }
+
+    /// Notes the variables, public outputs and graph settings, before editing them.
+    pub fn touch_data(&mut self, state: &FullGraphState) {
+        self.changes.data.get_or_insert_with(|| GraphData::of(&state.user_state));
+    }
+
+    /// Notes the frames, before changing them.
+    pub fn touch_frames(&mut self, state: &FullGraphState) {
+        self.changes.frames.get_or_insert_with(|| state.state.frames.clone());
+    }
+
+    /// Takes the changes the graph editor listed while it was drawn.
+    pub fn take_graph_edits(&mut self, state: &mut FullGraphState) {
+        self.follow_zoom(&state.state);
+        let edits: Vec<PulseGraphEdit> = state.state.edits.drain(..).collect();
+        let changes = &mut self.changes;
+        for edit in edits {
+            match edit {
+                GraphEdit::NodeAdded(node_id) => changes.nodes.touch(node_id, || None),
+                GraphEdit::NodeRemoved(removed) => changes.touch_node(state, NodeSnapshot::removed(&removed, state)),
+                GraphEdit::Connections { input, before } => changes.connections.touch(input, || before),
+                GraphEdit::NodeMoved { node, from } => {
+                    if !changes.nodes.contains(node) {
+                        changes.moves.touch(node, || from);
+                    }
+                }
+                GraphEdit::InputValue { before } => {
+                    if !changes.nodes.contains(before.node) {
+                        changes.inputs.touch(before.id, || before);
+                    }
+                }
+                GraphEdit::Frames { before } => {
+                    changes.frames.get_or_insert(before);
+                }
+            }
+        }
+    }
+
+    // Zooming moves every node and frame around the middle of the view, the stored positions follow along.
+    fn follow_zoom(&mut self, editor: &MyEditorState) {
+        let zoom = editor.pan_zoom.zoom;
+        let last = *self.zoom.get_or_insert(zoom);
+        if last == zoom {
+            return;
+        }
+        let center = (editor.pan_zoom.clip_rect.size() / 2.0 - editor.pan_zoom.pan).to_pos2();
+        let scale = zoom / last;
+        let transform = |pos: Pos2| center + (pos - center) * scale;
+        for step in self.undo.iter_mut().chain(self.redo.iter_mut()) {
+            for command in step.commands.iter_mut() {
+                command.transform_positions(&transform);
+            }
+        }
+        self.changes.transform_positions(&transform);
+        self.zoom = Some(zoom);
+    }
+
+    /// Records the changes noted since the last call, returns true if there were any.
+    /// While `interacting`, further changes of the same things go into the same step,
+    /// so that dragging a node or typing in a field is undone at once.
+    pub fn record(&mut self, state: &FullGraphState, interacting: bool) -> bool {
+        self.frame += 1;
+        self.follow_zoom(&state.state);
+        let editor = &state.state;
+        let commands = std::mem::take(&mut self.changes).into_commands(state);
+        if commands.is_empty() {
+            self.next_label = None;
+            if !interacting {
+                self.open = false;
+            }
+            return false;
+        }
+        let position = self.undo.len();
+        if self.saved_position > position {
+            // the saved state was undone, and the redo stack is about to go
+            self.saved_unreachable = true;
+        }
+        let consecutive = self.last_change_frame + 1 == self.frame;
+        let merge_into = self.undo.back_mut().filter(|step| {
+            self.open && self.next_label.is_none() && (consecutive || (interacting && commands.iter().all(|command| {
+                step.commands.iter().any(|other| other.same_target(command))
+            })))
+        });
+        if let Some(step) = merge_into {
+            if self.saved_position == position {
+                self.saved_unreachable = true;
+            }
+            for command in commands {
+                if !step.commands.iter_mut().any(|other| other.absorb(&command)) {
+                    step.commands.push(command);
+                }
+            }
+            if !step.named {
+                step.label = describe(&step.commands, &editor.graph);
+            }
+        } else {
+            let named = self.next_label.is_some();
+            let label = self.next_label.take().unwrap_or_else(|| describe(&commands, &editor.graph));
+            self.undo.push_back(UndoStep { label, named, commands });
+            if self.undo.len() > MAX_STEPS {
+                self.undo.pop_front();
+                match self.saved_position.checked_sub(1) {
+                    Some(position) => self.saved_position = position,
+                    None => self.saved_unreachable = true,
+                }
+            }
+        }
+        self.redo.clear();
+        self.open = true;
+        self.last_change_frame = self.frame;
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo.back().map(|step| step.label.as_str())
+    }
+
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(|step| step.label.as_str())
+    }
+
+    /// Number of steps that can be undone.
+    pub fn position(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Remembers the current state as the one in the graph's file.
+    pub fn mark_saved(&mut self) {
+        self.saved_position = self.undo.len();
+        self.saved_unreachable = false;
+    }
+
+    /// For graphs that differ from their file from the start, e.g. restored after a crash.
+    pub fn mark_modified(&mut self) {
+        self.saved_unreachable = true;
+    }
+
+    /// Whether the graph differs from its saved state. Undoing or redoing back to it counts as unmodified.
+    pub fn is_modified(&self) -> bool {
+        self.saved_unreachable || self.saved_position != self.undo.len()
+    }
+
+    pub fn undo(&mut self, state: &mut FullGraphState) -> bool {
+        self.flush(state);
+        let Some(mut step) = self.undo.pop_back() else {
+            return false;
+        };
+        let mut map = IdMap::default();
+        for command in step.commands.iter_mut().rev() {
+            command.remap(&map);
+            command.apply(state, false, &mut map);
+        }
+        self.finish(&mut step, state, &map);
+        self.redo.push(step);
+        true
+    }
+
+    pub fn redo(&mut self, state: &mut FullGraphState) -> bool {
+        self.flush(state);
+        let Some(mut step) = self.redo.pop() else {
+            return false;
+        };
+        let mut map = IdMap::default();
+        for command in step.commands.iter_mut() {
+            command.remap(&map);
+            command.apply(state, true, &mut map);
+        }
+        self.finish(&mut step, state, &map);
+        self.undo.push_back(step);
+        true
+    }
+
+    // Changes not recorded yet get their own step, before stepping over them.
+    fn flush(&mut self, state: &FullGraphState) {
+        if !self.changes.is_empty() {
+            self.record(state, false);
+        }
+    }
+
+    // Points everything at the nodes that came back, and starts recording from the restored state.
+    fn finish(&mut self, step: &mut UndoStep, state: &mut FullGraphState, map: &IdMap) {
+        if !map.is_empty() {
+            let steps = self.undo.iter_mut().chain(self.redo.iter_mut()).chain(std::iter::once(step));
+            for command in steps.flat_map(|step| step.commands.iter_mut()) {
+                command.remap(map);
+            }
+            let editor = &mut state.state;
+            for (_, param) in editor.graph.inputs.iter_mut() {
+                map.value(&mut param.value);
+            }
+            for (_, node) in editor.graph.nodes.iter_mut() {
+                for input_id in node.user_data.added_inputs.iter_mut() {
+                    *input_id = map.input(*input_id);
+                }
+            }
+            for node_id in editor.selected_nodes.iter_mut() {
+                *node_id = map.node(*node_id);
+            }
+        }
+        state.state.connection_in_progress = None;
+        self.changes = Changes::default();
+        self.open = false;
+        self.next_label = None;
+    }
+
+    /// Shows the list of steps, returns the position to undo or redo to if one was clicked.
+    pub fn show(&self, ctx: &egui::Context, open: &mut bool) -> Option<usize> {
+        let mut target = None;
+        egui::Window::new("History")
+            .open(open)
+            .default_size([240.0, 320.0])
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                    let position = self.position();
+                    if ui.selectable_label(position == 0, "Opened graph").clicked() {
+                        target = Some(0);
+                    }
+                    for (idx, step) in self.undo.iter().enumerate() {
+                        if ui.selectable_label(position == idx + 1, &step.label).clicked() {
+                            target = Some(idx + 1);
+                        }
+                    }
+                    // undone steps are listed greyed out after the current one
+                    for (idx, step) in self.redo.iter().rev().enumerate() {
+                        let label = RichText::new(&step.label).weak();
+                        if ui.selectable_label(false, label).clicked() {
+                            target = Some(position + idx + 1);
+                        }
+                    }
+                });
+            });
+        target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui_node_graph2::geometry::pos2;
+
+    // A function node, connected to a call node that refers to it.
+    fn function_and_call(state: &mut FullGraphState) -> (NodeId, NodeId) {
+        let function = state.add_node(PulseNodeTemplate::Function, pos2(0.0, 0.0));
+        state.user_state.exposed_nodes.insert(function, "func".to_string());
+        let call = state.add_node(PulseNodeTemplate::CallNode, pos2(300.0, 0.0));
+        let graph = &mut state.state.graph;
+        let node_choice = graph[call].get_input("nodeId").unwrap();
+        graph.get_input_mut(node_choice).value = PulseGraphValueType::NodeChoice { node: Some(function) };
+        let output = graph[function].get_output("outAction").unwrap();
+        let input = graph[call].get_input("ActionIn").unwrap();
+        graph.add_connection(output, input, 0);
+        (function, call)
+    }
+
+    // Removes a node along with its exposed name, like deleting it in the editor.
+    fn remove_node(state: &mut FullGraphState, node_id: NodeId) {
+        state.state.graph.remove_node(node_id);
+        state.state.node_positions.remove(node_id);
+        state.state.node_order.retain(|id| *id != node_id);
+        state.user_state.exposed_nodes.remove(node_id);
+    }
+
+    fn action_sources(state: &FullGraphState, call: NodeId) -> Vec<OutputId> {
+        let graph = &state.state.graph;
+        graph.connections(graph[call].get_input("ActionIn").unwrap())
+    }
+
+    // The function the call node refers to, which has to be the one connected to it, under its old name.
+    fn called_function(state: &FullGraphState, call: NodeId) -> NodeId {
+        let graph = &state.state.graph;
+        let node_choice = graph.get_input(graph[call].get_input("nodeId").unwrap());
+        let PulseGraphValueType::NodeChoice { node: Some(function) } = &node_choice.value else {
+            panic!("the call node doesn't refer to a node");
+        };
+        assert_eq!(action_sources(state, call), [graph[*function].get_output("outAction").unwrap()]);
+        assert_eq!(state.user_state.exposed_nodes.get(*function).map(String::as_str), Some("func"));
+        *function
+    }
+
+    #[test]
+    fn delete_undo_redo() {
+        let mut state = FullGraphState::default();
+        let mut history = History::default();
+        let (function, call) = function_and_call(&mut state);
+
+        history.begin_step("Delete 'Function'");
+        history.touch_node(&state, function);
+        remove_node(&mut state, function);
+        assert!(history.record(&state, false));
+        assert!(action_sources(&state, call).is_empty());
+
+        // the function comes back under a new id, which the call node is pointed at
+        assert!(history.undo(&mut state));
+        let restored = called_function(&state, call);
+        assert_ne!(restored, function);
+        assert_eq!(state.state.node_positions[restored], pos2(0.0, 0.0));
+
+        assert!(history.redo(&mut state));
+        assert!(!state.state.graph.nodes.contains_key(restored));
+        assert!(action_sources(&state, call).is_empty());
+
+        assert!(history.undo(&mut state));
+        called_function(&state, call);
+    }
+
+    #[test]
+    fn undo_past_deleted_node() {
+        let mut state = FullGraphState::default();
+        let mut history = History::default();
+        let (function, call) = function_and_call(&mut state);
+
+        history.begin_step("Move 'Function'");
+        history.touch_positions(&state, &[function]);
+        state.state.node_positions[function] = pos2(100.0, 50.0);
+        assert!(history.record(&state, false));
+
+        history.begin_step("Delete 'Function'");
+        history.touch_node(&state, function);
+        remove_node(&mut state, function);
+        assert!(history.record(&state, false));
+
+        assert!(history.undo(&mut state));
+        let restored = called_function(&state, call);
+        assert_eq!(state.state.node_positions[restored], pos2(100.0, 50.0));
+
+        // the earlier step now moves the node that came back
+        assert!(history.undo(&mut state));
+        assert_eq!(state.state.node_positions[restored], pos2(0.0, 0.0));
+
+        assert!(history.redo(&mut state));
+        assert_eq!(state.state.node_positions[restored], pos2(100.0, 50.0));
+        assert!(history.redo(&mut state));
+        assert!(!state.state.graph.nodes.contains_key(restored));
+        assert!(action_sources(&state, call).is_empty());
+    }
+
+    #[test]
+    fn delete_listed_by_graph_editor() {
+        let mut state = FullGraphState::default();
+        let mut history = History::default();
+        let (function, call) = function_and_call(&mut state);
+
+        // what the graph editor lists when the node is deleted, its exposed name is removed afterwards
+        let graph = &state.state.graph;
+        let node = graph[function].clone();
+        let action_in = graph[call].get_input("ActionIn").unwrap();
+        let connections = GraphEdit::Connections { input: action_in, before: graph.connections(action_in) };
+        let removed = RemovedNode {
+            inputs: node.input_ids().map(|id| graph.get_input(id).clone()).collect(),
+            outputs: node.output_ids().map(|id| graph.get_output(id).clone()).collect(),
+            position: state.state.node_positions[function],
+            size: state.state.node_sizes.get(function).copied(),
+            order: 0,
+            node,
+        };
+        state.state.graph.remove_node(function);
+        state.state.node_positions.remove(function);
+        state.state.node_order.retain(|id| *id != function);
+        state.state.edits.extend([connections, GraphEdit::NodeRemoved(Box::new(removed))]);
+        history.take_graph_edits(&mut state);
+        assert!(state.state.edits.is_empty());
+        state.user_state.exposed_nodes.remove(function);
+        assert!(history.record(&state, false));
+        assert_eq!(history.undo_label(), Some("Delete 'Function'"));
+
+        assert!(history.undo(&mut state));
+        let restored = called_function(&state, call);
+        assert_eq!(state.state.node_order.first(), Some(&restored));
+
+        assert!(history.redo(&mut state));
+        assert!(!state.state.graph.nodes.contains_key(restored));
+        assert!(!state.user_state.exposed_nodes.contains_key(restored));
+
+        assert!(history.undo(&mut state));
+        called_function(&state, call);
+    }
+}
@@ -1,14 +1,17 @@
 use std::borrow::Cow;
 use egui_node_graph2::*;
+#[cfg(feature = "gui")]
 use eframe::egui::Color32;
+#[cfg(feature = "gui")]
 use eframe::egui::{self, ComboBox, DragValue};
 use super::types::*;
 use crate::typing::*;
+#[cfg(feature = "gui")]
 use super::help;
 use crate::pulsetypes::*;
 use crate::bindings::FunctionBinding;
 use crate::app::help::help_hover_text;
-use crate::app::FullGraphState;
+use crate::app::{add_library_binding_params, FullGraphState};
 
 impl Default for PulseGraphValueType {
     fn default() -> Self {
@@ -234,6 +237,7 @@ impl PulseGraphValueType {
 
 // A trait for the data types, to tell the library how to display them
 impl DataTypeTrait<PulseGraphState> for PulseDataType {
+    #[cfg(feature = "gui")]
     fn data_type_color(&self, _user_state: &mut PulseGraphState) -> egui::Color32 {
         match self {
             PulseDataType::Scalar => egui::Color32::from_rgb(38, 109, 211),
@@ -311,6 +315,14 @@ impl DataTypeTrait<PulseGraphState> for PulseDataType {
     fn allow_any_type(&self) -> bool {
         matches!(self, PulseDataType::Any)
     }
+
+    // action wires only lead to other actions, even though `Any` ports would accept them.
+    fn accepts_in_finder(&self, other: &Self) -> bool {
+        match (self, other) {
+            (PulseDataType::Action, _) | (_, PulseDataType::Action) => self == other,
+            _ => self == other || self.allow_any_type() || other.allow_any_type(),
+        }
+    }
 }
 
 // A trait for the node kinds, which tells the library how to build new nodes
@@ -380,6 +392,7 @@ impl NodeTemplateTrait for PulseNodeTemplate {
             PulseNodeTemplate::RandomFloat => "Random float".into(),
             PulseNodeTemplate::RandomInt => "Random int".into(),
             PulseNodeTemplate::EntOutputHandler => "Entity Output Handler".into(),
+            PulseNodeTemplate::Reroute => "Reroute".into(),
         }
     }
 
@@ -431,7 +444,7 @@ impl NodeTemplateTrait for PulseNodeTemplate {
             | PulseNodeTemplate::WhileLoop
             | PulseNodeTemplate::ForEach => vec!["Loops"],
             PulseNodeTemplate::SoundEventStart => vec!["Sound"],
-            PulseNodeTemplate::Comment => vec!["Editor"],
+            PulseNodeTemplate::Comment | PulseNodeTemplate::Reroute => vec!["Editor"],
             PulseNodeTemplate::SetAnimGraphParam => vec!["Animation"],
             PulseNodeTemplate::ConstantBool
             | PulseNodeTemplate::ConstantFloat
@@ -458,6 +471,22 @@ impl NodeTemplateTrait for PulseNodeTemplate {
         }
     }
 
+    fn node_finder_keywords(&self, user_state: &Self::UserState) -> Vec<String> {
+        match self {
+            PulseNodeTemplate::LibraryBindingAssigned { binding } => {
+                let Some(binding) = user_state.bindings.find_function_by_id(*binding) else {
+                    return Vec::new();
+                };
+                let params = binding.inparams.iter().chain(binding.outparams.iter()).flatten();
+                std::iter::once(binding.libname.clone())
+                    .chain(params.map(|param| param.name.clone()))
+                    .collect()
+            }
+            // the name of the kind itself, e.g. "CellWait", which the graph search matches as well
+            _ => vec![format!("{self:?}")],
+        }
+    }
+
     fn user_data(&self, _user_state: &mut Self::UserState) -> Self::NodeData {
         PulseNodeData {
             template: *self,
@@ -1083,6 +1112,9 @@ impl NodeTemplateTrait for PulseNodeTemplate {
                     InputParamKind::ConstantOnly,
                     true,
                 );
+                if let Some(binding) = user_state.bindings.find_function_by_id(*binding) {
+                    add_library_binding_params(graph, node_id, binding);
+                }
             }
             PulseNodeTemplate::GetArrayElement => {
                 //input_typ(graph, "expectedType");
@@ -1132,6 +1164,11 @@ impl NodeTemplateTrait for PulseNodeTemplate {
                 //input_typ(graph, "expectedType", PulseValueType::PVAL_ANY);
                 output_action(graph, "outAction");
             }
+            PulseNodeTemplate::Reroute => {
+                // the output takes on the type of whatever gets connected to the input.
+                input_any(graph, "in");
+                graph.add_output_param(node_id, "out".to_string(), PulseDataType::Any);
+            }
         }
     }
 }
@@ -1194,6 +1231,7 @@ impl NodeTemplateIter for AllMyNodeTemplates {
             PulseNodeTemplate::RandomInt,
             PulseNodeTemplate::RandomFloat,
             PulseNodeTemplate::EntOutputHandler,
+            PulseNodeTemplate::Reroute,
         ];
         templates.extend(
                 (0..self.game_function_count).map(|i| PulseNodeTemplate::LibraryBindingAssigned {
@@ -1205,6 +1243,7 @@ impl NodeTemplateIter for AllMyNodeTemplates {
     }
 }
 
+#[cfg(feature = "gui")]
 impl PulseNodeTemplate {
     fn has_user_addable_outputs(&self) -> bool {
         // This function is used to determine if the node has outputs that can be
@@ -1215,6 +1254,7 @@ impl PulseNodeTemplate {
     }
 }
 
+#[cfg(feature = "gui")]
 pub fn type_selection_widget(
     ui: &mut egui::Ui,
     id_salt: impl std::hash::Hash,
@@ -1238,6 +1278,7 @@ pub fn type_selection_widget(
         });
 }
 
+#[cfg(feature = "gui")]
 impl WidgetValueTrait for PulseGraphValueType {
     type Response = PulseGraphResponse;
     type UserState = PulseGraphState;
@@ -1595,6 +1636,7 @@ impl WidgetValueTrait for PulseGraphValueType {
 }
 
 impl UserResponseTrait for PulseGraphResponse {}
+#[cfg(feature = "gui")]
 impl NodeDataTrait for PulseNodeData {
     type Response = PulseGraphResponse;
     type UserState = PulseGraphState;
@@ -1616,10 +1658,15 @@ impl NodeDataTrait for PulseNodeData {
         if !help_text.is_empty() {
             _ui.label("ℹ").on_hover_text(help_text);
         }
-        if let Some(node_name) = user_state.exposed_nodes.get_mut(_node_id) {
-            _ui.text_edit_singleline(node_name);
+        let mut responses = vec![];
+        if let Some(node_name) = user_state.exposed_nodes.get(_node_id) {
+            // renamed by the editor, so the old name can be noted for undoing it
+            let mut new_name = node_name.clone();
+            if _ui.text_edit_singleline(&mut new_name).changed() {
+                responses.push(NodeResponse::User(PulseGraphResponse::RenameExposedNode(_node_id, new_name)));
+            }
         }
-        vec![]
+        responses
     }
 
     // This method will be called when drawing each node. This allows adding
@@ -1689,6 +1736,10 @@ impl NodeDataTrait for PulseNodeData {
         responses
     }
 
+    fn is_knot(&self) -> bool {
+        self.template == PulseNodeTemplate::Reroute
+    }
+
     fn titlebar_color(
         &self,
         _ui: &egui::Ui,
@@ -1746,6 +1797,7 @@ impl NodeDataTrait for PulseNodeData {
             | PulseNodeTemplate::NewArray => Some(Color32::from_rgb(77, 100, 105)),
             PulseNodeTemplate::ConcatString
             | PulseNodeTemplate::Comment
+            | PulseNodeTemplate::Reroute
             | PulseNodeTemplate::SetAnimGraphParam
             | PulseNodeTemplate::ReturnValue
             | PulseNodeTemplate::ScaleVector
@@ -1785,23 +1837,44 @@ impl Default for EditorConfig {
         EditorConfig {
             // TODO: it could be python3 on Linux
             python_interpreter: String::from("python"),
-            assetassembler_path: PathBuf::from(""),
-            red2_template_path: PathBuf::from("graph_red2_template.kv3"),
+            assetassembler_path: std::path::PathBuf::from(""),
+            red2_template_path: std::path::PathBuf::from("graph_red2_template.kv3"),
         }
     }
 }
 
+#[cfg(feature = "nongame_asset_build")]
+impl EditorConfig {
+    pub fn load_from_file(path: &std::path::Path) -> anyhow::Result<Self> {
+        let cfg_str = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&cfg_str)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))
+    }
+}
+
 fn slotmap_eq <K: slotmap::Key, T: PartialEq>(a: &slotmap::SlotMap<K, T>, b: &slotmap::SlotMap<K, T>) -> bool {
     a.len() == b.len() && a.iter().all(|(key, value)| b.get(key) == Some(value))
 }
 
+// frame positions change with the zoom, so like the nodes, moving them doesn't count as an edit.
+fn frames_eq(a: &slotmap::SlotMap<FrameId, Frame>, b: &slotmap::SlotMap<FrameId, Frame>) -> bool {
+    a.len() == b.len() && a.iter().all(|(key, frame)| b.get(key).is_some_and(|other| {
+        frame.title == other.title &&
+        frame.description == other.description &&
+        frame.color == other.color &&
+        frame.collapsed == other.collapsed
+    }))
+}
+
 impl PartialEq for FullGraphState {
     fn eq(&self, other: &Self) -> bool {
         self.state.graph.connections == other.state.graph.connections &&
         slotmap_eq(&self.state.graph.nodes, &other.state.graph.nodes) &&
         slotmap_eq(&self.state.graph.inputs, &other.state.graph.inputs) &&
         slotmap_eq(&self.state.graph.outputs, &other.state.graph.outputs) &&
-        // user_state has PartialEq derived, but for purposes of undo we only want to compare some fields that are relevant to us.
-        self.user_state.eq_limited(&other.user_state)
+        // user_state has PartialEq derived, but for detecting edits we only want to compare some fields that are relevant to us.
+        self.user_state.eq_limited(&other.user_state) &&
+        frames_eq(&self.state.frames, &other.state.frames)
     }
 }
\ No newline at end of file
@@ -0,0 +1,349 @@
+// Keyboard shortcuts. Every action with a shortcut can be rebound in the shortcuts window, the
+// bindings are kept in `keymap.json` in the working directory, which can also be edited by hand.
+// Actions missing from the file keep their default shortcut.
+use std::collections::BTreeMap;
+use std::fs;
+use anyhow::anyhow;
+use eframe::egui::{self, Key, KeyboardShortcut, Modifiers, RichText};
+use serde::{Deserialize, Serialize};
+use strum::VariantArray as _;
+use strum_macros::VariantArray;
+
+const KEYMAP_PATH: &str = "keymap.json";
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, VariantArray, Serialize, Deserialize)]
+pub(super) enum Action {
+    Compile,
+    Save,
+    SaveAs,
+    Undo,
+    Redo,
+    Duplicate,
+    Delete,
+    AutoLayout,
+    Group,
+    Search,
+    OpenNodeFinder,
+    FocusSelection,
+    ToggleMinimap,
+    ToggleExecutionOrder,
+    NewTab,
+    CloseTab,
+    NextTab,
+    PreviousTab,
+    ShowShortcuts,
+}
+
+impl Action {
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Compile => "Compile",
+            Action::Save => "Save",
+            Action::SaveAs => "Save as",
+            Action::Undo => "Undo",
+            Action::Redo => "Redo",
+            Action::Duplicate => "Duplicate selected nodes",
+            Action::Delete => "Delete selected nodes",
+            Action::AutoLayout => "Auto layout",
+            Action::Group => "Group selected nodes",
+            Action::Search => "Search",
+            Action::OpenNodeFinder => "Open node finder at cursor",
+            Action::FocusSelection => "Focus selection",
+            Action::ToggleMinimap => "Toggle minimap",
+            Action::ToggleExecutionOrder => "Toggle execution order",
+            Action::NewTab => "New tab",
+            Action::CloseTab => "Close tab",
+            Action::NextTab => "Next tab",
+            Action::PreviousTab => "Previous tab",
+            Action::ShowShortcuts => "Show shortcuts",
+        }
+    }
+
+    fn default_shortcut(self) -> Option<KeyboardShortcut> {
+        let (modifiers, key) = match self {
+            Action::Compile => (Modifiers::COMMAND, Key::R),
+            Action::Save => (Modifiers::COMMAND, Key::S),
+            Action::SaveAs => (Modifiers::COMMAND | Modifiers::SHIFT, Key::S),
+            Action::Undo => (Modifiers::COMMAND, Key::Z),
+            Action::Redo => (Modifiers::COMMAND, Key::Y),
+            Action::Duplicate => (Modifiers::SHIFT, Key::D),
+            Action::Delete => (Modifiers::NONE, Key::Delete),
+            Action::AutoLayout => (Modifiers::SHIFT, Key::L),
+            Action::Group => (Modifiers::COMMAND, Key::G),
+            Action::Search => (Modifiers::COMMAND, Key::F),
+            Action::OpenNodeFinder => (Modifiers::NONE, Key::Space),
+            Action::FocusSelection => (Modifiers::NONE, Key::F),
+            Action::ToggleMinimap => (Modifiers::NONE, Key::M),
+            Action::ToggleExecutionOrder => (Modifiers::NONE, Key::O),
+            Action::NewTab => (Modifiers::COMMAND, Key::T),
+            Action::CloseTab => (Modifiers::COMMAND, Key::W),
+            Action::NextTab => (Modifiers::COMMAND, Key::Tab),
+            Action::PreviousTab => (Modifiers::COMMAND | Modifiers::SHIFT, Key::Tab),
+            Action::ShowShortcuts => (Modifiers::NONE, Key::F1),
+        };
+        Some(KeyboardShortcut::new(modifiers, key))
+    }
+}
+
+// Shortcuts as written in the keymap file, e.g. "Ctrl+Shift+S" or "Alt+Plus", with the key names
+// egui uses. Ctrl is Cmd on macOS.
+fn parse_shortcut(text: &str) -> anyhow::Result<KeyboardShortcut> {
+    let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+    let key_name = parts.pop().unwrap_or_default();
+    let key = Key::from_name(key_name).ok_or_else(|| anyhow!("unknown key '{key_name}'"))?;
+    let mut modifiers = Modifiers::NONE;
+    for part in parts {
+        modifiers |= match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "cmd" | "command" => Modifiers::COMMAND,
+            "shift" => Modifiers::SHIFT,
+            "alt" | "option" => Modifiers::ALT,
+            _ => return Err(anyhow!("unknown modifier '{part}'")),
+        };
+    }
+    Ok(KeyboardShortcut::new(modifiers, key))
+}
+
+fn shortcut_to_string(shortcut: &KeyboardShortcut) -> String {
+    let mut text = String::new();
+    for (active, name) in [
+        (shortcut.modifiers.command, "Ctrl+"),
+        (shortcut.modifiers.alt, "Alt+"),
+        (shortcut.modifiers.shift, "Shift+"),
+    ] {
+        if active {
+            text.push_str(name);
+        }
+    }
+    text.push_str(shortcut.logical_key.name());
+    text
+}
+
+pub(super) struct Keymap {
+    bindings: BTreeMap<Action, Option<KeyboardShortcut>>,
+    /// The action waiting for a key combination in the shortcuts window.
+    capturing: Option<Action>,
+    pub show_settings: bool,
+    pub show_cheat_sheet: bool,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: Action::VARIANTS.iter().map(|action| (*action, action.default_shortcut())).collect(),
+            capturing: None,
+            show_settings: false,
+            show_cheat_sheet: false,
+        }
+    }
+}
+
+impl Keymap {
+    /// Reads the keymap file if there is one. Bindings that can't be read keep their default,
+    /// and are described in the returned messages along with conflicting shortcuts.
+    pub fn load() -> (Self, Vec<String>) {
+        let mut keymap = Self::default();
+        let mut problems = Vec::new();
+        let contents = match fs::read_to_string(KEYMAP_PATH) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return (keymap, problems),
+            Err(e) => {
+                problems.push(format!("Failed to read {KEYMAP_PATH}: {e}"));
+                return (keymap, problems);
+            }
+        };
+        let entries: BTreeMap<String, Option<String>> = match serde_json::from_str(&contents) {
+            Ok(entries) => entries,
+            Err(e) => {
+                problems.push(format!("Failed to read {KEYMAP_PATH}, using the default shortcuts: {e}"));
+                return (keymap, problems);
+            }
+        };
+        for (name, text) in entries {
+            let Some(action) = Action::VARIANTS.iter().find(|action| format!("{action:?}") == name) else {
+                problems.push(format!("{KEYMAP_PATH}: unknown action '{name}'"));
+                continue;
+            };
+            match text.as_deref().map(parse_shortcut).transpose() {
+                Ok(shortcut) => {
+                    keymap.bindings.insert(*action, shortcut);
+                }
+                Err(e) => problems.push(format!("{KEYMAP_PATH}: shortcut of '{name}': {e}")),
+            }
+        }
+        for action in Action::VARIANTS {
+            if let (Some(shortcut), Some(other)) = (keymap.get(*action), keymap.conflict(*action)) {
+                // reported once per pair
+                if other > *action {
+                    problems.push(format!(
+                        "{KEYMAP_PATH}: '{}' and '{}' are both bound to {}, neither will work until one is changed",
+                        action.label(), other.label(), shortcut_to_string(&shortcut),
+                    ));
+                }
+            }
+        }
+        (keymap, problems)
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        let entries: BTreeMap<Action, Option<String>> = self.bindings
+            .iter()
+            .map(|(action, shortcut)| (*action, shortcut.as_ref().map(shortcut_to_string)))
+            .collect();
+        fs::write(KEYMAP_PATH, serde_json::to_string_pretty(&entries)?)?;
+        Ok(())
+    }
+
+    pub fn get(&self, action: Action) -> Option<KeyboardShortcut> {
+        self.bindings.get(&action).copied().flatten()
+    }
+
+    /// Another action bound to the same shortcut.
+    fn conflict(&self, action: Action) -> Option<Action> {
+        let shortcut = self.get(action)?;
+        Action::VARIANTS.iter().copied().find(|other| *other != action && self.get(*other) == Some(shortcut))
+    }
+
+    /// Whether the shortcut of the action was pressed this frame. Shortcuts without Ctrl or Alt
+    /// don't work while typing, and conflicting ones don't work at all.
+    pub fn pressed(&self, ctx: &egui::Context, action: Action) -> bool {
+        let Some(shortcut) = self.get(action) else {
+            return false;
+        };
+        if self.capturing.is_some() || self.conflict(action).is_some() {
+            return false;
+        }
+        let typed = !(shortcut.modifiers.command || shortcut.modifiers.alt);
+        if typed && ctx.wants_keyboard_input() {
+            return false;
+        }
+        ctx.input(|i| i.modifiers.matches_exact(shortcut.modifiers) && i.key_pressed(shortcut.logical_key))
+    }
+
+    /// Hover text of a button, followed by the action's shortcut if it has one.
+    pub fn hint(&self, ctx: &egui::Context, text: &str, action: Action) -> String {
+        match self.get(action) {
+            Some(shortcut) => format!("{text} ({})", ctx.format_shortcut(&shortcut)),
+            None => text.to_string(),
+        }
+    }
+
+    fn shortcut_text(&self, ctx: &egui::Context, action: Action) -> String {
+        self.get(action).map(|shortcut| ctx.format_shortcut(&shortcut)).unwrap_or_default()
+    }
+
+    /// The window for rebinding the shortcuts. Changes are written to the keymap file right away,
+    /// failing to do so is returned as an error.
+    pub fn show_settings(&mut self, ctx: &egui::Context) -> anyhow::Result<()> {
+        if !self.show_settings {
+            self.capturing = None;
+            return Ok(());
+        }
+        let mut changed = false;
+        if let Some(action) = self.capturing {
+            let pressed = ctx.input(|i| {
+                i.events.iter().find_map(|event| match event {
+                    egui::Event::Key { key, pressed: true, modifiers, .. } => Some((*key, *modifiers)),
+                    _ => None,
+                })
+            });
+            match pressed {
+                Some((Key::Escape, modifiers)) if modifiers.is_none() => self.capturing = None,
+                Some((key, modifiers)) => {
+                    // only the modifiers a shortcut can be matched with
+                    let modifiers = Modifiers {
+                        alt: modifiers.alt,
+                        shift: modifiers.shift,
+                        command: modifiers.command,
+                        ..Default::default()
+                    };
+                    self.bindings.insert(action, Some(KeyboardShortcut::new(modifiers, key)));
+                    self.capturing = None;
+                    changed = true;
+                }
+                None => {}
+            }
+        }
+        let mut open = self.show_settings;
+        egui::Window::new("Keyboard shortcuts")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Click a shortcut and press the new key combination, Escape cancels.");
+                egui::Grid::new("keymap_settings").striped(true).show(ui, |ui| {
+                    for action in Action::VARIANTS.iter().copied() {
+                        ui.label(action.label());
+                        let text = if self.capturing == Some(action) {
+                            RichText::new("Press a key…").italics()
+                        } else {
+                            match self.get(action) {
+                                Some(shortcut) => RichText::new(ctx.format_shortcut(&shortcut)),
+                                None => RichText::new("Unbound").weak(),
+                            }
+                        };
+                        let conflict = self.conflict(action);
+                        let text = if conflict.is_some() { text.color(egui::Color32::RED) } else { text };
+                        let mut response = ui.add(egui::Button::new(text).min_size(egui::vec2(120.0, 0.0)));
+                        if let Some(other) = conflict {
+                            response = response.on_hover_text(format!(
+                                "Also bound to '{}', neither works until one of them is changed", other.label()
+                            ));
+                        }
+                        if response.clicked() {
+                            self.capturing = Some(action);
+                        }
+                        if ui.add_enabled(self.get(action).is_some(), egui::Button::new("Clear")).clicked() {
+                            self.bindings.insert(action, None);
+                            changed = true;
+                        }
+                        if ui.add_enabled(self.get(action) != action.default_shortcut(), egui::Button::new("Reset"))
+                            .on_hover_text(action.default_shortcut().map(|shortcut| ctx.format_shortcut(&shortcut)).unwrap_or_default())
+                            .clicked()
+                        {
+                            self.bindings.insert(action, action.default_shortcut());
+                            changed = true;
+                        }
+                        ui.end_row();
+                    }
+                });
+                ui.separator();
+                if ui.button("Reset all").clicked() {
+                    self.bindings = Self::default().bindings;
+                    self.capturing = None;
+                    changed = true;
+                }
+            });
+        self.show_settings = open;
+        if changed {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// A list of every action with a shortcut, closed by pressing its own shortcut again.
+    pub fn show_cheat_sheet(&mut self, ctx: &egui::Context) {
+        if !self.show_cheat_sheet {
+            return;
+        }
+        let mut open = self.show_cheat_sheet;
+        egui::Window::new("Shortcuts")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                egui::Grid::new("keymap_cheat_sheet").spacing(egui::vec2(24.0, 4.0)).show(ui, |ui| {
+                    for action in Action::VARIANTS.iter().copied().filter(|action| self.get(*action).is_some()) {
+                        ui.label(action.label());
+                        ui.label(RichText::new(self.shortcut_text(ctx, action)).monospace().strong());
+                        ui.end_row();
+                    }
+                });
+                ui.separator();
+                if ui.button("Change shortcuts…").clicked() {
+                    self.show_settings = true;
+                    self.show_cheat_sheet = false;
+                }
+            });
+        self.show_cheat_sheet &= open;
+    }
+}
@@ -0,0 +1,328 @@
+// Automatic arrangement of nodes: a layered (Sugiyama style) layout that runs
+// left to right along the action flow, with value producers placed in the
+// layer before their consumers.
+use std::collections::HashMap;
+use egui_node_graph2::*;
+use egui_node_graph2::geometry::{vec2, Pos2, Vec2};
+use super::types::*;
+use super::FullGraphState;
+
+/// Horizontal space between layers, at zoom 1.
+const LAYER_GAP: f32 = 80.0;
+/// Vertical space between nodes of a layer, at zoom 1.
+const NODE_GAP: f32 = 30.0;
+/// Height taken by a wire passing through a layer, at zoom 1.
+const WIRE_HEIGHT: f32 = 10.0;
+/// Number of sweeps used to reduce wire crossings.
+const ORDERING_SWEEPS: usize = 8;
+
+// where the action flow starts, each of these runs its own chain of actions.
+pub(super) fn is_inflow(template: PulseNodeTemplate) -> bool {
+    matches!(
+        template,
+        PulseNodeTemplate::EventHandler
+            | PulseNodeTemplate::CellPublicMethod
+            | PulseNodeTemplate::GraphHook
+            | PulseNodeTemplate::EntOutputHandler
+            | PulseNodeTemplate::Function
+    )
+}
+
+// Connections between the laid out nodes, by index into the node list.
+struct Edges {
+    succ: Vec<Vec<usize>>,
+    pred: Vec<Vec<usize>>,
+    // whether the node has an incoming action connection
+    action_in: Vec<bool>,
+}
+
+impl FullGraphState {
+    /// Arranges `nodes` in layers along the action flow, keeping the top left corner
+    /// of their bounding box in place. Only connections between these nodes are
+    /// taken into account. Positions are in the space of `node_positions`, so
+    /// `node_size` and the spacing have to be at the current zoom level.
+    pub(super) fn auto_layout(&mut self, nodes: &[NodeId], zoom: f32, node_size: impl Fn(NodeId) -> Vec2) {
+        let graph = &self.state.graph;
+        let nodes: Vec<NodeId> = nodes.iter().copied().filter(|id| graph.nodes.contains_key(*id)).collect();
+        if nodes.is_empty() {
+            return;
+        }
+        let index: HashMap<NodeId, usize> = nodes.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let mut edges = Edges {
+            succ: vec![vec![]; nodes.len()],
+            pred: vec![vec![]; nodes.len()],
+            action_in: vec![false; nodes.len()],
+        };
+        for (input, output) in graph.iter_connections() {
+            let output = graph.get_output(output);
+            let (Some(&from), Some(&to)) = (index.get(&output.node), index.get(&graph.get_input(input).node)) else {
+                continue;
+            };
+            if from == to {
+                continue;
+            }
+            if output.typ == PulseDataType::Action {
+                edges.action_in[to] = true;
+            }
+            if !edges.succ[from].contains(&to) {
+                edges.succ[from].push(to);
+                edges.pred[to].push(from);
+            }
+        }
+        let inflow: Vec<bool> = nodes.iter().map(|id| is_inflow(graph[*id].user_data.template)).collect();
+        let sizes: Vec<Vec2> = nodes.iter().map(|id| node_size(*id)).collect();
+
+        let origin = nodes
+            .iter()
+            .filter_map(|id| self.state.node_positions.get(*id))
+            .fold(Pos2::new(f32::INFINITY, f32::INFINITY), |min, pos| min.min(*pos));
+        let origin = if origin.x.is_finite() { origin } else { Pos2::ZERO };
+
+        // Unconnected groups of nodes are laid out on their own, one below the other.
+        let mut top = 0.0;
+        for component in components(&edges) {
+            let (positions, height) = layout_component(&component, &edges, &inflow, &sizes, zoom);
+            for (node, position) in positions {
+                self.state.node_positions.insert(nodes[node], origin + position + vec2(0.0, top));
+            }
+            top += height + NODE_GAP * 2.0 * zoom;
+        }
+    }
+}
+
+/// Weakly connected components, each in the order its nodes were given.
+fn components(edges: &Edges) -> Vec<Vec<usize>> {
+    let count = edges.succ.len();
+    let mut component_of = vec![usize::MAX; count];
+    let mut components: Vec<Vec<usize>> = vec![];
+    for start in 0..count {
+        if component_of[start] != usize::MAX {
+            continue;
+        }
+        let id = components.len();
+        let mut stack = vec![start];
+        component_of[start] = id;
+        let mut members = vec![];
+        while let Some(node) = stack.pop() {
+            members.push(node);
+            for &other in edges.succ[node].iter().chain(edges.pred[node].iter()) {
+                if component_of[other] == usize::MAX {
+                    component_of[other] = id;
+                    stack.push(other);
+                }
+            }
+        }
+        members.sort_unstable();
+        components.push(members);
+    }
+    components
+}
+
+/// Returns the position of each node relative to the top left of the component, and its height.
+fn layout_component(
+    members: &[usize],
+    edges: &Edges,
+    inflow: &[bool],
+    sizes: &[Vec2],
+    zoom: f32,
+) -> (Vec<(usize, Vec2)>, f32) {
+    // Break cycles with a depth first search starting from the inflow nodes,
+    // ignoring the edges that lead back into the current path.
+    let mut roots: Vec<usize> = members.iter().copied().filter(|n| inflow[*n]).collect();
+    roots.extend(members.iter().copied().filter(|n| !inflow[*n] && edges.pred[*n].is_empty()));
+    roots.extend(members.iter().copied());
+    // 0: unvisited, 1: on the current path, 2: done
+    let mut state: HashMap<usize, u8> = members.iter().map(|n| (*n, 0)).collect();
+    let mut post_order = vec![];
+    let mut dag_succ: HashMap<usize, Vec<usize>> = members.iter().map(|n| (*n, vec![])).collect();
+    for root in roots {
+        if state[&root] != 0 {
+            continue;
+        }
+        // iterative dfs: (node, index of the next successor to visit)
+        let mut stack = vec![(root, 0)];
+        state.insert(root, 1);
+        while let Some((node, next)) = stack.last_mut() {
+            let node = *node;
+            if let Some(&succ) = edges.succ[node].get(*next) {
+                *next += 1;
+                match state[&succ] {
+                    0 => {
+                        dag_succ.get_mut(&node).unwrap().push(succ);
+                        state.insert(succ, 1);
+                        stack.push((succ, 0));
+                    }
+                    2 => dag_succ.get_mut(&node).unwrap().push(succ),
+                    _ => {} // back edge
+                }
+            } else {
+                state.insert(node, 2);
+                post_order.push(node);
+                stack.pop();
+            }
+        }
+    }
+    let topo_order: Vec<usize> = post_order.into_iter().rev().collect();
+    let mut dag_pred: HashMap<usize, Vec<usize>> = members.iter().map(|n| (*n, vec![])).collect();
+    for node in &topo_order {
+        for succ in &dag_succ[node] {
+            dag_pred.get_mut(succ).unwrap().push(*node);
+        }
+    }
+
+    // Longest path layering, then pull the nodes that only produce values
+    // right up to the layer before their first consumer.
+    let mut layer: HashMap<usize, i32> = HashMap::new();
+    for node in &topo_order {
+        let l = dag_pred[node].iter().map(|p| layer[p] + 1).max().unwrap_or(0);
+        layer.insert(*node, l);
+    }
+    for node in topo_order.iter().rev() {
+        if inflow[*node] || edges.action_in[*node] {
+            continue;
+        }
+        if let Some(l) = dag_succ[node].iter().map(|s| layer[s] - 1).min() {
+            layer.insert(*node, l);
+        }
+    }
+    let min_layer = layer.values().copied().min().unwrap_or(0);
+    let layer_count = (layer.values().copied().max().unwrap_or(0) - min_layer + 1) as usize;
+
+    // Vertices are the nodes followed by dummies, which route wires spanning several layers.
+    let mut vertex_node: Vec<Option<usize>> = vec![];
+    let mut vertex_of: HashMap<usize, usize> = HashMap::new();
+    let mut vertex_layer: Vec<usize> = vec![];
+    let mut layers: Vec<Vec<usize>> = vec![vec![]; layer_count];
+    for node in &topo_order {
+        let l = (layer[node] - min_layer) as usize;
+        vertex_of.insert(*node, vertex_node.len());
+        layers[l].push(vertex_node.len());
+        vertex_layer.push(l);
+        vertex_node.push(Some(*node));
+    }
+    let mut up: Vec<Vec<usize>> = vec![vec![]; vertex_node.len()];
+    let mut down: Vec<Vec<usize>> = vec![vec![]; vertex_node.len()];
+    for node in &topo_order {
+        for succ in &dag_succ[node] {
+            let mut from = vertex_of[node];
+            let to = vertex_of[succ];
+            let span = vertex_layer[from] + 1..vertex_layer[to];
+            for (l, layer) in layers.iter_mut().enumerate().take(span.end).skip(span.start) {
+                let dummy = vertex_node.len();
+                vertex_node.push(None);
+                vertex_layer.push(l);
+                layer.push(dummy);
+                up.push(vec![from]);
+                down.push(vec![]);
+                down[from].push(dummy);
+                from = dummy;
+            }
+            down[from].push(to);
+            up[to].push(from);
+        }
+    }
+
+    // Reduce crossings by sorting each layer by the average position of its
+    // neighbours in the previous one, sweeping back and forth.
+    let mut best = layers.clone();
+    let mut best_crossings = count_crossings(&layers, &down);
+    for sweep in 0..ORDERING_SWEEPS {
+        let downwards = sweep % 2 == 0;
+        let order: Vec<usize> = if downwards {
+            (1..layer_count).collect()
+        } else {
+            (0..layer_count.saturating_sub(1)).rev().collect()
+        };
+        for l in order {
+            let (fixed, neighbours) = if downwards { (l - 1, &up) } else { (l + 1, &down) };
+            let position: HashMap<usize, usize> = layers[fixed].iter().enumerate().map(|(i, v)| (*v, i)).collect();
+            let mut keyed: Vec<(f32, usize)> = layers[l]
+                .iter()
+                .enumerate()
+                .map(|(i, v)| {
+                    let adjacent = &neighbours[*v];
+                    let key = if adjacent.is_empty() {
+                        i as f32
+                    } else {
+                        adjacent.iter().map(|a| position[a] as f32).sum::<f32>() / adjacent.len() as f32
+                    };
+                    (key, *v)
+                })
+                .collect();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            layers[l] = keyed.into_iter().map(|(_, v)| v).collect();
+        }
+        let crossings = count_crossings(&layers, &down);
+        if crossings < best_crossings {
+            best_crossings = crossings;
+            best = layers.clone();
+        }
+    }
+    let layers = best;
+
+    // Coordinates: layers are columns as wide as their widest node, nodes are stacked
+    // in order, and moved towards their neighbours where there's room to do so.
+    let size = |v: usize| match vertex_node[v] {
+        Some(node) => sizes[node],
+        None => vec2(0.0, WIRE_HEIGHT * zoom),
+    };
+    let mut x = 0.0;
+    let mut layer_x = vec![];
+    for layer in &layers {
+        layer_x.push(x);
+        x += layer.iter().map(|v| size(*v).x).fold(0.0, f32::max) + LAYER_GAP * zoom;
+    }
+    let mut y = vec![0.0; vertex_node.len()];
+    let center = |y: &[f32], v: usize| y[v] + size(v).y / 2.0;
+    let place = |y: &mut [f32], layer: &[usize], desired: &dyn Fn(&[f32], usize) -> Option<f32>| {
+        let mut bottom: Option<f32> = None;
+        for v in layer {
+            let top = desired(y, *v).map(|c| c - size(*v).y / 2.0);
+            y[*v] = match (top, bottom) {
+                (Some(top), Some(bottom)) => top.max(bottom),
+                (Some(top), None) => top,
+                (None, Some(bottom)) => bottom,
+                (None, None) => 0.0,
+            };
+            bottom = Some(y[*v] + size(*v).y + NODE_GAP * zoom);
+        }
+    };
+    let average = |y: &[f32], adjacent: &[usize]| {
+        (!adjacent.is_empty()).then(|| adjacent.iter().map(|a| center(y, *a)).sum::<f32>() / adjacent.len() as f32)
+    };
+    for layer in &layers {
+        place(&mut y, layer, &|y, v| average(y, &up[v]));
+    }
+    for layer in layers.iter().rev().skip(1) {
+        place(&mut y, layer, &|y, v| {
+            if up[v].is_empty() { average(y, &down[v]) } else { Some(center(y, v)) }
+        });
+    }
+
+    let min_y = (0..vertex_node.len()).map(|v| y[v]).fold(f32::INFINITY, f32::min);
+    let max_y = (0..vertex_node.len()).map(|v| y[v] + size(v).y).fold(f32::NEG_INFINITY, f32::max);
+    let positions = (0..vertex_node.len())
+        .filter_map(|v| vertex_node[v].map(|node| (node, vec2(layer_x[vertex_layer[v]], y[v] - min_y))))
+        .collect();
+    (positions, max_y - min_y)
+}
+
+fn count_crossings(layers: &[Vec<usize>], down: &[Vec<usize>]) -> usize {
+    let mut crossings = 0;
+    for pair in layers.windows(2) {
+        let position: HashMap<usize, usize> = pair[1].iter().enumerate().map(|(i, v)| (*v, i)).collect();
+        let wires: Vec<(usize, usize)> = pair[0]
+            .iter()
+            .enumerate()
+            .flat_map(|(i, v)| down[*v].iter().map(move |d| (i, *d)))
+            .map(|(i, d)| (i, position[&d]))
+            .collect();
+        for (k, a) in wires.iter().enumerate() {
+            crossings += wires[k + 1..]
+                .iter()
+                .filter(|b| (a.0 < b.0 && a.1 > b.1) || (a.0 > b.0 && a.1 < b.1))
+                .count();
+        }
+    }
+    crossings
+}
@@ -1,4 +1,4 @@
-use eframe::egui;
+use egui_node_graph2::geometry::vec2;
 use egui_node_graph2::{InputParamKind, NodeId};
 use ron::{Value, value::{Map, Number, F32}};
 
@@ -43,7 +43,7 @@ pub fn verify_compat(full_state: &mut FullGraphState) {
     // make sure that it is populated with every existing node.
     if full_state.state.node_sizes.is_empty() {
         for node in full_state.state.graph.nodes.iter() {
-            full_state.state.node_sizes.insert(node.0, egui::vec2(200.0, 200.0));
+            full_state.state.node_sizes.insert(node.0, vec2(200.0, 200.0));
         }
     }
     let mut sound_event_nodes = vec![];
@@ -0,0 +1,288 @@
+// Crash recovery. Open graphs with unsaved changes are periodically written to a directory of the
+// running editor, which is removed when it exits normally. Directories left behind are from
+// sessions that crashed or were killed, and are offered for restoring on the next start. Every
+// running editor keeps refreshing a lock file in its directory, so other editors leave it alone.
+use std::cell::{Cell, RefCell};
+use std::fs;
+use std::panic::{self, AssertUnwindSafe, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use rfd::MessageDialog;
+use super::FullGraphState;
+
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+const RECOVERY_DIR: &str = "recovery";
+const LOCK_FILE: &str = "session.lock";
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+// A lock that hasn't been refreshed for this long was left by an editor that isn't running anymore.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub(super) struct Autosaver {
+    dir: PathBuf,
+    last_save: Instant,
+    last_heartbeat: Instant,
+    /// Set when something changed since the last autosave.
+    pub pending: bool,
+}
+
+impl Default for Autosaver {
+    fn default() -> Self {
+        let started = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        Self {
+            dir: Path::new(RECOVERY_DIR).join(format!("session-{started}-{}", std::process::id())),
+            last_save: Instant::now(),
+            last_heartbeat: Instant::now(),
+            pending: false,
+        }
+    }
+}
+
+impl Autosaver {
+    /// Time left until the next autosave, `None` if there's nothing to save.
+    pub fn time_left(&self) -> Option<Duration> {
+        self.pending.then(|| AUTOSAVE_INTERVAL.saturating_sub(self.last_save.elapsed()))
+    }
+
+    /// Replaces the recovery files of this session with the given graphs.
+    pub fn save(&self, graphs: &[&FullGraphState]) -> anyhow::Result<()> {
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)?;
+        }
+        if graphs.is_empty() {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)?;
+        for (idx, graph) in graphs.iter().enumerate() {
+            graph.save_graph(&self.dir.join(format!("graph-{idx}.ron")))?;
+        }
+        write_lock(&self.dir)?;
+        Ok(())
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.pending = false;
+        self.last_save = Instant::now();
+    }
+
+    /// Waits for another interval before trying to save again, the changes stay pending.
+    pub fn retry_later(&mut self) {
+        self.last_save = Instant::now();
+    }
+
+    /// Refreshes the lock of the recovery files when it's due. Returns the time until the next
+    /// refresh, `None` if there are no recovery files to lock.
+    pub fn heartbeat(&mut self) -> Option<Duration> {
+        if !self.dir.exists() {
+            return None;
+        }
+        if self.last_heartbeat.elapsed() >= HEARTBEAT_INTERVAL {
+            self.last_heartbeat = Instant::now();
+            // a failed refresh is retried on the next one, at worst another editor offers the files early
+            let _ = write_lock(&self.dir);
+        }
+        Some(HEARTBEAT_INTERVAL.saturating_sub(self.last_heartbeat.elapsed()))
+    }
+
+    /// Leaves the recovery files for the next start, called when going down after a crash.
+    pub fn release(&self) {
+        let _ = fs::remove_file(self.dir.join(LOCK_FILE));
+    }
+
+    /// Removes the recovery files, called on a clean exit.
+    pub fn clear(&self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+pub(super) struct RecoveredGraph {
+    pub state: FullGraphState,
+    pub saved_at: SystemTime,
+    /// Node and connection counts of the graph's file on disk, if it has one.
+    saved_counts: Option<(usize, usize)>,
+}
+
+impl RecoveredGraph {
+    pub fn name(&self) -> String {
+        self.state.user_state.save_file_path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "<UNSAVED>".to_string())
+    }
+
+    /// What the recovered graph has compared to its file, e.g. "12 nodes (+3), 9 connections (-1)".
+    pub fn summary(&self) -> String {
+        let (nodes, connections) = graph_counts(&self.state);
+        match self.saved_counts {
+            Some((saved_nodes, saved_connections)) => format!(
+                "{nodes} nodes ({:+}), {connections} connections ({:+}) compared to the saved file",
+                nodes as i64 - saved_nodes as i64,
+                connections as i64 - saved_connections as i64,
+            ),
+            None => format!("{nodes} nodes, {connections} connections, never saved"),
+        }
+    }
+}
+
+pub(super) struct RecoveredSession {
+    dir: PathBuf,
+    pub graphs: Vec<RecoveredGraph>,
+}
+
+impl RecoveredSession {
+    pub fn discard(&self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn write_lock(dir: &Path) -> std::io::Result<()> {
+    fs::write(dir.join(LOCK_FILE), std::process::id().to_string())
+}
+
+// Whether the editor that owns the directory is still refreshing its lock.
+fn is_running(dir: &Path) -> bool {
+    let Ok(modified) = fs::metadata(dir.join(LOCK_FILE)).and_then(|meta| meta.modified()) else {
+        return false;
+    };
+    match modified.elapsed() {
+        Ok(age) => age < LOCK_TIMEOUT,
+        // refreshed "in the future", the clock was changed
+        Err(_) => true,
+    }
+}
+
+fn graph_counts(state: &FullGraphState) -> (usize, usize) {
+    let graph = &state.state.graph;
+    (graph.nodes.len(), graph.iter_connections().count())
+}
+
+fn read_graph(path: &Path) -> anyhow::Result<FullGraphState> {
+    let contents = fs::read_to_string(path)?;
+    Ok(ron::from_str(&contents)?)
+}
+
+/// Recovery files left behind by sessions that aren't running anymore, oldest first.
+pub(super) fn find_sessions(own_dir: &Path) -> Vec<RecoveredSession> {
+    let Ok(entries) = fs::read_dir(RECOVERY_DIR) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && path != own_dir && !is_running(path))
+        .collect();
+    dirs.sort();
+    let mut sessions = Vec::new();
+    for dir in dirs {
+        let mut files: Vec<PathBuf> = fs::read_dir(&dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+            .collect();
+        files.sort();
+        let graphs: Vec<RecoveredGraph> = files
+            .iter()
+            .filter_map(|path| {
+                let state = read_graph(path).ok()?;
+                let saved_at = fs::metadata(path).and_then(|meta| meta.modified()).unwrap_or(UNIX_EPOCH);
+                let saved_counts = state.user_state.save_file_path
+                    .as_deref()
+                    .and_then(|saved_path| read_graph(saved_path).ok())
+                    .map(|saved| graph_counts(&saved));
+                Some(RecoveredGraph { state, saved_at, saved_counts })
+            })
+            .collect();
+        let session = RecoveredSession { dir, graphs };
+        if session.graphs.is_empty() {
+            // nothing usable in there
+            session.discard();
+        } else {
+            sessions.push(session);
+        }
+    }
+    sessions
+}
+
+// Panics while the editor runs a frame are reported once the open graphs are saved for recovery,
+// so the report can tell whether that worked. Panics anywhere else are reported right away.
+thread_local! {
+    static IN_EDITOR: Cell<bool> = const { Cell::new(false) };
+    static PENDING_REPORT: RefCell<Option<PanicReport>> = const { RefCell::new(None) };
+}
+
+struct PanicReport {
+    info: String,
+    payload: String,
+}
+
+impl PanicReport {
+    fn new(panic_info: &PanicHookInfo<'_>) -> Self {
+        let payload = panic_info.payload();
+        let payload = payload.downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "<Panic payload unavailable>".to_string());
+        Self { info: format!("{panic_info:#?}"), payload }
+    }
+
+    fn show(&self, recovery_note: &str) {
+        let res = MessageDialog::new()
+            .set_level(rfd::MessageLevel::Error)
+            .set_title("Whoops!")
+            .set_description(format!(
+                "The editor has crashed due to an unhandled error. {recovery_note}\n\n{}\n\n{}",
+                self.info,
+                self.payload,
+            ))
+            .set_buttons(rfd::MessageButtons::OkCustom("Copy log".to_string()))
+            .show();
+
+        if let rfd::MessageDialogResult::Custom(_) = res {
+            if let Ok(mut clipboard) = arboard::Clipboard::new() {
+                let _ = clipboard.set_text(format!("Panic info:\n{}\n\nPanic payload:\n{}", self.info, self.payload));
+            }
+        }
+    }
+}
+
+/// Shows a dialog with the details of a panic, which can be copied from it.
+pub fn setup_panic_hook() {
+    panic::set_hook(Box::new(|panic_info| {
+        let report = PanicReport::new(panic_info);
+        if IN_EDITOR.get() {
+            // the first panic is the one worth reporting, not one from the last-chance save
+            PENDING_REPORT.with_borrow_mut(|pending| {
+                pending.get_or_insert(report);
+            });
+        } else {
+            report.show("Open graphs with unsaved changes will be offered for recovery the next time it starts.");
+        }
+    }));
+}
+
+/// Runs part of the editor, holding back the report of a panic until `report_panic`.
+pub(super) fn run_guarded<R>(f: impl FnOnce() -> R) -> std::thread::Result<R> {
+    IN_EDITOR.set(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    IN_EDITOR.set(false);
+    result
+}
+
+/// Reports the panic held back by `run_guarded`, along with how saving the graphs went.
+pub(super) fn report_panic(saved: std::thread::Result<anyhow::Result<()>>) {
+    let Some(report) = PENDING_REPORT.take() else {
+        return;
+    };
+    let note = match saved {
+        Ok(Ok(())) => "Open graphs with unsaved changes will be offered for recovery the next time it starts.".to_string(),
+        Ok(Err(e)) => format!("Saving the open graphs for recovery failed: {e:#}"),
+        Err(_) => "Saving the open graphs for recovery failed as well.".to_string(),
+    };
+    report.show(&note);
+}
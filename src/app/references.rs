@@ -0,0 +1,246 @@
+// Variables and public outputs are referred to by name from the nodes using them,
+// renaming or deleting one has to go through all of those nodes. Names are kept unique when items
+// are added or renamed, but older graphs can have several items with the same name, whose users
+// can't be told apart.
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use egui_node_graph2::*;
+use crate::typing::{PublicOutputIndex, VariableIndex};
+use super::types::*;
+use super::FullGraphState;
+
+/// Something defined by the graph that nodes refer to by name.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum NamedItem {
+    Variable,
+    PublicOutput,
+}
+
+impl NamedItem {
+    pub fn describe(self) -> &'static str {
+        match self {
+            NamedItem::Variable => "variable",
+            NamedItem::PublicOutput => "output",
+        }
+    }
+
+    fn referenced_name(self, value: &PulseGraphValueType) -> Option<&String> {
+        match (self, value) {
+            (NamedItem::Variable, PulseGraphValueType::InternalVariableName { value, .. })
+            | (NamedItem::PublicOutput, PulseGraphValueType::InternalOutputName { value, .. }) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn referenced_name_mut(self, value: &mut PulseGraphValueType) -> Option<&mut String> {
+        match (self, value) {
+            (NamedItem::Variable, PulseGraphValueType::InternalVariableName { value, .. })
+            | (NamedItem::PublicOutput, PulseGraphValueType::InternalOutputName { value, .. }) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl FullGraphState {
+    pub(super) fn item_names(&self, item: NamedItem) -> Vec<String> {
+        match item {
+            NamedItem::Variable => self.user_state.variables.iter().map(|var| var.name.clone()).collect(),
+            NamedItem::PublicOutput => self.user_state.public_outputs.iter().map(|out| out.name.clone()).collect(),
+        }
+    }
+
+    fn item_name_mut(&mut self, item: NamedItem, index: usize) -> Option<&mut String> {
+        match item {
+            NamedItem::Variable => self.user_state.variables.get_mut(index).map(|var| &mut var.name),
+            NamedItem::PublicOutput => self.user_state.public_outputs.get_mut(index).map(|out| &mut out.name),
+        }
+    }
+
+    /// A name no other item of the kind has, `base` with a number after it if it's taken.
+    pub(super) fn unique_item_name(&self, item: NamedItem, base: &str) -> String {
+        let names = self.item_names(item);
+        std::iter::once(base.to_string())
+            .chain((2..).map(|n| format!("{base}_{n}")))
+            .find(|name| !names.contains(name))
+            .unwrap_or_default()
+    }
+
+    // Whether another item of the kind has the name of the one at `index`, nodes using that
+    // name keep referring to the other item when this one is renamed or deleted.
+    fn name_is_shared(&self, item: NamedItem, index: usize) -> bool {
+        let names = self.item_names(item);
+        names.get(index).is_some_and(|name| {
+            names.iter().enumerate().any(|(idx, other)| idx != index && other == name)
+        })
+    }
+
+    /// Nodes referring to the item at `index`, in drawing order. None when another item has
+    /// the same name, since the nodes are left to that one.
+    pub(super) fn item_users(&self, item: NamedItem, index: usize) -> Vec<NodeId> {
+        match self.item_names(item).get(index) {
+            Some(name) if !self.name_is_shared(item, index) => self.name_users(item, name),
+            _ => Vec::new(),
+        }
+    }
+
+    // Nodes referring to an item by this name, in drawing order.
+    fn name_users(&self, item: NamedItem, name: &str) -> Vec<NodeId> {
+        let graph = &self.state.graph;
+        self.state
+            .node_order
+            .iter()
+            .copied()
+            .filter(|node_id| {
+                graph[*node_id].input_ids().any(|input_id| {
+                    item.referenced_name(&graph.get_input(input_id).value)
+                        .is_some_and(|referenced| referenced == name)
+                })
+            })
+            .collect()
+    }
+
+    // points every reference to `from` at `to`, returns the nodes that changed.
+    fn replace_references(&mut self, item: NamedItem, from: &str, to: &str) -> Vec<NodeId> {
+        let users = self.name_users(item, from);
+        for node_id in users.iter() {
+            let input_ids: Vec<InputId> = self.state.graph[*node_id].input_ids().collect();
+            for input_id in input_ids {
+                if let Some(name) = item.referenced_name_mut(&mut self.state.graph.get_input_mut(input_id).value) {
+                    if name == from {
+                        *name = to.to_string();
+                    }
+                }
+            }
+        }
+        users
+    }
+
+    /// Renames a variable or public output along with every node referring to it.
+    /// Returns how many nodes were updated.
+    pub(super) fn rename_item(&mut self, item: NamedItem, index: usize, new_name: &str) -> anyhow::Result<usize> {
+        let new_name = new_name.trim();
+        if new_name.is_empty() {
+            return Err(anyhow!("The {} name can't be empty", item.describe()));
+        }
+        let names = self.item_names(item);
+        let old_name = names.get(index)
+            .ok_or_else(|| anyhow!("No {} at index {index}", item.describe()))?
+            .clone();
+        if names.iter().enumerate().any(|(idx, name)| idx != index && name == new_name) {
+            return Err(anyhow!("There's already a {} named '{new_name}'", item.describe()));
+        }
+        let shared = self.name_is_shared(item, index);
+        if let Some(name) = self.item_name_mut(item, index) {
+            *name = new_name.to_string();
+        }
+        if shared {
+            return Ok(0);
+        }
+        Ok(self.replace_references(item, &old_name, new_name).len())
+    }
+
+    /// Points the nodes using an item at another one of the same kind, retyping their ports to match it.
+    pub(super) fn retarget_item(&mut self, item: NamedItem, from: usize, to: usize) -> Vec<NodeId> {
+        let names = self.item_names(item);
+        let (Some(from_name), Some(to_name)) = (names.get(from), names.get(to)) else {
+            return Vec::new();
+        };
+        if self.name_is_shared(item, from) {
+            return Vec::new();
+        }
+        let users = self.replace_references(item, from_name, to_name);
+        for node_id in users.iter() {
+            match item {
+                NamedItem::Variable => self.update_node_variable_types(*node_id, VariableIndex(to)),
+                NamedItem::PublicOutput => self.update_node_public_output_types(*node_id, PublicOutputIndex(to)),
+            }
+        }
+        users
+    }
+
+    pub(super) fn remove_item(&mut self, item: NamedItem, index: usize) {
+        match item {
+            NamedItem::Variable => {
+                self.user_state.variables.remove(index);
+            }
+            NamedItem::PublicOutput => {
+                self.user_state.public_outputs.remove(index);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui_node_graph2::geometry::pos2;
+    use crate::pulsetypes::PulseVariable;
+    use crate::typing::PulseValueType;
+
+    fn add_variable(state: &mut FullGraphState, name: &str) {
+        state.user_state.variables.push(PulseVariable {
+            name: name.to_string(),
+            data_type: PulseDataType::default(),
+            stored_value: PulseGraphValueType::default(),
+            typ_and_default_value: PulseValueType::PVAL_INVALID,
+            default_value_buffer: String::default(),
+        });
+    }
+
+    fn add_load_variable(state: &mut FullGraphState, name: &str) -> NodeId {
+        let node_id = state.add_node(PulseNodeTemplate::GetVar, pos2(0.0, 0.0));
+        let input_id = state.state.graph[node_id].get_input("variableName").unwrap();
+        state.state.graph.get_input_mut(input_id).value = PulseGraphValueType::InternalVariableName {
+            prevvalue: String::new(),
+            value: name.to_string(),
+        };
+        node_id
+    }
+
+    fn referenced_variable(state: &FullGraphState, node_id: NodeId) -> String {
+        let input_id = state.state.graph[node_id].get_input("variableName").unwrap();
+        NamedItem::Variable.referenced_name(&state.state.graph.get_input(input_id).value).cloned().unwrap()
+    }
+
+    #[test]
+    fn unique_names() {
+        let mut state = FullGraphState::default();
+        assert_eq!(state.unique_item_name(NamedItem::Variable, "variable"), "variable");
+        add_variable(&mut state, "variable");
+        add_variable(&mut state, "variable_2");
+        assert_eq!(state.unique_item_name(NamedItem::Variable, "variable"), "variable_3");
+    }
+
+    #[test]
+    fn rename_with_duplicate_names() {
+        let mut state = FullGraphState::default();
+        add_variable(&mut state, "variable");
+        add_variable(&mut state, "variable");
+        let node_id = add_load_variable(&mut state, "variable");
+
+        // the node can't be told apart from a user of the other variable, so it's left to that one
+        assert_eq!(state.rename_item(NamedItem::Variable, 1, "counter").unwrap(), 0);
+        assert_eq!(referenced_variable(&state, node_id), "variable");
+        assert_eq!(state.item_names(NamedItem::Variable), ["variable", "counter"]);
+
+        // now the name is unique again
+        assert_eq!(state.rename_item(NamedItem::Variable, 0, "total").unwrap(), 1);
+        assert_eq!(referenced_variable(&state, node_id), "total");
+        assert!(state.rename_item(NamedItem::Variable, 0, "counter").is_err());
+    }
+
+    #[test]
+    fn delete_with_duplicate_names() {
+        let mut state = FullGraphState::default();
+        add_variable(&mut state, "variable");
+        add_variable(&mut state, "variable");
+        let node_id = add_load_variable(&mut state, "variable");
+
+        assert!(state.item_users(NamedItem::Variable, 0).is_empty());
+        assert!(state.item_users(NamedItem::Variable, 1).is_empty());
+        assert!(state.retarget_item(NamedItem::Variable, 1, 0).is_empty());
+        state.remove_item(NamedItem::Variable, 1);
+        assert_eq!(state.item_users(NamedItem::Variable, 0), [node_id]);
+        assert_eq!(referenced_variable(&state, node_id), "variable");
+    }
+}
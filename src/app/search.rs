@@ -0,0 +1,204 @@
+// Graph-wide search, looking through node labels, bindings, variable names and constants.
+use egui_node_graph2::*;
+use eframe::egui;
+use regex::{Regex, RegexBuilder};
+use crate::bindings::GraphBindings;
+use super::types::*;
+
+/// One node matching every term of the query.
+pub(super) struct SearchHit {
+    pub node_id: NodeId,
+    pub label: String,
+    /// The texts that matched, for showing why the node is listed.
+    pub matched: Vec<String>,
+}
+
+#[derive(Default)]
+pub(super) struct GraphSearch {
+    pub open: bool,
+    query: String,
+    use_regex: bool,
+    terms: Vec<Regex>,
+    error: Option<String>,
+    hits: Vec<SearchHit>,
+    current: Option<usize>,
+    focus_query: bool,
+    // revision of the graph the hits were found in
+    searched_revision: Option<u64>,
+}
+
+impl GraphSearch {
+    pub fn open(&mut self) {
+        self.open = true;
+        self.focus_query = true;
+    }
+
+    // Every whitespace separated term must match some text of the node, so
+    // "Kill door_" finds the nodes firing Kill on doors. In regex mode each term is a regex.
+    fn compile(&mut self) {
+        self.error = None;
+        self.terms = self
+            .query
+            .split_whitespace()
+            .map(|term| {
+                let pattern = if self.use_regex { term.to_string() } else { regex::escape(term) };
+                RegexBuilder::new(&pattern).case_insensitive(true).build()
+            })
+            .collect::<Result<_, _>>()
+            .unwrap_or_else(|e| {
+                self.error = Some(e.to_string());
+                Vec::new()
+            });
+    }
+
+    fn update_hits(&mut self, graph: &PulseGraph, bindings: &GraphBindings, node_order: &[NodeId]) {
+        let previous = self.current.and_then(|idx| self.hits.get(idx)).map(|hit| hit.node_id);
+        self.hits = if self.terms.is_empty() {
+            Vec::new()
+        } else {
+            // listed in drawing order, which keeps the order stable while editing.
+            node_order
+                .iter()
+                .filter_map(|node_id| {
+                    let texts = searchable_texts(graph, bindings, *node_id);
+                    let matched: Vec<String> = self
+                        .terms
+                        .iter()
+                        .map(|term| texts.iter().find(|text| term.is_match(text)).cloned())
+                        .collect::<Option<_>>()?;
+                    Some(SearchHit {
+                        node_id: *node_id,
+                        label: graph[*node_id].label.clone(),
+                        matched,
+                    })
+                })
+                .collect()
+        };
+        self.current = previous.and_then(|node_id| self.hits.iter().position(|hit| hit.node_id == node_id));
+    }
+
+    fn step(&mut self, forward: bool) -> Option<NodeId> {
+        if self.hits.is_empty() {
+            return None;
+        }
+        let count = self.hits.len();
+        let next = match (self.current, forward) {
+            (None, true) => 0,
+            (None, false) => count - 1,
+            (Some(idx), true) => (idx + 1) % count,
+            (Some(idx), false) => (idx + count - 1) % count,
+        };
+        self.current = Some(next);
+        Some(self.hits[next].node_id)
+    }
+
+    /// Shows the search window, returns the node to bring into view if one was picked.
+    /// The hits are only searched for again when the query or `revision` of the graph changes.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        graph: &PulseGraph,
+        bindings: &GraphBindings,
+        node_order: &[NodeId],
+        revision: u64,
+    ) -> Option<NodeId> {
+        if !self.open {
+            return None;
+        }
+        let mut focus = None;
+        let mut is_open = true;
+        egui::Window::new("Search")
+            .open(&mut is_open)
+            .default_size([320.0, 400.0])
+            .show(ctx, |ui| {
+                let mut changed = false;
+                ui.horizontal(|ui| {
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.query)
+                            .hint_text("label, function, variable, entity name...")
+                            .desired_width(200.0),
+                    ).on_hover_text("Every term separated by spaces must match the node. Enter goes to the next result, Shift+Enter to the previous one.");
+                    if self.focus_query {
+                        response.request_focus();
+                        self.focus_query = false;
+                    }
+                    changed |= response.changed();
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        focus = self.step(!ui.input(|i| i.modifiers.shift));
+                        response.request_focus();
+                    }
+                    changed |= ui.checkbox(&mut self.use_regex, ".*")
+                        .on_hover_text("Use regular expressions")
+                        .changed();
+                });
+                if changed {
+                    self.compile();
+                }
+                if changed || self.searched_revision != Some(revision) {
+                    self.update_hits(graph, bindings, node_order);
+                    self.searched_revision = Some(revision);
+                }
+                if let Some(error) = &self.error {
+                    ui.colored_label(egui::Color32::LIGHT_RED, error);
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Previous").clicked() {
+                        focus = self.step(false);
+                    }
+                    if ui.button("Next").clicked() {
+                        focus = self.step(true);
+                    }
+                    match self.current {
+                        Some(idx) => ui.label(format!("{} of {}", idx + 1, self.hits.len())),
+                        None => ui.label(format!("{} results", self.hits.len())),
+                    };
+                });
+                ui.separator();
+                egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                    for (idx, hit) in self.hits.iter().enumerate() {
+                        let response = ui.selectable_label(self.current == Some(idx), &hit.label)
+                            .on_hover_text(hit.matched.join("\n"));
+                        ui.label(egui::RichText::new(hit.matched.join(", ")).weak().small());
+                        if response.clicked() {
+                            self.current = Some(idx);
+                            focus = Some(hit.node_id);
+                        }
+                    }
+                });
+            });
+        self.open = is_open;
+        focus
+    }
+}
+
+// Everything a node can be found by.
+fn searchable_texts(graph: &PulseGraph, bindings: &GraphBindings, node_id: NodeId) -> Vec<String> {
+    let node = &graph[node_id];
+    let mut texts = vec![node.label.clone(), format!("{:?}", node.user_data.template)];
+    if let PulseNodeTemplate::LibraryBindingAssigned { binding } = node.user_data.template {
+        if let Some(binding) = bindings.find_function_by_id(binding) {
+            texts.push(binding.libname.clone());
+        }
+    }
+    for input_id in node.input_ids() {
+        match &graph.get_input(input_id).value {
+            PulseGraphValueType::String { value }
+            | PulseGraphValueType::EntityName { value }
+            | PulseGraphValueType::SoundEventName { value }
+            | PulseGraphValueType::InternalOutputName { value, .. }
+            | PulseGraphValueType::InternalVariableName { value, .. }
+            | PulseGraphValueType::CommentBox { value } => texts.push(value.clone()),
+            PulseGraphValueType::LibraryBindingChoice { value } => {
+                texts.extend(bindings.find_function_by_id(*value).map(|binding| binding.libname.clone()));
+            }
+            PulseGraphValueType::EventBindingChoice { value } => {
+                texts.extend(bindings.find_event_by_id(*value).map(|binding| binding.libname.clone()));
+            }
+            PulseGraphValueType::HookBindingChoice { value } => {
+                texts.extend(bindings.find_hook_by_id(*value).map(|binding| binding.libname.clone()));
+            }
+            _ => {}
+        }
+    }
+    texts
+}
@@ -1,5 +1,5 @@
 use std::marker::PhantomData;
-use std::{path::PathBuf, borrow::Cow};
+use std::{path::PathBuf, borrow::Cow, sync::Arc};
 use serde::{Deserialize, Serialize};
 use slotmap::SecondaryMap;
 use egui_node_graph2::*;
@@ -11,7 +11,7 @@ use crate::bindings::{GraphBindings, FunctionBinding, EventBinding};
 /// store additional information that doesn't live in parameters. For this
 /// example, the node data stores the template (i.e. the "type") of the node.
 #[derive(Default, Clone, PartialEq)]
-#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct PulseNodeData {
     pub template: PulseNodeTemplate,
     #[serde(skip)]
@@ -77,7 +77,7 @@ pub enum PulseDataType {
 ///
 /// Empty enums mean that we do not support inputting, or storing a default value for that type.
 #[derive(Clone, Debug, PartialEq)]
-#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+#[derive(Serialize, Deserialize)]
 pub enum PulseGraphValueType {
     Vec2 {
         value: Vec2,
@@ -177,7 +177,7 @@ pub enum PulseGraphValueType {
 /// will display in the "new node" popup. The user code needs to tell the
 /// library how to convert a NodeTemplate into a Node.
 #[derive(Default, Clone, Copy, PartialEq, Debug)]
-#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+#[derive(Serialize, Deserialize)]
 pub enum PulseNodeTemplate {
     CellPublicMethod,
     EntFire,
@@ -231,6 +231,7 @@ pub enum PulseNodeTemplate {
     RandomInt,
     RandomFloat,
     EntOutputHandler,
+    Reroute,
 }
 
 /// The response type is used to encode side-effects produced when drawing a
@@ -252,13 +253,34 @@ pub enum PulseGraphResponse {
     ChangeFunctionBinding(NodeId, FunctionBinding),
     ChangeRemoteNodeId(NodeId, NodeId),
     UpdatePolymorphicTypes(NodeId),
+    RenameExposedNode(NodeId, String),
+}
+
+impl PulseGraphResponse {
+    /// The node the response changes, if it's about a single one.
+    pub fn node_id(&self) -> Option<NodeId> {
+        match self {
+            PulseGraphResponse::AddOutputParam(node_id, ..)
+            | PulseGraphResponse::AddCustomInputParam(node_id, ..)
+            | PulseGraphResponse::RemoveCustomInputParam(node_id, _)
+            | PulseGraphResponse::RemoveOutputParam(node_id, _)
+            | PulseGraphResponse::ChangeParamType(node_id, ..)
+            | PulseGraphResponse::ChangeEventBinding(node_id, _)
+            | PulseGraphResponse::ChangeFunctionBinding(node_id, _)
+            | PulseGraphResponse::ChangeRemoteNodeId(node_id, _)
+            | PulseGraphResponse::UpdatePolymorphicTypes(node_id)
+            | PulseGraphResponse::RenameExposedNode(node_id, _) => Some(*node_id),
+            PulseGraphResponse::ChangeOutputParamType(node_id, _)
+            | PulseGraphResponse::ChangeVariableParamType(node_id, _) => *node_id,
+        }
+    }
 }
 
 /// The graph 'global' state. This state struct is passed around to the node and
 /// parameter drawing callbacks. The contents of this struct are entirely up to
 /// the user. For this example, we use it to keep track of the 'active' node.
 #[derive(Clone, PartialEq)]
-#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+#[derive(Serialize, Deserialize)]
 pub struct PulseGraphState {
     pub public_outputs: Vec<OutputDefinition>,
     pub variables: Vec<PulseVariable>,
@@ -266,12 +288,13 @@ pub struct PulseGraphState {
     pub outputs_dropdown_choices: Vec<PulseValueType>,
 
     pub save_file_path: Option<PathBuf>,
-    #[cfg_attr(feature = "persistence", serde(skip))]
-    pub bindings: GraphBindings,
+    // shared, so that snapshots of the state (e.g. for background compilation) don't need a deep copy.
+    #[serde(skip)]
+    pub bindings: Arc<GraphBindings>,
 
-    #[cfg_attr(feature = "persistence", serde(default))]
+    #[serde(default)]
     pub graph_domain: String,
-    #[cfg_attr(feature = "persistence", serde(default))]
+    #[serde(default)]
     pub graph_subtype: String,
 }
 
@@ -283,7 +306,7 @@ impl Default for PulseGraphState {
             exposed_nodes: SecondaryMap::new(),
             outputs_dropdown_choices: vec![],
             save_file_path: None,
-            bindings: GraphBindings::default(),
+            bindings: Arc::default(),
             graph_domain: "ServerEntity".to_string(),
             graph_subtype: "PVAL_EHANDLE:point_pulse".to_string(),
         }
@@ -295,7 +318,7 @@ pub struct AllMyNodeTemplates {
 }
 
 #[cfg(feature = "nongame_asset_build")]
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 pub struct EditorConfig {
     pub python_interpreter: String,
     pub assetassembler_path: PathBuf,
@@ -303,13 +326,13 @@ pub struct EditorConfig {
 }
 
 #[derive(Default, Clone, Copy, PartialEq, Eq)]
-#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
-#[cfg_attr(feature = "persistence", serde(tag = "version"))]
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "version")]
 pub enum FileVersion {
     #[default]
-    #[cfg_attr(feature = "persistence", serde(rename = "v1"))]
+    #[serde(rename = "v1")]
     V1,
-    #[cfg_attr(feature = "persistence", serde(rename = "v2"))]
+    #[serde(rename = "v2")]
     V2,
 }
 
@@ -0,0 +1,17 @@
+#![forbid(unsafe_code)]
+#![cfg_attr(not(debug_assertions), deny(warnings))] // Forbid warnings in release builds
+#![warn(clippy::all, rust_2018_idioms)]
+
+// `pulseedit-watch <graph.ron>...`, the `--watch` mode of the editor as a console program.
+// Release builds of the editor are windowed programs on Windows, which have no console to print to.
+fn main() {
+    let paths: Vec<std::path::PathBuf> = std::env::args_os()
+        .skip(1)
+        .map(|arg| std::path::absolute(&arg).unwrap_or_else(|_| arg.into()))
+        .collect();
+    libpulseedit::bindings::enter_install_dir();
+    if let Err(e) = libpulseedit::run_watch_cli(&paths) {
+        eprintln!("{e:#}");
+        std::process::exit(1);
+    }
+}
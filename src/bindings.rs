@@ -4,14 +4,14 @@ use crate::typing::{EnumBindingIndex, EnumBindingValueIndex, EventBindingIndex,
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
-#[cfg_attr(feature = "persistence", derive(Serialize))]
+#[derive(Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum LibraryBindingType {
     Action,
     Value,
 }
 #[derive(Deserialize, Debug, Clone, PartialEq)]
-#[cfg_attr(feature = "persistence", derive(Serialize))]
+#[derive(Serialize)]
 pub enum PolimorphicTypeInfo {
     TypeParam(String),
     FullType(String),
@@ -19,7 +19,7 @@ pub enum PolimorphicTypeInfo {
 }
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
-#[cfg_attr(feature = "persistence", derive(Serialize))]
+#[derive(Serialize)]
 pub struct ParamInfo {
     pub name: String,
     #[serde(rename = "type")]
@@ -187,6 +187,23 @@ fn load_game_bindings(enum_bindings: &EnumBindings, filepath: &std::path::Path)
     Ok(bindings)
 }
 
+// Relative to the working directory, bindings are shipped next to the executable.
+pub const BINDINGS_MANIFEST_PATH: &str = "bindings/bindings_manifest.json";
+
+/// Switches the working directory to the one of the executable, if the bindings are shipped next to it.
+/// The bindings, config and other files of the editor are found relative to the working directory, which
+/// can be anywhere when a graph is opened through a file association. Make paths given on the command
+/// line absolute before calling this.
+pub fn enter_install_dir() {
+    let Some(dir) = std::env::current_exe().ok().and_then(|exe| exe.parent().map(std::path::Path::to_path_buf)) else {
+        return;
+    };
+    // development builds are run from the repository instead
+    if dir.join(BINDINGS_MANIFEST_PATH).is_file() {
+        let _ = std::env::set_current_dir(dir);
+    }
+}
+
 pub fn load_bindings(filepath: &std::path::Path) -> anyhow::Result<GraphBindings> {
     let json = std::fs::read_to_string(filepath)
         .map_err(|e| {
@@ -0,0 +1,268 @@
+//! Building and compiling graphs from code, without opening the editor.
+//!
+//! Nodes are created from [`PulseNodeTemplate`]s or game functions looked up by their library name,
+//! and connected by the names of their ports, the same names that are shown in the editor.
+//! The result can be compiled to text KV3 in memory, or saved as a `.ron` file that the editor can open.
+//!
+//! ```no_run
+//! use libpulseedit::builder::GraphBuilder;
+//! use libpulseedit::types::{PulseGraphValueType, PulseNodeTemplate};
+//! use libpulseedit::typing::PulseValueType;
+//!
+//! let mut builder = GraphBuilder::from_bindings_file("bindings/bindings_manifest.json".as_ref())?;
+//! builder.add_variable("counter", PulseValueType::PVAL_INT(None));
+//!
+//! let method = builder.add_node(PulseNodeTemplate::CellPublicMethod);
+//! builder.set_input_value(method, "name", PulseGraphValueType::String { value: "Run".into() })?;
+//! let log = builder.add_node(PulseNodeTemplate::DebugLog);
+//! builder.set_input_value(log, "pMessage", PulseGraphValueType::String { value: "Hello".into() })?;
+//! builder.connect(method, "outAction", log, "ActionIn")?;
+//!
+//! let kv3_text = builder.compile_to_string()?;
+//! println!("{kv3_text}");
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+use std::path::Path;
+use std::sync::Arc;
+use anyhow::{anyhow, bail};
+use egui_node_graph2::geometry::pos2;
+use egui_node_graph2::{DataTypeTrait, InputParamKind, Node};
+use crate::app::FullGraphState;
+use crate::app::types::{
+    pulse_value_type_to_node_types, PulseDataType, PulseGraphState, PulseGraphValueType, PulseNodeData, PulseNodeTemplate,
+};
+use crate::bindings::{load_bindings, GraphBindings};
+use crate::compiler::{compile_graph_to_string, CompileError};
+use crate::pulsetypes::{OutputDefinition, PulseVariable};
+use crate::typing::{EventBindingIndex, LibraryBindingIndex, PulseValueType};
+
+pub use egui_node_graph2::NodeId;
+
+// horizontal spacing of nodes placed by the builder, they're laid out in a single row.
+const NODE_SPACING: f32 = 250.0;
+
+/// Creates a graph step by step, see the [module documentation](self) for an example.
+pub struct GraphBuilder {
+    state: FullGraphState,
+}
+
+impl GraphBuilder {
+    /// Creates an empty graph using the given bindings to resolve game functions and events.
+    pub fn new(bindings: GraphBindings) -> Self {
+        let mut state = FullGraphState::default();
+        state.user_state.bindings = Arc::new(bindings);
+        Self { state }
+    }
+
+    /// Creates an empty graph, loading bindings from a manifest file like `bindings/bindings_manifest.json`.
+    pub fn from_bindings_file(manifest_path: &Path) -> anyhow::Result<Self> {
+        Ok(Self::new(load_bindings(manifest_path)?))
+    }
+
+    /// Loads an existing graph saved by the editor, to be modified further.
+    pub fn load(path: &Path, bindings: GraphBindings) -> anyhow::Result<Self> {
+        let mut builder = Self::new(bindings);
+        builder.state.load_state(&path.to_path_buf())?;
+        Ok(builder)
+    }
+
+    pub fn set_graph_domain(&mut self, domain: &str) -> &mut Self {
+        self.state.user_state.graph_domain = domain.to_string();
+        self
+    }
+
+    pub fn set_graph_subtype(&mut self, subtype: &str) -> &mut Self {
+        self.state.user_state.graph_subtype = subtype.to_string();
+        self
+    }
+
+    /// Declares a graph variable with the type's default value.
+    pub fn add_variable(&mut self, name: &str, typ: PulseValueType) -> &mut Self {
+        let (data_type, stored_value) = pulse_value_type_to_node_types(&typ);
+        self.state.user_state.variables.push(PulseVariable {
+            name: name.to_string(),
+            data_type,
+            stored_value,
+            typ_and_default_value: PulseValueType::PVAL_INVALID,
+            default_value_buffer: String::default(),
+        });
+        self
+    }
+
+    /// Declares a public output of the graph entity, `typ` is the type of its parameter.
+    pub fn add_public_output(&mut self, name: &str, typ: PulseValueType) -> &mut Self {
+        let (data_type, value_type) = pulse_value_type_to_node_types(&typ);
+        self.state.user_state.public_outputs.push(OutputDefinition {
+            name: name.to_string(),
+            data_type,
+            value_type,
+            typ: PulseValueType::PVAL_INT(None),
+            typ_old: PulseValueType::PVAL_INT(None),
+        });
+        self
+    }
+
+    /// Adds a node built from the template, with default values on all of its inputs.
+    pub fn add_node(&mut self, template: PulseNodeTemplate) -> NodeId {
+        let position = pos2(self.state.state.node_order.len() as f32 * NODE_SPACING, 0.0);
+        self.state.add_node(template, position)
+    }
+
+    /// Adds a game function node, looked up by its library name (e.g. `CBaseEntity::GetAbsOrigin`).
+    pub fn add_library_node(&mut self, libname: &str) -> anyhow::Result<NodeId> {
+        let binding = self.state.user_state.bindings
+            .find_function_by_libname(libname)
+            .ok_or_else(|| anyhow!("No library binding named '{libname}'"))?
+            .id;
+        Ok(self.add_node(PulseNodeTemplate::LibraryBindingAssigned { binding }))
+    }
+
+    /// Adds an event handler entry point for the event with given library name.
+    pub fn add_event_handler(&mut self, libname: &str) -> anyhow::Result<NodeId> {
+        let binding = self.state.user_state.bindings
+            .find_event_by_libname(libname)
+            .ok_or_else(|| anyhow!("No event binding named '{libname}'"))?
+            .clone();
+        let node_id = self.add_node(PulseNodeTemplate::EventHandler);
+        self.set_input_value(node_id, "event", PulseGraphValueType::EventBindingChoice { value: binding.id })?;
+        self.state.update_event_binding_params(&node_id, &binding);
+        Ok(node_id)
+    }
+
+    /// Sets the constant value of a node input.
+    /// Choosing a variable or public output on nodes that reference them also updates the types of their ports.
+    pub fn set_input_value(&mut self, node_id: NodeId, input: &str, value: PulseGraphValueType) -> anyhow::Result<()> {
+        let input_id = self.node(node_id)?.get_input(input)?;
+        let param = self.state.state.graph.get_input_mut(input_id);
+        // keep the previous value for referencing inputs, like the editor does.
+        let value = match (value, &param.value) {
+            (PulseGraphValueType::InternalVariableName { value, .. },
+                PulseGraphValueType::InternalVariableName { value: prevvalue, .. }) => {
+                PulseGraphValueType::InternalVariableName { prevvalue: prevvalue.clone(), value }
+            }
+            (PulseGraphValueType::InternalOutputName { value, .. },
+                PulseGraphValueType::InternalOutputName { value: prevvalue, .. }) => {
+                PulseGraphValueType::InternalOutputName { prevvalue: prevvalue.clone(), value }
+            }
+            (value, _) => value,
+        };
+        param.value = value.clone();
+        match value {
+            PulseGraphValueType::InternalVariableName { value: name, .. } => {
+                let idx = self.state.user_state.get_variable_id_from_name(&name)
+                    .ok_or_else(|| anyhow!("No variable named '{name}'"))?;
+                self.state.update_node_variable_types(node_id, idx);
+            }
+            PulseGraphValueType::InternalOutputName { value: name, .. } => {
+                let idx = self.state.user_state.get_public_output_id_from_name(&name)
+                    .ok_or_else(|| anyhow!("No public output named '{name}'"))?;
+                self.state.update_node_public_output_types(node_id, idx);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Connects an output of one node to an input of another.
+    /// Fails if the ports don't exist, their types are not compatible, or the input only takes a constant
+    /// value or no further connections.
+    pub fn connect(&mut self, from: NodeId, output: &str, to: NodeId, input: &str) -> anyhow::Result<()> {
+        let output_id = self.node(from)?.get_output(output)?;
+        let input_id = self.node(to)?.get_input(input)?;
+        let graph = &mut self.state.state.graph;
+        let output_type = &graph.get_output(output_id).typ;
+        let input_param = graph.get_input(input_id);
+        let input_type = &input_param.typ;
+        let allow_any = DataTypeTrait::<PulseGraphState>::allow_any_type(output_type)
+            || DataTypeTrait::<PulseGraphState>::allow_any_type(input_type);
+        if !allow_any && output_type != input_type {
+            bail!("Can't connect '{output}' ({output_type:?}) to '{input}' ({input_type:?}), types don't match");
+        }
+        if input_param.kind == InputParamKind::ConstantOnly {
+            bail!("Can't connect to '{input}', it only takes a constant value");
+        }
+        let connections = graph.connections(input_id);
+        if connections.contains(&output_id) {
+            return Ok(());
+        }
+        let pos = connections.len();
+        if let Some(max_connections) = input_param.max_connections {
+            if pos >= max_connections.get() as usize {
+                bail!("Can't connect to '{input}', it takes at most {max_connections} connection(s)");
+            }
+        }
+        graph.add_connection(output_id, input_id, pos);
+        Ok(())
+    }
+
+    /// Returns the type of a node's input, useful for checking what value `set_input_value` expects.
+    pub fn input_type(&self, node_id: NodeId, input: &str) -> anyhow::Result<PulseDataType> {
+        let input_id = self.node(node_id)?.get_input(input)?;
+        Ok(self.state.state.graph.get_input(input_id).typ.clone())
+    }
+
+    /// Compiles the graph and returns the text KV3 that would be written to the `.vpulse` file.
+    pub fn compile_to_string(&self) -> Result<String, CompileError> {
+        compile_graph_to_string(&self.state.state.graph, &self.state.user_state, &())
+    }
+
+    /// Saves the graph in the editor's format, so it can be opened and edited further by hand.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        self.state.save_graph(&path.to_path_buf())
+    }
+
+    pub fn library_binding_id(&self, libname: &str) -> Option<LibraryBindingIndex> {
+        self.state.user_state.bindings.find_function_by_libname(libname).map(|b| b.id)
+    }
+
+    pub fn event_binding_id(&self, libname: &str) -> Option<EventBindingIndex> {
+        self.state.user_state.bindings.find_event_by_libname(libname).map(|b| b.id)
+    }
+
+    pub fn state(&self) -> &FullGraphState {
+        &self.state
+    }
+
+    pub fn into_state(self) -> FullGraphState {
+        self.state
+    }
+
+    fn node(&self, node_id: NodeId) -> anyhow::Result<&Node<PulseNodeData>> {
+        self.state.state.graph.nodes.get(node_id)
+            .ok_or_else(|| anyhow!("Node {node_id:?} doesn't exist in the graph"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn method_and_log() -> (GraphBuilder, NodeId, NodeId) {
+        let mut builder = GraphBuilder::new(GraphBindings::default());
+        let method = builder.add_node(PulseNodeTemplate::CellPublicMethod);
+        let log = builder.add_node(PulseNodeTemplate::DebugLog);
+        builder.connect(method, "outAction", log, "ActionIn").unwrap();
+        builder.connect(method, "argument1", log, "pMessage").unwrap();
+        (builder, method, log)
+    }
+
+    #[test]
+    fn compiles() {
+        let (builder, _, _) = method_and_log();
+        assert!(builder.compile_to_string().is_ok());
+    }
+
+    #[test]
+    fn rejected_connections() {
+        let (mut builder, method, log) = method_and_log();
+        let concat = builder.add_node(PulseNodeTemplate::ConcatString);
+        // an action can't go into a string input
+        assert!(builder.connect(method, "outAction", concat, "A").is_err());
+        // the method name only takes a constant
+        assert!(builder.connect(concat, "out", method, "name").is_err());
+        // the message is already connected to the method's argument
+        assert!(builder.connect(concat, "out", log, "pMessage").is_err());
+        // connecting the same ports again is fine
+        assert!(builder.connect(method, "argument1", log, "pMessage").is_ok());
+    }
+}
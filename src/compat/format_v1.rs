@@ -3,7 +3,7 @@ use serde::{Serialize, Deserialize};
 #[derive(Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct PulseGraphEditorV1 {
-    #[cfg_attr(feature = "persistence", serde(skip))]
+    #[serde(skip)]
     #[allow(unused)]
     version: FileVersion,
     state: MyEditorState,
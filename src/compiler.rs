@@ -1,6 +1,7 @@
 mod instruction_templates;
 mod nodes;
 pub mod serialization;
+pub mod binary;
 
 use std::path;
 use std::{fs, borrow::Cow};
@@ -18,7 +19,7 @@ use serialization::*;
 use thiserror::Error;
 
 #[cfg(feature = "nongame_asset_build")]
-use std::{path::PathBuf, path, process::Command};
+use std::{path::PathBuf, process::Command};
 #[cfg(feature = "nongame_asset_build")]
 use crate::app::types::EditorConfig;
 
@@ -30,6 +31,29 @@ pub enum CompileError {
     WriteError(path::PathBuf, String),
     #[error("{}", .0)]
     Generic(#[from] anyhow::Error),
+    #[error("Compilation was cancelled")]
+    Cancelled,
+}
+
+/// Receives progress messages from `compile_graph` and allows the caller to cancel it.
+/// Cancellation is checked between compilation stages, so it won't stop immediately.
+pub trait CompileObserver {
+    fn progress(&self, message: String);
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+// For callers that don't care about progress.
+impl CompileObserver for () {
+    fn progress(&self, _message: String) {}
+}
+
+fn check_cancelled(observer: &dyn CompileObserver) -> Result<(), CompileError> {
+    if observer.is_cancelled() {
+        return Err(CompileError::Cancelled);
+    }
+    Ok(())
 }
 
 macro_rules! graph_next_action {
@@ -37,9 +61,9 @@ macro_rules! graph_next_action {
         graph_next_action!($graph, $current_node, $graph_def, $graph_state, $target_chunk, false)
     };
     ($graph:ident, $current_node:ident, $graph_def:ident, $graph_state:ident, $target_chunk:ident, $force_regenerate:expr) => {
-        let connected_nodes = get_nodes_connected_to_output($current_node, $graph, "outAction");
+        let connected_nodes = get_next_action_nodes($current_node, $graph, "outAction");
         if let Ok(connected_nodes) = connected_nodes {
-            for (connected_node, input_name) in connected_nodes.iter() {
+            if let Some((connected_node, input_name)) = connected_nodes.first() {
                 return traverse_nodes_and_populate(
                     $graph,
                     connected_node,
@@ -60,7 +84,7 @@ macro_rules! graph_run_next_actions_no_return {
         graph_run_next_actions_no_return!($graph, $current_node, $graph_def, $graph_state, $target_chunk, $action_name, false)
     }};
     ($graph:ident, $current_node:ident, $graph_def:ident, $graph_state:ident, $target_chunk:ident, $action_name:expr, $force_regenerate:expr) => {{
-        let connected_nodes = get_nodes_connected_to_output($current_node, $graph, $action_name);
+        let connected_nodes = get_next_action_nodes($current_node, $graph, $action_name);
         if let Ok(connected_nodes) = connected_nodes {
             let mut any = false;
             for (connected_node, input_name) in connected_nodes.iter() {
@@ -120,6 +144,7 @@ fn traverse_inflow_nodes(
     graph: &PulseGraph,
     graph_def: &mut PulseGraphDef,
     graph_state: &PulseGraphState,
+    observer: &dyn CompileObserver,
 ) -> Result<bool, CompileError> {
     let mut processed: bool = false;
     // add a empty method at the start, otherwise async calls don't work
@@ -133,6 +158,13 @@ fn traverse_inflow_nodes(
     for node in graph.iter_nodes() {
         let data: &Node<PulseNodeData> = graph.nodes.get(node).unwrap();
         // start at all possible entry points
+        if matches!(data.user_data.template,
+            PulseNodeTemplate::EventHandler | PulseNodeTemplate::CellPublicMethod
+            | PulseNodeTemplate::GraphHook | PulseNodeTemplate::EntOutputHandler)
+        {
+            check_cancelled(observer)?;
+            observer.progress(format!("Compiling entry point: {}", data.label));
+        }
         match data.user_data.template {
             PulseNodeTemplate::EventHandler => {
                 processed = true;
@@ -266,7 +298,7 @@ fn traverse_event_cell(
     }
 
     graph_def.cells.push(Box::from(cell_event));
-    let connected_node = get_nodes_connected_to_output(node, graph, "outAction")
+    let connected_node = get_next_action_nodes(node, graph, "outAction")
         .map_err(|e| CompileError::Node(node.id, e.to_string()))?;
     for (connected_node, input_name) in connected_node.iter() {
         traverse_nodes_and_populate(
@@ -299,7 +331,7 @@ fn traverse_graphhook_cell(
     let cell_hook =
         CPulseCell_Inflow_GraphHook::new(hook.libname.clone().into(), RegisterMap::default(), chunk_id);
     graph_def.cells.push(Box::from(cell_hook));
-    let connected_node = get_nodes_connected_to_output(node, graph, "outAction")
+    let connected_node = get_next_action_nodes(node, graph, "outAction")
         .map_err(|e| CompileError::Node(node.id, e.to_string()))?;
     for (connected_node, input_name) in connected_node.iter() {
         traverse_nodes_and_populate(
@@ -428,7 +460,7 @@ fn traverse_method_cell(
     graph_def.add_register_mapping(output_id_arg1, reg_id_arg1);
     graph_def.cells.push(Box::from(cell_method));
 
-    let connected_node = get_nodes_connected_to_output(node, graph, "outAction")?;
+    let connected_node = get_next_action_nodes(node, graph, "outAction")?;
     for (connected_node, input_name) in connected_node.iter() {
         traverse_nodes_and_populate(
             graph,
@@ -499,17 +531,13 @@ fn traverse_ent_output_cell(
     Ok(())
 }
 
-pub fn compile_graph(
+/// Compiles the graph into text KV3, without writing anything to disk.
+pub fn compile_graph_to_string(
     graph: &PulseGraph,
     graph_state: &PulseGraphState,
-    #[cfg(feature = "nongame_asset_build")]
-    config: &EditorConfig,
-) -> Result<(), CompileError> {
+    observer: &dyn CompileObserver,
+) -> Result<String, CompileError> {
     let mut graph_def = PulseGraphDef::default();
-    let file_dir = graph_state
-        .save_file_path
-        .as_ref()
-        .ok_or(anyhow!("File needs to be saved before compiling"))?;
     graph_def.variables = graph_state.variables.clone();
     graph_def.public_outputs = graph_state.public_outputs.clone();
     graph_def.map_name = String::from("maps/main.vmap");
@@ -517,10 +545,27 @@ pub fn compile_graph(
     graph_def.graph_domain = graph_state.graph_domain.clone();
     graph_def.graph_subtype = graph_state.graph_subtype.clone();
 
-    if !traverse_inflow_nodes(graph, &mut graph_def, graph_state)? {
+    if !traverse_inflow_nodes(graph, &mut graph_def, graph_state, observer)? {
         return Err(CompileError::Generic(anyhow!("No inflow nodes found in graph")));
     }
-    let data = kv3::to_string(&graph_def.serialize(&graph_state.bindings));
+    check_cancelled(observer)?;
+    observer.progress("Serializing graph".into());
+    Ok(kv3::to_string(&graph_def.serialize(&graph_state.bindings)))
+}
+
+/// Compiles the graph and writes the result next to the file it was saved to.
+pub fn compile_graph(
+    graph: &PulseGraph,
+    graph_state: &PulseGraphState,
+    #[cfg(feature = "nongame_asset_build")]
+    config: &EditorConfig,
+    observer: &dyn CompileObserver,
+) -> Result<(), CompileError> {
+    let file_dir = graph_state
+        .save_file_path
+        .as_ref()
+        .ok_or(anyhow!("File needs to be saved before compiling"))?;
+    let data = compile_graph_to_string(graph, graph_state, observer)?;
     let dir = file_dir.parent().ok_or_else(|| {
         CompileError::WriteError(file_dir.clone(), "Failed to get parent directory of this file".into())
     })?;
@@ -531,6 +576,7 @@ pub fn compile_graph(
     #[cfg(not(feature = "nongame_asset_build"))] {
         let mut file_path: std::path::PathBuf = file_dir.clone();
         file_path.set_extension("vpulse");
+        observer.progress(format!("Writing {}", file_path.display()));
         fs::write(file_path, data)
             .map_err(|e| CompileError::WriteError(file_dir.clone(), e.to_string()))?;
     }
@@ -551,9 +597,10 @@ pub fn compile_graph(
         ));
         fs::write(&temp_dir_file, data)
             .map_err(|e| CompileError::WriteError(temp_dir_file.clone(), e.to_string()))?;
-        run_asset_builder(config, &temp_dir_file, file_dir)
-            .map_err(|e| CompileError::Generic(e.to_string()))?;
+        let res = run_asset_builder(config, &temp_dir_file, file_dir, observer);
         let _ = fs::remove_file(&temp_dir_file); // ok to ignore
+        res?;
+        check_cancelled(observer)?;
     }
     Ok(())
 }
@@ -597,8 +644,8 @@ fn get_output_path(original_path: &path::Path) -> anyhow::Result<PathBuf> {
 }
 
 #[cfg(feature = "nongame_asset_build")]
-fn run_asset_builder(config: &EditorConfig, path_src: &path::Path, path_editor_file: &path::Path) -> anyhow::Result<()> {
-    println!("Running asset assembler for file: {}", path_src.display());
+fn run_asset_builder(config: &EditorConfig, path_src: &path::Path, path_editor_file: &path::Path, observer: &dyn CompileObserver) -> anyhow::Result<()> {
+    observer.progress(format!("Running asset assembler for file: {}", path_src.display()));
     let assetbuilder_path = config.assetassembler_path.as_path();
     let red2_path = config.red2_template_path.as_path();
     if !assetbuilder_path.exists() || !assetbuilder_path.is_file() {
@@ -622,7 +669,7 @@ fn run_asset_builder(config: &EditorConfig, path_src: &path::Path, path_editor_f
     // get rid of file name for the output path
     let mut out_file = get_output_path(path_editor_file.parent().unwrap())?.join(file_name);
     out_file.set_extension("vpulse_c");
-    println!("Determined full output path: {}", out_file.display());
+    observer.progress(format!("Determined full output path: {}", out_file.display()));
     let mut process = Command::new(config.python_interpreter.as_str())
         .arg(assetbuilder_path)
         .arg("-p")
@@ -633,10 +680,21 @@ fn run_asset_builder(config: &EditorConfig, path_src: &path::Path, path_editor_f
         .arg("-o")
         .arg(out_file.as_os_str())
         .spawn()?;
-    // it's fine to freeze the UI here, because recompiling while loading could lead to issues.
-    // this process should usually be fast enough to not be very noticable.
-    process.wait()?;
-    Ok(())
+    // poll instead of blocking on wait(), so that the process can be killed when compilation gets cancelled.
+    loop {
+        if let Some(status) = process.try_wait()? {
+            if !status.success() {
+                return Err(anyhow::anyhow!("Asset assembler exited with {status}"));
+            }
+            return Ok(());
+        }
+        if observer.is_cancelled() {
+            let _ = process.kill();
+            process.wait()?;
+            return Ok(());
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
 }
 
 fn try_find_output_mapping(graph_def: &PulseGraphDef, output_id: &Option<OutputId>) -> Option<i32> {
@@ -673,7 +731,8 @@ fn get_input_register_or_create_constant_from_id(
     value_type: PulseValueType,
     always_reevaluate: bool,
 ) -> Result<Option<i32>, CompileError> {
-    let connection_to_input: Option<OutputId> = graph.connection(input_id);
+    // reroutes are skipped, an input fed by a dangling reroute behaves as if it wasn't connected.
+    let connection_to_input: Option<OutputId> = skip_reroutes(graph, graph.connection(input_id));
     let target_register: i32;
     // if we find a connection, then traverse to that node, whatever happens we should get a register id back.
     match connection_to_input {
@@ -1959,12 +2018,12 @@ fn traverse_nodes_and_populate<'a>(
             let cell_binding_id =
                 add_cell_invoke_binding(graph_def, register_map, target_chunk, "CPulseCell_Outflow_IntSwitch::Run".into(), -1);
             let mut instructions_jump_end = vec![];
-            for out in current_node.outputs.iter() {
-                if out.0.parse::<i32>().is_err() {
+            for (case_name, case_output) in get_action_outputs(current_node, graph) {
+                if case_name.parse::<i32>().is_err() {
                     continue;
                 }
 
-                let next_actions = get_nodes_and_inputs_connected_from_output(graph, &out.1)?;
+                let next_actions = get_nodes_and_inputs_connected_from_output(graph, &case_output)?;
                 if !next_actions.is_empty() {
                     let this_action_instruction_id = graph_def
                         .chunks
@@ -2006,7 +2065,7 @@ fn traverse_nodes_and_populate<'a>(
                     );
 
                     outflow_connections.push(OutflowConnection::new(
-                        out.0.clone().into(),
+                        case_name.to_string().into(),
                         target_chunk,
                         this_action_instruction_id,
                         Some(RegisterMap::default()),
@@ -2692,6 +2751,37 @@ fn traverse_nodes_and_populate<'a>(
             );
             return Ok(reg_out);
         }
+        PulseNodeTemplate::Reroute => {
+            // reroutes only organize wires, the value or flow passes through without any instructions.
+            if output_id.is_some() {
+                let input_id = current_node.get_input("in")
+                    .map_err(|e| CompileError::Node(current_node.id, e.to_string()))?;
+                let source = skip_reroutes(graph, graph.connection(input_id))
+                    .ok_or_else(|| CompileError::Node(current_node.id, "Reroute has nothing connected to its input".into()))?;
+                let source_node = graph.nodes.get(graph.get_output(source).node)
+                    .ok_or(CompileError::Generic(anyhow!("Can't find output node")))?;
+                return traverse_nodes_and_populate(
+                    graph,
+                    source_node,
+                    graph_def,
+                    graph_state,
+                    target_chunk,
+                    &Some(source),
+                    &None,
+                    force_regenerate,
+                );
+            }
+            // an action carries on after the reroute by the rule of the output it hangs off.
+            graph_run_next_actions_no_return!(
+                graph,
+                current_node,
+                graph_def,
+                graph_state,
+                target_chunk,
+                "out",
+                force_regenerate
+            );
+        }
         _ => todo!(
             "Implement node template: {:?}",
             current_node.user_data.template
@@ -0,0 +1,701 @@
+// Reader for compiled resources (.vpulse_c), used for inspecting graphs that are only available in their compiled form.
+// The resource container is parsed to find the DATA block, which is then decoded from binary KV3
+// into the same Value tree that the compiler produces with KV3Serialize.
+use anyhow::{anyhow, bail, Context};
+use kv3::{Metadata, ObjectKey, Value};
+use std::path::Path;
+
+const KV3_LEGACY_MAGIC: u32 = 0x03564B56; // "VKV\x03"
+const KV3_01_MAGIC: u32 = 0x4B563301; // "\x013VK"
+const KV3_02_MAGIC: u32 = 0x4B563302;
+// Newer containers split the data into more buffers, their layout isn't known well enough to read them.
+const KV3_03_MAGIC: u32 = 0x4B563303;
+const KV3_05_MAGIC: u32 = 0x4B563305;
+const KV3_TRAILER: u32 = 0xFFEEDD00;
+
+// KV3_ENCODING_BINARY_UNCOMPRESSED and KV3_ENCODING_BINARY_BLOCK_LZ4 as laid out in memory.
+const ENCODING_LEGACY_UNCOMPRESSED: [u8; 16] = [
+    0x00, 0x05, 0x86, 0x1B, 0xD8, 0xF7, 0xC1, 0x40, 0xAD, 0x82, 0x75, 0xA4, 0x82, 0x67, 0xE7, 0x14,
+];
+const ENCODING_LEGACY_LZ4: [u8; 16] = [
+    0x8A, 0x34, 0x47, 0x68, 0xA1, 0x63, 0x5C, 0x4F, 0xA1, 0x97, 0x53, 0x80, 0x6F, 0xD9, 0xB1, 0x19,
+];
+
+const COMPRESSION_NONE: u32 = 0;
+const COMPRESSION_LZ4: u32 = 1;
+// LZ4 can't compress better than this, larger declared sizes come from corrupted headers.
+const LZ4_MAX_RATIO: usize = 255;
+
+// Nesting deeper than this is taken as a corrupted file rather than risking a stack overflow.
+const MAX_DEPTH: usize = 256;
+// Items that take no data of their own (nulls, constant booleans...) can't be checked against the
+// remaining data, so their count is capped instead.
+const MAX_EMPTY_ITEMS: usize = 1 << 16;
+// Integers further from zero than this don't fit in the f64 of a Value::Number.
+const MAX_EXACT_INTEGER: u64 = 1 << 53;
+
+// The buffer the data of a node comes from, every buffer is the same stream in the legacy format.
+#[derive(Clone, Copy)]
+enum Payload {
+    None,
+    Byte,
+    Int,
+    EightBytes,
+    Type,
+}
+
+impl Payload {
+    fn size(self) -> usize {
+        match self {
+            Payload::None => 0,
+            Payload::Byte | Payload::Type => 1,
+            Payload::Int => 4,
+            Payload::EightBytes => 8,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum NodeType {
+    Null,
+    Boolean,
+    Int64,
+    UInt64,
+    Double,
+    String,
+    BinaryBlob,
+    Array,
+    Object,
+    ArrayTyped,
+    Int32,
+    UInt32,
+    BooleanTrue,
+    BooleanFalse,
+    Int64Zero,
+    Int64One,
+    DoubleZero,
+    DoubleOne,
+    Float,
+    Int16,
+    UInt16,
+    Int32AsByte,
+    ArrayTypedByteLength,
+}
+
+impl TryFrom<u8> for NodeType {
+    type Error = anyhow::Error;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            1 => NodeType::Null,
+            2 => NodeType::Boolean,
+            3 => NodeType::Int64,
+            4 => NodeType::UInt64,
+            5 => NodeType::Double,
+            6 => NodeType::String,
+            7 => NodeType::BinaryBlob,
+            8 => NodeType::Array,
+            9 => NodeType::Object,
+            10 => NodeType::ArrayTyped,
+            11 => NodeType::Int32,
+            12 => NodeType::UInt32,
+            13 => NodeType::BooleanTrue,
+            14 => NodeType::BooleanFalse,
+            15 => NodeType::Int64Zero,
+            16 => NodeType::Int64One,
+            17 => NodeType::DoubleZero,
+            18 => NodeType::DoubleOne,
+            19 => NodeType::Float,
+            20 => NodeType::Int16,
+            21 => NodeType::UInt16,
+            23 => NodeType::Int32AsByte,
+            24 => NodeType::ArrayTypedByteLength,
+            _ => bail!("Unsupported KV3 node type {value}"),
+        })
+    }
+}
+
+impl NodeType {
+    // Where the data of a node of this type starts, used to check item counts.
+    fn payload(self) -> Payload {
+        match self {
+            NodeType::Null
+            | NodeType::BooleanTrue
+            | NodeType::BooleanFalse
+            | NodeType::Int64Zero
+            | NodeType::Int64One
+            | NodeType::DoubleZero
+            | NodeType::DoubleOne => Payload::None,
+            NodeType::Boolean | NodeType::Int32AsByte | NodeType::ArrayTypedByteLength => Payload::Byte,
+            NodeType::Int64 | NodeType::UInt64 | NodeType::Double => Payload::EightBytes,
+            NodeType::String
+            | NodeType::BinaryBlob
+            | NodeType::Array
+            | NodeType::Object
+            | NodeType::ArrayTyped
+            | NodeType::Int32
+            | NodeType::UInt32
+            | NodeType::Float
+            | NodeType::Int16
+            | NodeType::UInt16 => Payload::Int,
+        }
+    }
+}
+
+// The f64 of a Value::Number can't hold every 64-bit integer, the ones it can't are kept
+// as their exact decimal text instead of being rounded.
+fn integer_value(value: i128) -> Value {
+    if value.unsigned_abs() <= MAX_EXACT_INTEGER as u128 {
+        Value::Number(value as f64)
+    } else {
+        Value::String(value.to_string())
+    }
+}
+
+fn flag_name(flag: u8) -> Option<&'static str> {
+    match flag {
+        1 => Some("resource"),
+        2 => Some("resource_name"),
+        8 => Some("panorama"),
+        16 => Some("soundevent"),
+        32 => Some("subclass"),
+        _ => None,
+    }
+}
+
+struct Cursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+    fn take(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        let end = self.pos.checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| anyhow!("Unexpected end of data at offset {}", self.pos))?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+    fn align(&mut self, alignment: usize) {
+        self.pos = self.pos.next_multiple_of(alignment);
+    }
+    fn remaining(&self) -> usize {
+        self.data.len().saturating_sub(self.pos)
+    }
+    fn rest(&self) -> anyhow::Result<&'a [u8]> {
+        self.data.get(self.pos..).ok_or_else(|| anyhow!("Unexpected end of data at offset {}", self.pos))
+    }
+    fn u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+    fn u16(&mut self) -> anyhow::Result<u16> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into()?))
+    }
+    fn u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+    fn i32(&mut self) -> anyhow::Result<i32> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+    fn u64(&mut self) -> anyhow::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+    fn cstring(&mut self) -> anyhow::Result<String> {
+        let rest = self.rest()?;
+        let len = rest.iter().position(|b| *b == 0)
+            .ok_or_else(|| anyhow!("Unterminated string at offset {}", self.pos))?;
+        let s = String::from_utf8_lossy(&rest[..len]).into_owned();
+        self.pos += len + 1;
+        Ok(s)
+    }
+}
+
+// Binary KV3 stores values in separate buffers grouped by size,
+// the legacy format keeps everything (including types) in one stream instead.
+enum ValueSource<'a> {
+    Legacy(Cursor<'a>),
+    Split {
+        bytes: Cursor<'a>,
+        ints: Cursor<'a>,
+        eight_bytes: Cursor<'a>,
+        types: Cursor<'a>,
+    },
+}
+
+struct BinaryKV3Reader<'a> {
+    strings: Vec<String>,
+    source: ValueSource<'a>,
+    depth: usize,
+}
+
+impl BinaryKV3Reader<'_> {
+    fn read_type(&mut self) -> anyhow::Result<(NodeType, Option<u8>)> {
+        let (types, flag_mask) = match &mut self.source {
+            ValueSource::Legacy(stream) => (stream, 0x7F),
+            ValueSource::Split { types, .. } => (types, 0x3F),
+        };
+        let mut type_byte = types.u8()?;
+        let mut flag = None;
+        if type_byte & 0x80 != 0 {
+            type_byte &= flag_mask;
+            flag = Some(types.u8()?);
+        }
+        Ok((NodeType::try_from(type_byte)?, flag))
+    }
+    fn read_byte(&mut self) -> anyhow::Result<u8> {
+        match &mut self.source {
+            ValueSource::Legacy(stream) => stream.u8(),
+            ValueSource::Split { bytes, .. } => bytes.u8(),
+        }
+    }
+    fn read_int(&mut self) -> anyhow::Result<i32> {
+        match &mut self.source {
+            ValueSource::Legacy(stream) => {
+                stream.align(4);
+                stream.i32()
+            }
+            ValueSource::Split { ints, .. } => ints.i32(),
+        }
+    }
+    fn read_eight_bytes(&mut self) -> anyhow::Result<u64> {
+        match &mut self.source {
+            ValueSource::Legacy(stream) => {
+                stream.align(8);
+                stream.u64()
+            }
+            ValueSource::Split { eight_bytes, .. } => eight_bytes.u64(),
+        }
+    }
+    fn read_blob(&mut self, len: usize) -> anyhow::Result<&[u8]> {
+        match &mut self.source {
+            ValueSource::Legacy(stream) => stream.take(len),
+            ValueSource::Split { bytes, .. } => bytes.take(len),
+        }
+    }
+    fn remaining(&self, payload: Payload) -> usize {
+        match &self.source {
+            ValueSource::Legacy(stream) => stream.remaining(),
+            ValueSource::Split { bytes, ints, eight_bytes, types } => match payload {
+                Payload::None => 0,
+                Payload::Byte => bytes.remaining(),
+                Payload::Int => ints.remaining(),
+                Payload::EightBytes => eight_bytes.remaining(),
+                Payload::Type => types.remaining(),
+            },
+        }
+    }
+    // Checks a count read from the file against the data left for its items, so corrupted
+    // counts fail instead of allocating or looping for a long time.
+    fn check_count(&self, count: usize, payload: Payload) -> anyhow::Result<usize> {
+        let fits = match payload.size() {
+            0 => count <= MAX_EMPTY_ITEMS,
+            size => count <= self.remaining(payload) / size,
+        };
+        if !fits {
+            bail!("Item count {count} is larger than the remaining data, the file might be corrupted");
+        }
+        Ok(count)
+    }
+    fn read_count(&mut self, payload: Payload) -> anyhow::Result<usize> {
+        let count = self.read_int()?;
+        let count = usize::try_from(count).map_err(|_| anyhow!("Negative item count {count}"))?;
+        self.check_count(count, payload)
+    }
+    fn read_string_index(&mut self) -> anyhow::Result<String> {
+        let idx = self.read_int()?;
+        if idx < 0 {
+            return Ok(String::new());
+        }
+        self.strings.get(idx as usize).cloned()
+            .ok_or_else(|| anyhow!("String index {idx} out of range"))
+    }
+
+    fn read_node(&mut self) -> anyhow::Result<Value> {
+        let (typ, flag) = self.read_type()?;
+        self.read_value(typ, flag)
+    }
+
+    fn read_value(&mut self, typ: NodeType, flag: Option<u8>) -> anyhow::Result<Value> {
+        if self.depth >= MAX_DEPTH {
+            bail!("KV3 values are nested more than {MAX_DEPTH} levels deep, the file might be corrupted");
+        }
+        self.depth += 1;
+        let value = self.decode_value(typ, flag);
+        self.depth -= 1;
+        value
+    }
+
+    fn decode_value(&mut self, typ: NodeType, flag: Option<u8>) -> anyhow::Result<Value> {
+        let value = match typ {
+            NodeType::Null => Value::Null,
+            NodeType::Boolean => Value::Bool(self.read_byte()? != 0),
+            NodeType::BooleanTrue => Value::Bool(true),
+            NodeType::BooleanFalse => Value::Bool(false),
+            NodeType::Int64 => integer_value(self.read_eight_bytes()? as i64 as i128),
+            NodeType::UInt64 => integer_value(self.read_eight_bytes()? as i128),
+            NodeType::Double => Value::Number(f64::from_bits(self.read_eight_bytes()?)),
+            NodeType::Int64Zero | NodeType::DoubleZero => Value::Number(0.0),
+            NodeType::Int64One | NodeType::DoubleOne => Value::Number(1.0),
+            NodeType::Int32 => Value::Number(self.read_int()? as f64),
+            NodeType::UInt32 => Value::Number(self.read_int()? as u32 as f64),
+            NodeType::Float => Value::Number(f32::from_bits(self.read_int()? as u32) as f64),
+            NodeType::Int16 => Value::Number(self.read_int()? as i16 as f64),
+            NodeType::UInt16 => Value::Number(self.read_int()? as u16 as f64),
+            NodeType::Int32AsByte => Value::Number(self.read_byte()? as f64),
+            NodeType::String => Value::String(self.read_string_index()?),
+            NodeType::BinaryBlob => {
+                // there's no blob representation in the text format we produce, keep it as an array of bytes.
+                let len = self.read_count(Payload::Byte)?;
+                let blob = self.read_blob(len)?;
+                Value::Array(blob.iter().map(|b| Value::Number(*b as f64)).collect())
+            }
+            NodeType::Array => {
+                let count = self.read_count(Payload::Type)?;
+                let mut items = Vec::new();
+                for _ in 0..count {
+                    items.push(self.read_node()?);
+                }
+                Value::Array(items)
+            }
+            NodeType::ArrayTyped | NodeType::ArrayTypedByteLength => {
+                let count = if typ == NodeType::ArrayTyped {
+                    let count = self.read_int()?;
+                    usize::try_from(count).map_err(|_| anyhow!("Negative item count {count}"))?
+                } else {
+                    self.read_byte()? as usize
+                };
+                let (sub_type, sub_flag) = self.read_type()?;
+                self.check_count(count, sub_type.payload())?;
+                let mut items = Vec::new();
+                for _ in 0..count {
+                    items.push(self.read_value(sub_type, sub_flag)?);
+                }
+                Value::Array(items)
+            }
+            NodeType::Object => {
+                let count = self.read_count(Payload::Int)?;
+                let mut members = Vec::new();
+                for _ in 0..count {
+                    let key = self.read_string_index()?;
+                    let value = self.read_node()?;
+                    members.push((ObjectKey::Identifier(key), value));
+                }
+                Value::Object(members)
+            }
+        };
+        Ok(match flag.and_then(flag_name) {
+            Some(name) => Value::Flag(name.to_string(), Box::new(value)),
+            None => value,
+        })
+    }
+}
+
+fn decompress(compression: u32, data: &[u8], uncompressed_size: usize) -> anyhow::Result<Vec<u8>> {
+    match compression {
+        COMPRESSION_NONE => Ok(data.get(..uncompressed_size)
+            .ok_or_else(|| anyhow!("KV3 data is shorter than declared size"))?
+            .to_vec()),
+        COMPRESSION_LZ4 => {
+            if uncompressed_size > data.len().saturating_mul(LZ4_MAX_RATIO) {
+                bail!("Declared KV3 size {uncompressed_size} is too large for its compressed data");
+            }
+            lz4_flex::block::decompress(data, uncompressed_size)
+                .context("Failed to decompress LZ4 KV3 data")
+        }
+        _ => bail!("Unsupported KV3 compression method {compression}"),
+    }
+}
+
+fn read_strings(cursor: &mut Cursor<'_>, count: usize) -> anyhow::Result<Vec<String>> {
+    // every string takes at least its terminator
+    if count > cursor.remaining() {
+        bail!("String count {count} is larger than the remaining data, the file might be corrupted");
+    }
+    (0..count).map(|_| cursor.cstring()).collect()
+}
+
+fn parse_legacy(mut cursor: Cursor<'_>) -> anyhow::Result<Value> {
+    let encoding: [u8; 16] = cursor.take(16)?.try_into()?;
+    let _format = cursor.take(16)?;
+    let data = match encoding {
+        ENCODING_LEGACY_UNCOMPRESSED => cursor.rest()?.to_vec(),
+        ENCODING_LEGACY_LZ4 => {
+            let uncompressed_size = cursor.u32()? as usize;
+            decompress(COMPRESSION_LZ4, cursor.rest()?, uncompressed_size)?
+        }
+        _ => bail!("Unsupported legacy KV3 encoding"),
+    };
+    let mut stream = Cursor::new(&data);
+    let string_count = stream.u32()? as usize;
+    let strings = read_strings(&mut stream, string_count)?;
+    let mut reader = BinaryKV3Reader { strings, source: ValueSource::Legacy(stream), depth: 0 };
+    reader.read_node()
+}
+
+fn parse_versioned(mut cursor: Cursor<'_>, version: u32) -> anyhow::Result<Value> {
+    let _format = cursor.take(16)?;
+    let compression = cursor.u32()?;
+    let data;
+    let (binary_bytes, int_count, eight_byte_count, strings_and_types_size) = if version == 1 {
+        let binary_bytes = cursor.u32()? as usize;
+        let int_count = cursor.u32()? as usize;
+        let eight_byte_count = cursor.u32()? as usize;
+        let uncompressed_size = cursor.u32()? as usize;
+        data = decompress(compression, cursor.rest()?, uncompressed_size)?;
+        (binary_bytes, int_count, eight_byte_count, None)
+    } else {
+        let _dictionary_id = cursor.u16()?;
+        let _frame_size = cursor.u16()?;
+        let binary_bytes = cursor.u32()? as usize;
+        let int_count = cursor.u32()? as usize;
+        let eight_byte_count = cursor.u32()? as usize;
+        let strings_and_types_size = cursor.u32()? as usize;
+        let _prealloc = cursor.take(4)?;
+        let uncompressed_size = cursor.u32()? as usize;
+        let compressed_size = cursor.u32()? as usize;
+        let block_count = cursor.u32()?;
+        let _block_total_size = cursor.u32()?;
+        if block_count > 0 {
+            bail!("KV3 files with binary blob blocks are not supported");
+        }
+        let payload = if compression == COMPRESSION_NONE {
+            cursor.rest()?
+        } else {
+            cursor.take(compressed_size)?
+        };
+        data = decompress(compression, payload, uncompressed_size)?;
+        (binary_bytes, int_count, eight_byte_count, Some(strings_and_types_size))
+    };
+
+    let mut stream = Cursor::new(&data);
+    let bytes = Cursor::new(stream.take(binary_bytes)?);
+    stream.align(4);
+    // the first integer is the string count, the rest is used by values
+    let string_count = stream.u32()? as usize;
+    let ints = Cursor::new(stream.take(int_count.saturating_sub(1).saturating_mul(4))?);
+    stream.align(8);
+    let eight_bytes = Cursor::new(stream.take(eight_byte_count.saturating_mul(8))?);
+    let strings_start = stream.pos;
+    let strings = read_strings(&mut stream, string_count)?;
+    let types_end = match strings_and_types_size {
+        Some(size) => strings_start.saturating_add(size),
+        None => data.len().checked_sub(4).ok_or_else(|| anyhow!("KV3 data is too short"))?,
+    };
+    let types = Cursor::new(data.get(stream.pos..types_end)
+        .ok_or_else(|| anyhow!("KV3 type buffer is out of range"))?);
+    let mut trailer = Cursor::new(&data[types_end.min(data.len())..]);
+    if trailer.u32().ok() != Some(KV3_TRAILER) {
+        bail!("KV3 data is missing the end marker, the file might be corrupted");
+    }
+    let mut reader = BinaryKV3Reader {
+        strings,
+        source: ValueSource::Split { bytes, ints, eight_bytes, types },
+        depth: 0,
+    };
+    reader.read_node()
+}
+
+/// Decodes a binary KV3 buffer into a Value tree.
+pub fn parse_binary_kv3(data: &[u8]) -> anyhow::Result<Value> {
+    let mut cursor = Cursor::new(data);
+    let magic = cursor.u32()?;
+    match magic {
+        KV3_LEGACY_MAGIC => parse_legacy(cursor),
+        KV3_01_MAGIC => parse_versioned(cursor, 1),
+        KV3_02_MAGIC => parse_versioned(cursor, 2),
+        KV3_03_MAGIC..=KV3_05_MAGIC => bail!(
+            "Binary KV3 version {} is not supported yet, only the legacy format and versions 1 and 2 can be read",
+            magic - KV3_01_MAGIC + 1
+        ),
+        _ => bail!("Unsupported binary KV3 version (magic {magic:#010x})"),
+    }
+}
+
+// Returns the contents of the DATA block from a resource container.
+fn find_data_block(data: &[u8]) -> anyhow::Result<&[u8]> {
+    let mut cursor = Cursor::new(data);
+    let _file_size = cursor.u32()?;
+    let _header_version = cursor.u16()?;
+    let _version = cursor.u16()?;
+    let block_offset_pos = cursor.pos;
+    let block_offset = cursor.u32()? as usize;
+    let block_count = cursor.u32()?;
+    cursor.pos = block_offset_pos.saturating_add(block_offset);
+    for _ in 0..block_count {
+        let block_type = cursor.take(4)?;
+        let offset_pos = cursor.pos;
+        let offset = cursor.u32()? as usize;
+        let size = cursor.u32()? as usize;
+        if block_type == b"DATA" {
+            let start = offset_pos.saturating_add(offset);
+            return data.get(start..start.saturating_add(size))
+                .ok_or_else(|| anyhow!("DATA block is out of file bounds"));
+        }
+    }
+    bail!("Resource doesn't contain a DATA block")
+}
+
+/// Reads a compiled resource (e.g. .vpulse_c) and returns its DATA block as a KV3 file value,
+/// ready to be turned into text with `kv3::to_string`.
+pub fn read_compiled_resource(path: &Path) -> anyhow::Result<Value> {
+    let data = std::fs::read(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    // allow loading bare binary KV3 as well, not wrapped in a resource container
+    let kv3_data = match parse_binary_kv3(&data) {
+        Ok(value) => return Ok(wrap_text_header(value)),
+        Err(_) => find_data_block(&data)?,
+    };
+    let value = parse_binary_kv3(kv3_data).context("Failed to decode the DATA block")?;
+    Ok(wrap_text_header(value))
+}
+
+fn wrap_text_header(value: Value) -> Value {
+    Value::File(
+        kv3::Header(vec![
+            Metadata {
+                key: "encoding".into(),
+                value: "text".into(),
+                version: "e21c7f3c-8a33-41c5-9977-a76d3a32aa0d".into()
+            },
+            Metadata {
+                key: "format".into(),
+                value: "generic".into(),
+                version: "7412167c-06e9-4698-aff2-e63eb59037e7".into()
+            },
+        ]),
+        Box::new(value),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An uncompressed version 1 KV3 buffer with the given value buffers.
+    fn kv3_v1(bytes: &[u8], ints: &[i32], eight_bytes: &[u64], strings: &[&str], types: &[u8]) -> Vec<u8> {
+        let mut data = bytes.to_vec();
+        data.resize(data.len().next_multiple_of(4), 0);
+        data.extend((strings.len() as u32).to_le_bytes());
+        for int in ints {
+            data.extend(int.to_le_bytes());
+        }
+        data.resize(data.len().next_multiple_of(8), 0);
+        for value in eight_bytes {
+            data.extend(value.to_le_bytes());
+        }
+        for string in strings {
+            data.extend(string.as_bytes());
+            data.push(0);
+        }
+        data.extend(types);
+        data.extend(KV3_TRAILER.to_le_bytes());
+
+        let mut file = KV3_01_MAGIC.to_le_bytes().to_vec();
+        file.extend([0; 16]);
+        for field in [COMPRESSION_NONE, bytes.len() as u32, ints.len() as u32 + 1, eight_bytes.len() as u32, data.len() as u32] {
+            file.extend(field.to_le_bytes());
+        }
+        file.extend(data);
+        file
+    }
+
+    fn member<'a>(value: &'a Value, name: &str) -> &'a Value {
+        let Value::Object(members) = value else {
+            panic!("not an object: {value:?}");
+        };
+        members.iter()
+            .find(|(ObjectKey::Identifier(key), _)| key == name)
+            .map(|(_, value)| value)
+            .unwrap_or_else(|| panic!("no member {name}"))
+    }
+
+    // { count = 7, name = "relay", enabled = true, big = 2^60 + 1, small = -5 }
+    fn sample() -> Vec<u8> {
+        kv3_v1(
+            &[1],
+            &[5, 0, 7, 1, 5, 2, 3, 4],
+            &[(1 << 60) + 1, -5i64 as u64],
+            &["count", "name", "enabled", "big", "small", "relay"],
+            &[9, 11, 6, 2, 3, 3],
+        )
+    }
+
+    #[test]
+    fn reads_object() {
+        let value = parse_binary_kv3(&sample()).unwrap();
+        assert!(matches!(member(&value, "count"), Value::Number(n) if *n == 7.0));
+        assert!(matches!(member(&value, "name"), Value::String(s) if s == "relay"));
+        assert!(matches!(member(&value, "enabled"), Value::Bool(true)));
+        assert!(matches!(member(&value, "small"), Value::Number(n) if *n == -5.0));
+    }
+
+    #[test]
+    fn keeps_large_integers_exact() {
+        let value = parse_binary_kv3(&sample()).unwrap();
+        assert!(matches!(member(&value, "big"), Value::String(s) if s == "1152921504606846977"));
+        let max = kv3_v1(&[], &[], &[u64::MAX], &[], &[4]);
+        assert!(matches!(parse_binary_kv3(&max).unwrap(), Value::String(s) if s == u64::MAX.to_string()));
+    }
+
+    #[test]
+    fn reads_typed_array() {
+        let data = kv3_v1(&[], &[3], &[1.5f64.to_bits(), 2.5f64.to_bits(), 0], &[], &[10, 5]);
+        let Value::Array(items) = parse_binary_kv3(&data).unwrap() else {
+            panic!("not an array");
+        };
+        assert_eq!(items.len(), 3);
+        assert!(matches!(items[1], Value::Number(n) if n == 2.5));
+    }
+
+    #[test]
+    fn reads_legacy() {
+        let mut file = KV3_LEGACY_MAGIC.to_le_bytes().to_vec();
+        file.extend(ENCODING_LEGACY_UNCOMPRESSED);
+        file.extend([0; 16]);
+        // no strings, then an Int32 aligned to 4 bytes
+        file.extend([0, 0, 0, 0, 11, 0, 0, 0]);
+        file.extend(42i32.to_le_bytes());
+        assert!(matches!(parse_binary_kv3(&file).unwrap(), Value::Number(n) if n == 42.0));
+    }
+
+    #[test]
+    fn truncated_data_fails() {
+        let data = sample();
+        for len in 0..data.len() {
+            assert!(parse_binary_kv3(&data[..len]).is_err(), "parsed {len} bytes");
+        }
+    }
+
+    #[test]
+    fn corrupt_counts_fail() {
+        for count in [-1, i32::MAX] {
+            // array, typed array of nulls, object and blob
+            for types in [&[8][..], &[10, 1], &[9], &[7]] {
+                let data = kv3_v1(&[], &[count], &[], &[], types);
+                assert!(parse_binary_kv3(&data).is_err(), "count {count} of {types:?}");
+            }
+        }
+        let data = kv3_v1(&[], &[], &[], &[], &[99]);
+        assert!(parse_binary_kv3(&data).is_err());
+    }
+
+    #[test]
+    fn deep_nesting_fails() {
+        let depth = MAX_DEPTH + 10;
+        let data = kv3_v1(&[], &vec![1; depth], &[], &[], &vec![8; depth]);
+        assert!(parse_binary_kv3(&data).is_err());
+        let data = kv3_v1(&[], &[1; 10], &[], &[], &[vec![8; 10], vec![1]].concat());
+        assert!(parse_binary_kv3(&data).is_ok());
+    }
+
+    #[test]
+    fn newer_versions_are_reported() {
+        let mut data = sample();
+        data[..4].copy_from_slice(&0x4B563304u32.to_le_bytes());
+        let error = parse_binary_kv3(&data).unwrap_err().to_string();
+        assert!(error.contains("version 4 is not supported"), "{error}");
+    }
+}
@@ -4,24 +4,29 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod app;
-mod bindings;
-mod compiler;
-mod pulsetypes;
-mod typing;
+pub mod bindings;
+pub mod builder;
+pub mod compiler;
+pub mod pulsetypes;
+pub mod typing;
 mod utils;
 mod compat;
-pub use app::PulseGraphEditor;
+mod watch;
+pub use app::{types, FullGraphState};
+#[cfg(feature = "gui")]
+pub use app::{setup_panic_hook, PulseGraphEditor};
+pub use watch::run_watch_cli;
 // ----------------------------------------------------------------------------
 // When compiling for web:
 
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(feature = "gui", target_arch = "wasm32"))]
 use eframe::wasm_bindgen::{self, prelude::*};
 
 /// This is the entry-point for all the web-assembly.
 /// This is called once from the HTML.
 /// It loads the app, installs some callbacks, then returns.
 /// You can add more callbacks like this if you want to call in to your code.
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(feature = "gui", target_arch = "wasm32"))]
 #[wasm_bindgen]
 pub fn start(canvas_id: &str) -> Result<(), eframe::wasm_bindgen::JsValue> {
     let app = PulseGraphEditor::default();
@@ -3,12 +3,63 @@
 #![warn(clippy::all, rust_2018_idioms)]
 #![cfg_attr(all(not(debug_assertions), target_os = "windows"), windows_subsystem = "windows")]
 
-use libpulseedit::PulseGraphEditor;
+#[cfg(feature = "gui")]
+use libpulseedit::{setup_panic_hook, PulseGraphEditor};
+#[cfg(feature = "gui")]
 use std::sync::Arc;
+use std::path::PathBuf;
 
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
 fn main() {
+    // `pulseedit --watch <graph.ron>...` recompiles the graphs on change without opening the editor.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let watch = args.first().is_some_and(|arg| arg == "--watch");
+    // the rest are graphs, which are looked for where the command was run from
+    let paths: Vec<PathBuf> = args[usize::from(watch)..]
+        .iter()
+        .map(|arg| std::path::absolute(arg).unwrap_or_else(|_| arg.into()))
+        .collect();
+    libpulseedit::bindings::enter_install_dir();
+    if watch {
+        run_watch(&paths);
+        return;
+    }
+    // anything else is a graph to open, e.g. from a file association.
+    #[cfg(feature = "gui")]
+    run_editor(paths);
+    #[cfg(not(feature = "gui"))]
+    {
+        eprintln!("This build doesn't include the editor, use `pulseedit --watch <graph.ron>...` to compile graphs.");
+        std::process::exit(2);
+    }
+}
+
+#[cfg(not(all(not(debug_assertions), target_os = "windows")))]
+fn run_watch(paths: &[PathBuf]) {
+    if let Err(e) = libpulseedit::run_watch_cli(paths) {
+        eprintln!("{e:#}");
+        std::process::exit(1);
+    }
+}
+
+// A windowed program has nowhere to print to, the console version runs in a console of its own.
+#[cfg(all(not(debug_assertions), target_os = "windows"))]
+fn run_watch(paths: &[PathBuf]) {
+    let cli = std::env::current_exe().map(|exe| exe.with_file_name("pulseedit-watch.exe"));
+    if let Err(e) = cli.and_then(|cli| std::process::Command::new(cli).args(paths).spawn()) {
+        #[cfg(feature = "gui")]
+        rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Error)
+            .set_title("Pulse Graph Editor")
+            .set_description(format!("Couldn't start pulseedit-watch: {e}"))
+            .show();
+        std::process::exit(1);
+    }
+}
+
+#[cfg(feature = "gui")]
+fn run_editor(files: Vec<PathBuf>) {
     let d = eframe::icon_data::from_png_bytes(include_bytes!("../icon.png"))
         .expect("The icon data must be valid");
 
@@ -25,49 +76,11 @@ fn main() {
         Box::new(|cc| {
             let graph_editor = PulseGraphEditor::new(cc);
             match graph_editor {
-                Ok(graph_editor) => Ok(Box::new(graph_editor)),
+                Ok(mut graph_editor) => {
+                    graph_editor.open_files(&files);
+                    Ok(Box::new(graph_editor))
+                }
                 Err(e) => Err(e.into())
             }
         }));
 }
-
-fn setup_panic_hook() {
-    use rfd::MessageDialog;
-    std::panic::set_hook(Box::new(move |panic_info| {
-        let panic_formatted = format!("{:#?}", panic_info);
-        let panic_payload_display = if let Some(s) = panic_info.payload().downcast_ref::<&str>() {
-            Some(s.to_string())
-        } else {
-            panic_info
-                .payload()
-                .downcast_ref::<String>()
-                .map(|s| s.to_owned())
-        };
-
-        let res = MessageDialog::new()
-            .set_level(rfd::MessageLevel::Error)
-            .set_title("Whoops!")
-            .set_description(format!(
-                "The editor has crashed due to an unhandled error.\n\n{}\n\n{}",
-                panic_formatted,
-                panic_payload_display
-                    .as_deref()
-                    .unwrap_or("<Panic payload unavailable>")
-            ))
-            .set_buttons(rfd::MessageButtons::OkCustom("Copy log".to_string()))
-            .show();
-
-        if let rfd::MessageDialogResult::Custom(_) = res {
-            let mut clipboard = arboard::Clipboard::new().unwrap();
-            clipboard
-                .set_text(format!(
-                    "Panic info:\n{}\n\nPanic payload:\n{}",
-                    panic_formatted,
-                    panic_payload_display
-                        .as_deref()
-                        .unwrap_or("<Panic payload unavailable>")
-                ))
-                .unwrap();
-        }
-    }));
-}
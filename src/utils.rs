@@ -51,6 +51,84 @@ pub fn get_nodes_connected_to_output<'a>(
     Ok(res)
 }
 
+// The rules the compiler follows the action wires by, also used to show the execution order in the editor.
+
+// nodes whose actions run from `outAction` when they're triggered or called, everything connected there runs.
+fn is_action_entry(template: PulseNodeTemplate) -> bool {
+    matches!(
+        template,
+        PulseNodeTemplate::EventHandler
+            | PulseNodeTemplate::CellPublicMethod
+            | PulseNodeTemplate::GraphHook
+            | PulseNodeTemplate::EntOutputHandler
+            | PulseNodeTemplate::Function
+            | PulseNodeTemplate::ListenForEntityOutput
+    )
+}
+
+// returns the action outputs of a node in the order their actions are compiled: branches, loop bodies and switch cases
+// as listed on the node, then the default case of a switch, which is listed before the cases, and `outAction` last.
+pub fn get_action_outputs<'a>(node: &'a Node<PulseNodeData>, graph: &PulseGraph) -> Vec<(&'a str, OutputId)> {
+    let mut outputs: Vec<(&str, OutputId)> = node
+        .outputs
+        .iter()
+        .filter(|(_, output)| graph.get_output(*output).typ == PulseDataType::Action)
+        .map(|(name, output)| (name.as_str(), *output))
+        .collect();
+    outputs.sort_by_key(|(name, _)| match *name {
+        "outAction" => 2,
+        "defaultcase" => 1,
+        _ => 0,
+    });
+    outputs
+}
+
+// whether every node connected to an action output runs, or only the first one.
+// Past entry points, a node carries on with only the first node connected to its `outAction`.
+// A reroute follows the rule of the output it hangs off, so that it doesn't change what runs.
+pub fn runs_every_connected_action(graph: &PulseGraph, node: &Node<PulseNodeData>, output_name: &str) -> bool {
+    if node.user_data.template == PulseNodeTemplate::Reroute {
+        let source = node.get_input("in").ok().and_then(|input| skip_reroutes(graph, graph.connection(input)));
+        let Some(source) = source else {
+            return true;
+        };
+        let source_node = &graph[graph.get_output(source).node];
+        let source_name = source_node.outputs.iter()
+            .find(|(_, id)| *id == source)
+            .map(|(name, _)| name.as_str())
+            .unwrap_or_default();
+        return runs_every_connected_action(graph, source_node, source_name);
+    }
+    output_name != "outAction" || is_action_entry(node.user_data.template)
+}
+
+// follows the connection back through reroute nodes, to the output that actually produces the value.
+pub fn skip_reroutes(graph: &PulseGraph, mut output: Option<OutputId>) -> Option<OutputId> {
+    // reroutes connected in a loop never reach a value, bounding the walk treats them as unconnected.
+    for _ in 0..graph.nodes.len() {
+        let node = &graph[graph.get_output(output?).node];
+        if node.user_data.template != PulseNodeTemplate::Reroute {
+            return output;
+        }
+        output = node.get_input("in").ok().and_then(|input| graph.connection(input));
+    }
+    None
+}
+
+// return list of pairs of the nodes that run after an action output and their corresponding input name
+pub fn get_next_action_nodes<'a>(
+    origin_node: &'a Node<PulseNodeData>,
+    graph: &'a PulseGraph,
+    name: &str,
+) -> anyhow::Result<Vec<(&'a Node<PulseNodeData>, &'a str)>> {
+    let mut res = get_nodes_connected_to_output(origin_node, graph, name)?;
+    if !runs_every_connected_action(graph, origin_node, name) {
+        res.truncate(1);
+    }
+    Ok(res)
+}
+
+#[cfg(feature = "gui")]
 pub fn get_node_ids_connected_to_output(
     origin_node: &Node<PulseNodeData>,
     graph: &PulseGraph,
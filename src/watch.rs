@@ -0,0 +1,157 @@
+// Watch mode, recompiles graphs when their files or the bindings change.
+// Used by the editor and the `--watch` command line flag.
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use std::fs;
+use crate::app::FullGraphState;
+#[cfg(feature = "nongame_asset_build")]
+use crate::app::types::EditorConfig;
+use crate::bindings::{load_bindings, GraphBindings, BINDINGS_MANIFEST_PATH};
+use crate::compiler::{compile_graph, CompileObserver};
+
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Detects changes of files by polling their modification times.
+/// Directories count as changed when any file inside of them changes.
+#[derive(Default)]
+pub struct FileWatcher {
+    watched: Vec<(PathBuf, Option<SystemTime>)>,
+    last_poll: Option<Instant>,
+}
+
+impl FileWatcher {
+    /// Sets the watched paths, keeping the last known state of ones that were already watched.
+    pub fn set_paths(&mut self, paths: &[PathBuf]) {
+        if self.watched.len() == paths.len()
+            && self.watched.iter().zip(paths).all(|((watched, _), path)| watched == path) {
+            return;
+        }
+        let mut watched = Vec::with_capacity(paths.len());
+        for path in paths {
+            let existing = self.watched.iter().find(|(p, _)| p == path);
+            watched.push(match existing {
+                Some(entry) => entry.clone(),
+                None => (path.clone(), modified_time(path)),
+            });
+        }
+        self.watched = watched;
+    }
+
+    #[cfg(feature = "gui")]
+    pub fn clear(&mut self) {
+        self.watched.clear();
+    }
+
+    /// Returns the paths that changed since the last poll.
+    /// Polls happening more often than `POLL_INTERVAL` are skipped.
+    pub fn poll_changes(&mut self) -> Vec<PathBuf> {
+        if self.last_poll.is_some_and(|t| t.elapsed() < POLL_INTERVAL) {
+            return vec![];
+        }
+        self.last_poll = Some(Instant::now());
+        let mut changed = vec![];
+        for (path, last_modified) in self.watched.iter_mut() {
+            let modified = modified_time(path);
+            if modified != *last_modified {
+                *last_modified = modified;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    let metadata = fs::metadata(path).ok()?;
+    let mut newest = metadata.modified().ok();
+    if metadata.is_dir() {
+        for entry in fs::read_dir(path).ok()?.flatten() {
+            newest = newest.max(modified_time(&entry.path()));
+        }
+    }
+    newest
+}
+
+pub fn bindings_dir() -> PathBuf {
+    Path::new(BINDINGS_MANIFEST_PATH)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+struct PrintObserver;
+impl CompileObserver for PrintObserver {
+    fn progress(&self, message: String) {
+        println!("  {message}");
+    }
+}
+
+fn print_line(text: &str) {
+    println!("{} {}", chrono::Local::now().format("[%H:%M:%S]"), text);
+}
+
+fn compile_file(
+    path: &Path,
+    bindings: &Arc<GraphBindings>,
+    #[cfg(feature = "nongame_asset_build")]
+    config: &EditorConfig,
+) {
+    print_line(&format!("Compiling {}", path.display()));
+    let mut state = FullGraphState::default();
+    state.user_state.bindings = bindings.clone();
+    if let Err(e) = state.load_state(&path.to_path_buf()) {
+        print_line(&format!("Failed to load {}: {e}", path.display()));
+        return;
+    }
+    match compile_graph(&state.state.graph, &state.user_state,
+        #[cfg(feature = "nongame_asset_build")] config,
+        &PrintObserver)
+    {
+        Ok(()) => print_line("Graph compiled successfully"),
+        Err(e) => print_line(&format!("Compile error: {e}")),
+    }
+}
+
+/// Compiles the given graph files, then keeps recompiling them whenever they or the bindings change.
+/// Runs until the process is terminated.
+pub fn run_watch_cli(graph_paths: &[PathBuf]) -> anyhow::Result<()> {
+    if graph_paths.is_empty() {
+        anyhow::bail!("No graph files were given to watch");
+    }
+    let mut bindings = Arc::new(load_bindings(Path::new(BINDINGS_MANIFEST_PATH))?);
+    #[cfg(feature = "nongame_asset_build")]
+    let config = EditorConfig::load_from_file(Path::new("config.json"))?;
+    for path in graph_paths {
+        compile_file(path, &bindings, #[cfg(feature = "nongame_asset_build")] &config);
+    }
+
+    let bindings_dir = bindings_dir();
+    let mut watched_paths = graph_paths.to_vec();
+    watched_paths.push(bindings_dir.clone());
+    let mut watcher = FileWatcher::default();
+    watcher.set_paths(&watched_paths);
+    print_line("Watching for changes, press Ctrl+C to stop.");
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let changed = watcher.poll_changes();
+        let to_compile: &[PathBuf] = if changed.contains(&bindings_dir) {
+            match load_bindings(Path::new(BINDINGS_MANIFEST_PATH)) {
+                Ok(new_bindings) => {
+                    print_line("Bindings changed, reloaded");
+                    bindings = Arc::new(new_bindings);
+                    graph_paths
+                }
+                Err(e) => {
+                    print_line(&format!("Failed to reload bindings: {e:#}"));
+                    continue;
+                }
+            }
+        } else {
+            &changed
+        };
+        for path in to_compile {
+            compile_file(path, &bindings, #[cfg(feature = "nongame_asset_build")] &config);
+        }
+    }
+}